@@ -0,0 +1,238 @@
+use std::sync::Arc;
+
+use actix_web::{
+    Error, HttpResponse,
+    web::{self, Query},
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+use crate::{
+    automation::{
+        AutomationSwitchClient, AutomationSwitchTarget,
+        adapter::db::PlanningTraceRepository,
+        planner::{
+            CommandTimelineEntry, PlanningStepStatus, ResourcePlanningTrace, dry_run_for_home, explain, replay_for_home,
+        },
+        resource_plans,
+    },
+    command::CommandClient,
+    core::time::{DateTime, DateTimeRange},
+    home_state::{HomeStateClient, StateSnapshot},
+    t,
+};
+
+#[derive(Clone)]
+pub struct AutomationApi {
+    latest_snapshot: watch::Receiver<Option<StateSnapshot>>,
+    command_client: CommandClient,
+    home_state_client: HomeStateClient,
+    trace_repo: Arc<PlanningTraceRepository>,
+    switch_client: AutomationSwitchClient,
+}
+
+impl AutomationApi {
+    pub fn new(
+        latest_snapshot: watch::Receiver<Option<StateSnapshot>>,
+        command_client: CommandClient,
+        home_state_client: HomeStateClient,
+        trace_repo: PlanningTraceRepository,
+        switch_client: AutomationSwitchClient,
+    ) -> Self {
+        Self {
+            latest_snapshot,
+            command_client,
+            home_state_client,
+            trace_repo: Arc::new(trace_repo),
+            switch_client,
+        }
+    }
+
+    pub fn routes(&self) -> actix_web::Scope {
+        web::scope("/automation")
+            .route("/plan/dry-run", web::get().to(dry_run_handler))
+            .route("/traces", web::get().to(traces_handler))
+            .route("/replay", web::get().to(replay_handler))
+            .route("/explain", web::get().to(explain_handler))
+            .route("/switches", web::get().to(switches_handler))
+            .route("/switches", web::post().to(set_switch_handler))
+            .app_data(web::Data::new(self.clone()))
+    }
+}
+
+async fn dry_run_handler(api: web::Data<AutomationApi>) -> Result<HttpResponse, Error> {
+    let snapshot = api.latest_snapshot.borrow().clone();
+
+    let Some(snapshot) = snapshot else {
+        return Ok(HttpResponse::ServiceUnavailable().body("No home state snapshot available yet"));
+    };
+
+    let result = dry_run_for_home(&snapshot, &api.switch_client.current(), &api.command_client)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error during dry-run planning: {}", e)))?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct TraceQuery {
+    from: DateTime,
+    to: DateTime,
+    #[serde(default)]
+    resource: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct TraceDto {
+    timestamp: DateTime,
+    resource: String,
+    winner: Option<String>,
+    correlation_id: Option<String>,
+    trace_id: Option<String>,
+    steps: Vec<TraceStepDto>,
+}
+
+#[derive(Debug, Serialize)]
+struct TraceStepDto {
+    action: String,
+    status: PlanningStepStatus,
+    triggered: Option<bool>,
+    error: Option<String>,
+}
+
+impl From<ResourcePlanningTrace> for TraceDto {
+    fn from(trace: ResourcePlanningTrace) -> Self {
+        Self {
+            timestamp: trace.timestamp,
+            winner: trace.winner().map(|step| step.action.clone()),
+            correlation_id: trace.correlation_id.as_ref().map(|id| id.to_string()),
+            trace_id: trace.correlation_id.as_ref().map(|id| id.trace_id()),
+            resource: trace.resource,
+            steps: trace
+                .steps
+                .into_iter()
+                .map(|step| TraceStepDto {
+                    status: step.status(),
+                    action: step.action,
+                    triggered: step.triggered,
+                    error: step.error,
+                })
+                .collect(),
+        }
+    }
+}
+
+async fn traces_handler(api: web::Data<AutomationApi>, query: Query<TraceQuery>) -> Result<HttpResponse, Error> {
+    let range = DateTimeRange::new(query.from, query.to).non_future();
+
+    let traces = api
+        .trace_repo
+        .query_traces(query.resource.as_deref(), &range)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error querying planning traces: {}", e)))?;
+
+    let dtos: Vec<TraceDto> = traces.into_iter().map(TraceDto::from).collect();
+
+    Ok(HttpResponse::Ok().json(dtos))
+}
+
+#[derive(Debug, Serialize)]
+struct ReplayDto {
+    replayed: Vec<CommandTimelineEntry>,
+    actual: Vec<CommandTimelineEntry>,
+}
+
+async fn replay_handler(api: web::Data<AutomationApi>, query: Query<TraceQuery>) -> Result<HttpResponse, Error> {
+    let range = DateTimeRange::new(query.from, query.to).non_future();
+    let matches_resource = |entry: &CommandTimelineEntry| match &query.resource {
+        Some(resource) => entry.resource.to_string() == *resource,
+        None => true,
+    };
+
+    let replayed = replay_for_home(range.clone(), &api.home_state_client)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error replaying automation rules: {}", e)))?;
+
+    let actual = api
+        .command_client
+        .get_all_commands(*range.start(), *range.end())
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error querying commands: {}", e)))?;
+
+    Ok(HttpResponse::Ok().json(ReplayDto {
+        replayed: replayed.into_iter().filter(matches_resource).collect(),
+        actual: actual
+            .into_iter()
+            .filter(|cmd| range.contains(&cmd.created))
+            .map(CommandTimelineEntry::from)
+            .filter(matches_resource)
+            .collect(),
+    }))
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ExplainQuery {
+    resource: String,
+}
+
+async fn explain_handler(api: web::Data<AutomationApi>, query: Query<ExplainQuery>) -> Result<HttpResponse, Error> {
+    let Some((resource, rules)) = resource_plans()
+        .into_iter()
+        .find(|(resource, _)| resource.to_string() == query.resource)
+    else {
+        return Err(actix_web::error::ErrorNotFound(format!(
+            "Unknown resource {}",
+            query.resource
+        )));
+    };
+
+    let trace = api
+        .trace_repo
+        .latest_trace(&query.resource)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error querying planning trace: {}", e)))?;
+
+    let latest_command = api
+        .command_client
+        .get_latest_command(resource.clone(), t!(now) - t!(30 days))
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error querying latest command: {}", e)))?;
+
+    Ok(HttpResponse::Ok().json(explain(&resource, &rules, trace, latest_command)))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SwitchDto {
+    target: AutomationSwitchTarget,
+    enabled: bool,
+}
+
+async fn switches_handler(api: web::Data<AutomationApi>) -> Result<HttpResponse, Error> {
+    let switches = api.switch_client.current();
+
+    let mut dtos: Vec<SwitchDto> = switches
+        .disabled()
+        .map(|target| SwitchDto {
+            target: target.clone(),
+            enabled: false,
+        })
+        .collect();
+    dtos.sort_by_key(|dto| dto.target.to_string());
+
+    Ok(HttpResponse::Ok().json(dtos))
+}
+
+async fn set_switch_handler(api: web::Data<AutomationApi>, body: web::Json<SwitchDto>) -> Result<HttpResponse, Error> {
+    let SwitchDto { target, enabled } = body.into_inner();
+
+    if !target.is_planned() {
+        return Err(actix_web::error::ErrorBadRequest(format!("Unknown target {}", target)));
+    }
+
+    api.switch_client
+        .set_enabled(target.clone(), enabled)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error updating automation switch: {}", e)))?;
+
+    Ok(HttpResponse::Ok().json(SwitchDto { target, enabled }))
+}
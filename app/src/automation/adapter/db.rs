@@ -0,0 +1,270 @@
+use anyhow::{Context as _, Result};
+use sqlx::PgPool;
+
+use crate::{
+    automation::{
+        AutomationSwitchTarget,
+        planner::{PlanningTraceStep, ResourcePlanningTrace},
+    },
+    core::time::DateTimeRange,
+};
+
+#[derive(Debug, Clone)]
+pub struct PlanningTraceRepository {
+    pool: PgPool,
+}
+
+impl PlanningTraceRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    #[tracing::instrument(skip_all, fields(resource = %trace.resource))]
+    pub async fn insert_trace(&self, trace: &ResourcePlanningTrace) -> Result<()> {
+        let steps = serde_json::to_value(&trace.steps)?;
+
+        sqlx::query!(
+            r#"INSERT INTO planning_trace (timestamp, resource, correlation_id, steps) VALUES ($1, $2, $3, $4)"#,
+            trace.timestamp.into_db(),
+            trace.resource,
+            trace.correlation_id.as_ref().map(|id| id.to_string()),
+            steps,
+        )
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .context("Error inserting planning trace")
+    }
+
+    /// Traces recorded in range, including the latest one before the range per resource,
+    /// as it was still valid at the start of the range.
+    pub async fn query_traces(
+        &self,
+        resource: Option<&str>,
+        range: &DateTimeRange,
+    ) -> Result<Vec<ResourcePlanningTrace>> {
+        let records = sqlx::query!(
+            r#"(SELECT id as "id!", timestamp as "timestamp!", resource as "resource!", correlation_id, steps as "steps!"
+                FROM planning_trace
+                WHERE (resource = $1 OR $1 IS NULL)
+                AND timestamp >= $2
+                AND timestamp <= $3)
+            UNION ALL
+            (SELECT DISTINCT ON (resource) id, timestamp, resource, correlation_id, steps
+                FROM planning_trace
+                WHERE (resource = $1 OR $1 IS NULL)
+                AND timestamp < $2
+                ORDER BY resource, timestamp DESC)
+            ORDER BY 2 ASC, 1 ASC"#,
+            resource,
+            range.start().into_db(),
+            range.end().into_db(),
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let traces = records
+            .into_iter()
+            .filter_map(
+                |row| match serde_json::from_value::<Vec<PlanningTraceStep>>(row.steps) {
+                    Ok(steps) => Some(ResourcePlanningTrace {
+                        timestamp: row.timestamp.into(),
+                        resource: row.resource,
+                        correlation_id: row.correlation_id.map(|id| id.into()),
+                        steps,
+                    }),
+                    Err(e) => {
+                        tracing::warn!(
+                            "Error mapping planning trace with id {} from database, ignoring: {}",
+                            row.id,
+                            e
+                        );
+                        None
+                    }
+                },
+            )
+            .collect();
+
+        Ok(traces)
+    }
+
+    pub async fn latest_trace(&self, resource: &str) -> Result<Option<ResourcePlanningTrace>> {
+        let row = sqlx::query!(
+            r#"SELECT id, timestamp, resource, correlation_id, steps
+                FROM planning_trace
+                WHERE resource = $1
+                ORDER BY timestamp DESC, id DESC
+                LIMIT 1"#,
+            resource,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let steps = serde_json::from_value::<Vec<PlanningTraceStep>>(row.steps)
+            .with_context(|| format!("Error mapping planning trace with id {} from database", row.id))?;
+
+        Ok(Some(ResourcePlanningTrace {
+            timestamp: row.timestamp.into(),
+            resource: row.resource,
+            correlation_id: row.correlation_id.map(|id| id.into()),
+            steps,
+        }))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AutomationSwitchRepository {
+    pool: PgPool,
+}
+
+impl AutomationSwitchRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get_disabled(&self) -> Result<Vec<AutomationSwitchTarget>> {
+        let records = sqlx::query!(r#"SELECT kind, name FROM automation_switch WHERE NOT enabled"#)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(records
+            .into_iter()
+            .filter_map(|row| {
+                let target = AutomationSwitchTarget::from_db(&row.kind, row.name);
+                if target.is_none() {
+                    tracing::warn!("Unknown automation switch kind {}, ignoring", row.kind);
+                }
+                target
+            })
+            .collect())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn set_enabled(&self, target: &AutomationSwitchTarget, enabled: bool) -> Result<()> {
+        sqlx::query!(
+            r#"INSERT INTO automation_switch (kind, name, enabled, updated_at) VALUES ($1, $2, $3, now())
+               ON CONFLICT (kind, name) DO UPDATE SET enabled = EXCLUDED.enabled, updated_at = EXCLUDED.updated_at"#,
+            target.kind(),
+            target.name(),
+            enabled,
+        )
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .context("Error saving automation switch")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{core::time::DateTime, t};
+
+    fn trace(resource: &str, action: &str, timestamp: DateTime) -> ResourcePlanningTrace {
+        let mut step = PlanningTraceStep::new(&action, &resource);
+        step.fulfilled = Some(true);
+
+        ResourcePlanningTrace {
+            timestamp,
+            resource: resource.to_string(),
+            correlation_id: None,
+            steps: vec![step],
+        }
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn query_includes_latest_trace_before_range_per_resource(db_pool: PgPool) -> anyhow::Result<()> {
+        let repo = PlanningTraceRepository::new(db_pool);
+
+        for trace in [
+            trace(
+                "SetPower[Dehumidifier]",
+                "BlockAutomation::BathroomDehumidifier",
+                t!(3 hours ago),
+            ),
+            trace("SetPower[Dehumidifier]", "Dehumidify::Bathroom", t!(2 hours ago)),
+            trace(
+                "SetPower[Dehumidifier]",
+                "FollowDefaultSetting::SetPower",
+                t!(30 minutes ago),
+            ),
+            trace(
+                "ControlFan[BedroomDehumidifier]",
+                "Dehumidify::Bedroom",
+                t!(20 minutes ago),
+            ),
+        ] {
+            repo.insert_trace(&trace).await?;
+        }
+
+        let range = DateTimeRange::new(t!(1 hours ago), t!(now));
+
+        let all = repo.query_traces(None, &range).await?;
+        let actions = all.iter().map(|t| t.steps[0].action.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            actions,
+            vec![
+                "Dehumidify::Bathroom",
+                "FollowDefaultSetting::SetPower",
+                "Dehumidify::Bedroom"
+            ]
+        );
+
+        let fan_only = repo
+            .query_traces(Some("ControlFan[BedroomDehumidifier]"), &range)
+            .await?;
+        assert_eq!(fan_only.len(), 1);
+        assert_eq!(
+            fan_only[0].winner().map(|s| s.action.as_str()),
+            Some("Dehumidify::Bedroom")
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn latest_trace_keeps_step_inputs(db_pool: PgPool) -> anyhow::Result<()> {
+        let repo = PlanningTraceRepository::new(db_pool);
+
+        let mut latest = trace("SetPower[Dehumidifier]", "Dehumidify::Bathroom", t!(10 minutes ago));
+        latest.steps[0]
+            .attributes
+            .insert("mould_risk".to_string(), "true".to_string());
+
+        repo.insert_trace(&trace(
+            "SetPower[Dehumidifier]",
+            "FollowDefaultSetting::SetPower",
+            t!(1 hours ago),
+        ))
+        .await?;
+        repo.insert_trace(&latest).await?;
+
+        let result = repo.latest_trace("SetPower[Dehumidifier]").await?;
+        assert_eq!(
+            result.as_ref().and_then(|t| t.winner()).map(|s| s.attributes.clone()),
+            Some(latest.steps[0].attributes.clone())
+        );
+        assert!(repo.latest_trace("ControlFan[BedroomDehumidifier]").await?.is_none());
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn only_disabled_switches_are_returned(db_pool: PgPool) -> anyhow::Result<()> {
+        let repo = AutomationSwitchRepository::new(db_pool);
+        let action = AutomationSwitchTarget::Action("dehumidify::bedroom".to_string());
+        let resource = AutomationSwitchTarget::Resource("ControlFan[LivingRoomAirPurifier]".to_string());
+
+        repo.set_enabled(&action, false).await?;
+        repo.set_enabled(&resource, false).await?;
+        repo.set_enabled(&resource, true).await?;
+
+        assert_eq!(repo.get_disabled().await?, vec![action]);
+
+        Ok(())
+    }
+}
@@ -4,7 +4,7 @@ use crate::automation::RuleEvaluationContext;
 use crate::core::unit::FanAirflow;
 use crate::{
     automation::domain::action::{Rule, RuleResult},
-    command::{Command, Fan, PowerToggle},
+    command::{Command, CommandTarget, Fan, PowerToggle},
     home_state::FanActivity,
     home_state::Resident,
     t,
@@ -17,6 +17,19 @@ pub enum BlockAutomation {
     BedroomDehumidifier,
 }
 
+impl BlockAutomation {
+    pub fn command_target(&self) -> CommandTarget {
+        match self {
+            BlockAutomation::BathroomDehumidifier => CommandTarget::SetPower {
+                device: PowerToggle::Dehumidifier,
+            },
+            BlockAutomation::BedroomDehumidifier => CommandTarget::ControlFan {
+                device: Fan::BedroomDehumidifier,
+            },
+        }
+    }
+}
+
 impl Rule for BlockAutomation {
     fn evaluate(&self, ctx: &RuleEvaluationContext) -> anyhow::Result<RuleResult> {
         let sleeping_start = {
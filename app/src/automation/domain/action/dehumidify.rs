@@ -1,6 +1,6 @@
 use super::{RuleEvaluationContext, SimpleRule};
 use crate::{
-    command::{Command, Fan, PowerToggle},
+    command::{Command, CommandTarget, Fan, PowerToggle},
     core::domain::{HeatingZone, Room, RoomWithWindow},
     core::unit::{
         DegreeCelsius,
@@ -22,6 +22,19 @@ pub enum Dehumidify {
     Bedroom,
 }
 
+impl Dehumidify {
+    pub fn command_target(&self) -> CommandTarget {
+        match self {
+            Dehumidify::Bathroom => CommandTarget::SetPower {
+                device: PowerToggle::Dehumidifier,
+            },
+            Dehumidify::Bedroom => CommandTarget::ControlFan {
+                device: Fan::BedroomDehumidifier,
+            },
+        }
+    }
+}
+
 impl SimpleRule for Dehumidify {
     fn command(&self) -> Command {
         match self {
@@ -11,11 +11,16 @@ impl FollowDefaultSetting {
     pub fn new(target: CommandTarget) -> Self {
         Self(target)
     }
-}
 
-impl Rule for FollowDefaultSetting {
-    fn evaluate(&self, _: &RuleEvaluationContext) -> anyhow::Result<RuleResult> {
-        tracing::info!("Applying default setting");
+    pub fn command_target(&self) -> CommandTarget {
+        self.0.clone()
+    }
+
+    pub fn has_default(&self) -> bool {
+        self.default_command().is_some()
+    }
+
+    fn default_command(&self) -> Option<Command> {
         let command = match self.0.clone() {
             CommandTarget::SetPower { device } => Command::SetPower {
                 device,
@@ -38,12 +43,25 @@ impl Rule for FollowDefaultSetting {
                 device,
                 target_state: crate::command::HeatingTargetState::Off,
             },
-            CommandTarget::OpenDoor { .. } => {
-                tracing::warn!("No default setting defined for OpenDoor, skipping");
-                return Ok(RuleResult::Skip);
-            }
+            //Locking must be an explicit decision
+            CommandTarget::OpenDoor { .. } | CommandTarget::SetLock { .. } => return None,
         };
 
-        Ok(RuleResult::Execute(command))
+        Some(command)
+    }
+}
+
+impl Rule for FollowDefaultSetting {
+    fn evaluate(&self, _: &RuleEvaluationContext) -> anyhow::Result<RuleResult> {
+        match self.default_command() {
+            Some(command) => {
+                tracing::info!("Applying default setting");
+                Ok(RuleResult::Execute(command))
+            }
+            None => {
+                tracing::warn!("No default setting defined for {}, skipping", self.0);
+                Ok(RuleResult::Skip)
+            }
+        }
     }
 }
@@ -1,7 +1,7 @@
 use r#macro::{EnumVariants, Id};
 
 use super::{Rule, RuleEvaluationContext, RuleResult};
-use crate::command::{Command, PowerToggle};
+use crate::command::{Command, CommandTarget, PowerToggle};
 use crate::core::time::Duration;
 use crate::home_state::PowerAvailable;
 use crate::t;
@@ -11,6 +11,16 @@ pub enum AutoTurnOff {
     IrHeater,
 }
 
+impl AutoTurnOff {
+    pub fn command_target(&self) -> CommandTarget {
+        match self {
+            AutoTurnOff::IrHeater => CommandTarget::SetPower {
+                device: PowerToggle::InfraredHeater,
+            },
+        }
+    }
+}
+
 impl Rule for AutoTurnOff {
     fn evaluate(&self, ctx: &RuleEvaluationContext) -> anyhow::Result<RuleResult> {
         let should_turn_off = match self {
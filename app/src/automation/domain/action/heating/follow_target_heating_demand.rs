@@ -1,5 +1,5 @@
 use crate::{
-    command::{Command, HeatingTargetState},
+    command::{Command, CommandTarget, HeatingTargetState},
     core::domain::Radiator,
     core::unit::DegreeCelsius,
     home_state::{HeatingDemandLimit, HeatingMode, SetPoint, TargetHeatingMode},
@@ -17,6 +17,10 @@ impl FollowTargetHeatingDemand {
     pub fn new(radiator: Radiator) -> Self {
         Self { radiator }
     }
+
+    pub fn command_target(&self) -> CommandTarget {
+        CommandTarget::SetHeating { device: self.radiator }
+    }
 }
 
 impl Rule for FollowTargetHeatingDemand {
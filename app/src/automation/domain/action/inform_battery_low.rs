@@ -0,0 +1,70 @@
+use r#macro::{EnumVariants, Id};
+
+use super::{Rule, RuleEvaluationContext, RuleResult};
+use crate::command::{Command, CommandTarget, Notification, NotificationAction, NotificationRecipient};
+use crate::core::unit::Percent;
+use crate::home_state::{BatteryLevel, LOW_BATTERY_LEVEL};
+
+#[derive(Debug, Clone, Id, EnumVariants)]
+pub enum InformBatteryLow {
+    PushNotification(NotificationRecipient),
+}
+
+impl InformBatteryLow {
+    pub fn command_target(&self) -> CommandTarget {
+        match self {
+            InformBatteryLow::PushNotification(recipient) => CommandTarget::PushNotify {
+                recipient: recipient.clone(),
+                notification: Notification::BatteryLow,
+            },
+        }
+    }
+}
+
+impl Rule for InformBatteryLow {
+    fn evaluate(&self, ctx: &RuleEvaluationContext) -> anyhow::Result<RuleResult> {
+        //not every device reported its battery yet, missing levels are no reason to skip
+        let levels: Vec<(BatteryLevel, Percent)> = BatteryLevel::variants()
+            .into_iter()
+            .filter_map(|item| ctx.current(item).ok().map(|level| (item, level)))
+            .collect();
+
+        let low = low_batteries(&levels);
+        if low.is_empty() {
+            return Ok(RuleResult::Skip);
+        }
+
+        tracing::info!("Battery low for {:?}", low);
+
+        let InformBatteryLow::PushNotification(recipient) = self;
+        Ok(RuleResult::Execute(Command::PushNotify {
+            action: NotificationAction::Notify,
+            notification: Notification::BatteryLow,
+            recipient: recipient.clone(),
+        }))
+    }
+}
+
+fn low_batteries(levels: &[(BatteryLevel, Percent)]) -> Vec<BatteryLevel> {
+    levels
+        .iter()
+        .filter(|(_, level)| *level < LOW_BATTERY_LEVEL)
+        .map(|(item, _)| *item)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_levels_below_threshold_are_low() {
+        let levels = vec![
+            (BatteryLevel::KitchenWindowSensor, Percent(19.0)),
+            (BatteryLevel::BedroomClimateSensor, Percent(20.0)),
+            (BatteryLevel::ApartmentDoorLock, Percent(80.0)),
+        ];
+
+        assert_eq!(low_batteries(&levels), vec![BatteryLevel::KitchenWindowSensor]);
+    }
+}
@@ -1,7 +1,7 @@
 use r#macro::{EnumVariants, Id};
 
 use super::{Rule, RuleEvaluationContext, RuleResult};
-use crate::command::{Command, Notification, NotificationAction, NotificationRecipient, PowerToggle};
+use crate::command::{Command, CommandTarget, Notification, NotificationAction, NotificationRecipient, PowerToggle};
 use crate::core::domain::RoomWithWindow;
 use crate::core::time::DateTime;
 use crate::core::timeseries::DataPoint;
@@ -16,6 +16,20 @@ pub enum InformWindowOpen {
     NotificationLightLivingRoom,
 }
 
+impl InformWindowOpen {
+    pub fn command_target(&self) -> CommandTarget {
+        match self {
+            InformWindowOpen::PushNotification(recipient) => CommandTarget::PushNotify {
+                recipient: recipient.clone(),
+                notification: Notification::WindowOpened,
+            },
+            InformWindowOpen::NotificationLightLivingRoom => CommandTarget::SetPower {
+                device: PowerToggle::LivingRoomNotificationLight,
+            },
+        }
+    }
+}
+
 impl Rule for InformWindowOpen {
     fn evaluate(&self, ctx: &RuleEvaluationContext) -> anyhow::Result<super::RuleResult> {
         let command = match self {
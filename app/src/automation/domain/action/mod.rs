@@ -2,16 +2,19 @@ mod block_automation;
 mod dehumidify;
 mod follow_default_setting;
 mod heating;
+mod inform_battery_low;
 mod inform_window_open;
 mod purify_air;
 mod remote_turn_off;
 mod user_trigger_action;
 
+use std::cell::RefCell;
 use std::fmt::Debug;
 use std::fmt::Display;
 
-use crate::command::Command;
+use crate::command::{Command, CommandTarget};
 use crate::core::id::ExternalId;
+use crate::core::time::{DateTime, Duration};
 use crate::core::timeseries::DataPoint;
 use crate::trigger::UserTriggerExecution;
 use crate::trigger::UserTriggerId;
@@ -22,6 +25,7 @@ pub use block_automation::BlockAutomation;
 pub use dehumidify::Dehumidify;
 pub use follow_default_setting::FollowDefaultSetting;
 pub use heating::*;
+pub use inform_battery_low::InformBatteryLow;
 pub use inform_window_open::InformWindowOpen;
 pub use purify_air::PurifyAir;
 pub use remote_turn_off::RemoteTurnOff;
@@ -44,6 +48,19 @@ pub struct RuleEvaluationContext {
     snapshot: StateSnapshot,
 }
 
+/// Home state value a rule read during evaluation
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct StateRead {
+    pub state: String,
+    pub value: String,
+    pub timestamp: DateTime,
+    pub age: Duration,
+}
+
+thread_local! {
+    static CAPTURED_STATE_READS: RefCell<Option<Vec<StateRead>>> = const { RefCell::new(None) };
+}
+
 impl RuleEvaluationContext {
     pub fn new(snapshot: StateSnapshot) -> Self {
         Self { snapshot }
@@ -53,6 +70,15 @@ impl RuleEvaluationContext {
         &self.snapshot
     }
 
+    /// Runs `f` and additionally returns the home state values read via `current_dp` in the meantime,
+    /// each state only once. Rule evaluation is synchronous, so reads happen on the calling thread.
+    pub fn capture_state_reads<R>(f: impl FnOnce() -> R) -> (R, Vec<StateRead>) {
+        let outer = CAPTURED_STATE_READS.replace(Some(Vec::new()));
+        let result = f();
+        let reads = CAPTURED_STATE_READS.replace(outer).unwrap_or_default();
+        (result, reads)
+    }
+
     pub fn current_dp<S>(&self, id: S) -> Result<DataPoint<S::Type>>
     where
         S: Into<HomeStateId> + HomeStateItem + Clone + Into<ExternalId>,
@@ -75,6 +101,7 @@ impl RuleEvaluationContext {
             .ok_or_else(|| anyhow::anyhow!("Current value for state {:?} not found", ext_id));
 
         if let Ok(ref dp) = result {
+            capture_state_read(&ext_id, dp);
             span.record("otel.name", format!("{} - {}", ext_id, dp.value));
             span.record("dp.value", dp.value.to_string());
             span.record("dp.timestamp", dp.timestamp.to_iso_string());
@@ -121,6 +148,26 @@ impl RuleEvaluationContext {
     }
 }
 
+fn capture_state_read<T: Display>(ext_id: &ExternalId, dp: &DataPoint<T>) {
+    CAPTURED_STATE_READS.with_borrow_mut(|reads| {
+        let Some(reads) = reads else {
+            return;
+        };
+
+        let state = ext_id.to_string();
+        if reads.iter().any(|read| read.state == state) {
+            return;
+        }
+
+        reads.push(StateRead {
+            state,
+            value: dp.value.to_string(),
+            timestamp: dp.timestamp,
+            age: dp.timestamp.elapsed(),
+        });
+    });
+}
+
 pub trait Rule {
     fn evaluate(&self, ctx: &RuleEvaluationContext) -> Result<RuleResult>;
 }
@@ -146,6 +193,7 @@ impl<T: SimpleRule> Rule for T {
 pub enum HomeAction {
     Dehumidify(Dehumidify),
     InformWindowOpen(InformWindowOpen),
+    InformBatteryLow(InformBatteryLow),
     PurifyAir(PurifyAir),
     AutoTurnOff(AutoTurnOff),
     FollowDefaultSetting(FollowDefaultSetting),
@@ -167,6 +215,7 @@ impl HomeAction {
         match self {
             HomeAction::Dehumidify(r) => (r, r.ext_id()),
             HomeAction::InformWindowOpen(r) => (r, r.ext_id()),
+            HomeAction::InformBatteryLow(r) => (r, r.ext_id()),
             HomeAction::PurifyAir(r) => (r, r.ext_id()),
             HomeAction::AutoTurnOff(r) => (r, r.ext_id()),
             HomeAction::FollowDefaultSetting(r) => (r, r.ext_id()),
@@ -191,4 +240,29 @@ impl HomeAction {
     pub fn ext_id(&self) -> ExternalId {
         self.as_rule().1
     }
+
+    /// Target of the commands this action can produce, if known upfront.
+    pub fn command_target(&self) -> Option<CommandTarget> {
+        match self {
+            HomeAction::Dehumidify(r) => Some(r.command_target()),
+            HomeAction::InformWindowOpen(r) => Some(r.command_target()),
+            HomeAction::InformBatteryLow(r) => Some(r.command_target()),
+            HomeAction::PurifyAir(r) => Some(r.command_target()),
+            HomeAction::AutoTurnOff(r) => Some(r.command_target()),
+            HomeAction::FollowDefaultSetting(r) => Some(r.command_target()),
+            HomeAction::UserTriggerAction(r) => r.command_target(),
+            HomeAction::FollowTargetHeatingDemand(r) => Some(r.command_target()),
+            HomeAction::BlockAutomation(r) => Some(r.command_target()),
+            HomeAction::RemoteTurnOff(r) => Some(r.command_target()),
+        }
+    }
+
+    /// Never skips, so no lower-priority rule of the same resource can win.
+    pub fn is_unconditional(&self) -> bool {
+        match self {
+            HomeAction::FollowDefaultSetting(r) => r.has_default(),
+            HomeAction::FollowTargetHeatingDemand(_) => true,
+            _ => false,
+        }
+    }
 }
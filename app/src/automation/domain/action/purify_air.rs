@@ -2,7 +2,7 @@ use r#macro::Id;
 
 use super::{Rule, RuleEvaluationContext, RuleResult};
 use crate::{
-    command::{Command, Fan},
+    command::{Command, CommandTarget, Fan},
     core::{
         domain::RoomWithWindow,
         timeseries::DataPoint,
@@ -17,6 +17,16 @@ pub enum PurifyAir {
     LivingRoom,
 }
 
+impl PurifyAir {
+    pub fn command_target(&self) -> CommandTarget {
+        match self {
+            PurifyAir::LivingRoom => CommandTarget::ControlFan {
+                device: Fan::LivingRoomAirPurifier,
+            },
+        }
+    }
+}
+
 impl Rule for PurifyAir {
     fn evaluate(&self, ctx: &RuleEvaluationContext) -> anyhow::Result<RuleResult> {
         let command = match self {
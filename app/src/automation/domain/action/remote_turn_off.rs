@@ -1,7 +1,7 @@
 use r#macro::{EnumVariants, Id};
 
 use super::{Rule, RuleEvaluationContext, RuleResult};
-use crate::command::{Command, Fan, PowerToggle};
+use crate::command::{Command, CommandTarget, Fan, PowerToggle};
 use crate::core::unit::FanAirflow;
 use crate::t;
 use crate::trigger::{DualButtonPress, RemoteTrigger, RemoteTriggerTarget, UserTrigger, UserTriggerTarget};
@@ -12,6 +12,19 @@ pub enum RemoteTurnOff {
     BedroomDehumidifier,
 }
 
+impl RemoteTurnOff {
+    pub fn command_target(&self) -> CommandTarget {
+        match self {
+            RemoteTurnOff::InfraredHeater => CommandTarget::SetPower {
+                device: PowerToggle::InfraredHeater,
+            },
+            RemoteTurnOff::BedroomDehumidifier => CommandTarget::ControlFan {
+                device: Fan::BedroomDehumidifier,
+            },
+        }
+    }
+}
+
 impl Rule for RemoteTurnOff {
     fn evaluate(&self, ctx: &RuleEvaluationContext) -> anyhow::Result<RuleResult> {
         let trigger_target = UserTriggerTarget::Remote(RemoteTriggerTarget::BedroomDoorRemote);
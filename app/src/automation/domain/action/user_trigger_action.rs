@@ -1,7 +1,7 @@
 use r#macro::Id;
 
 use super::{Rule, RuleEvaluationContext, RuleResult};
-use crate::command::Command;
+use crate::command::{Command, CommandTarget};
 use crate::core::domain::HeatingZone;
 use crate::core::time::Duration;
 use crate::home_state::{FanActivity, PowerAvailable};
@@ -34,8 +34,8 @@ impl Rule for UserTriggerAction {
             return Ok(RuleResult::Skip);
         };
 
-        if latest_trigger.timestamp.elapsed() > trigger_max_duration {
-            tracing::info!("Trigger older than {trigger_max_duration}, skipping");
+        if latest_trigger.is_expired(&trigger_max_duration) {
+            tracing::info!("Trigger expired (default duration {trigger_max_duration}), skipping");
             return Ok(RuleResult::Skip);
         }
 
@@ -81,11 +81,51 @@ impl UserTriggerAction {
             UserTriggerTarget::Heating(HeatingZone::Bathroom) => Some(t!(30 minutes)),
             UserTriggerTarget::Remote(RemoteTriggerTarget::BedroomDoorRemote) => Some(t!(60 minutes)),
             UserTriggerTarget::OpenDoor(_) => Some(t!(30 seconds)),
+            UserTriggerTarget::DoorLock(_) => Some(t!(1 minutes)),
+            //expanded into member triggers when loaded
+            UserTriggerTarget::Scene(_) => None,
+        }
+    }
+
+    /// `None` for targets that never produce a command here (e.g. handled via home state).
+    pub fn command_target(&self) -> Option<CommandTarget> {
+        use crate::command::*;
+
+        match &self.target {
+            UserTriggerTarget::DevicePower(OnOffDevice::InfraredHeater) => Some(CommandTarget::SetPower {
+                device: PowerToggle::InfraredHeater,
+            }),
+            UserTriggerTarget::DevicePower(OnOffDevice::Dehumidifier) => Some(CommandTarget::SetPower {
+                device: PowerToggle::Dehumidifier,
+            }),
+            UserTriggerTarget::DevicePower(OnOffDevice::LivingRoomTvEnergySaving) => {
+                Some(CommandTarget::SetEnergySaving {
+                    device: EnergySavingDevice::LivingRoomTv,
+                })
+            }
+            UserTriggerTarget::FanSpeed(FanActivity::BedroomDehumidifier) => Some(CommandTarget::ControlFan {
+                device: Fan::BedroomDehumidifier,
+            }),
+            UserTriggerTarget::FanSpeed(FanActivity::LivingRoomAirPurifier) => Some(CommandTarget::ControlFan {
+                device: Fan::LivingRoomAirPurifier,
+            }),
+            UserTriggerTarget::OpenDoor(Door::Building) => Some(CommandTarget::OpenDoor {
+                device: Lock::BuildingEntrance,
+            }),
+            UserTriggerTarget::DoorLock(Door::Apartment) => Some(CommandTarget::SetLock {
+                device: Lock::ApartmentDoor,
+            }),
+            UserTriggerTarget::OpenDoor(Door::Apartment) | UserTriggerTarget::DoorLock(Door::Building) => None,
+            UserTriggerTarget::Heating(_) | UserTriggerTarget::Remote(_) | UserTriggerTarget::Scene(_) => None,
         }
     }
 
     fn is_one_shot(&self) -> bool {
-        matches!(self.target, UserTriggerTarget::OpenDoor(_))
+        //Re-applying a lock trigger could lock out someone who unlocked manually in the meantime
+        matches!(
+            self.target,
+            UserTriggerTarget::OpenDoor(_) | UserTriggerTarget::DoorLock(_)
+        )
     }
 }
 
@@ -125,9 +165,20 @@ fn into_command(trigger: &UserTrigger) -> Option<Command> {
             tracing::info!("Heating state trigger handled elsewhere, skipping");
             None
         }
-        UserTrigger::Remote(RemoteTrigger::BedroomDoorRemote(_)) => None,
+        UserTrigger::Remote(RemoteTrigger::BedroomDoorRemote(_)) | UserTrigger::Scene { .. } => None,
         UserTrigger::OpenDoor { door: Door::Building } => Some(Command::OpenDoor {
             device: Lock::BuildingEntrance,
         }),
+        UserTrigger::DoorLock {
+            door: Door::Apartment,
+            locked,
+        } => Some(Command::SetLock {
+            device: Lock::ApartmentDoor,
+            locked,
+        }),
+        UserTrigger::OpenDoor { door: Door::Apartment }
+        | UserTrigger::DoorLock {
+            door: Door::Building, ..
+        } => None,
     }
 }
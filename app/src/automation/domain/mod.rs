@@ -1,5 +1,9 @@
 mod action;
+mod plan_validation;
 mod resource_plan;
+mod switch;
 
-pub use action::{HomeAction, RuleEvaluationContext};
+pub use action::{HomeAction, RuleEvaluationContext, StateRead};
+pub use plan_validation::check_resource_plans;
 pub use resource_plan::resource_plans;
+pub use switch::{AutomationSwitchTarget, AutomationSwitches};
@@ -0,0 +1,223 @@
+use std::collections::HashSet;
+
+use super::action::{FollowDefaultSetting, HomeAction};
+use crate::command::CommandTarget;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum PlanIssueSeverity {
+    #[display("error")]
+    Error,
+    #[display("warning")]
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, derive_more::Display)]
+#[display("[{severity}] {resource}: {message}")]
+pub struct PlanIssue {
+    pub severity: PlanIssueSeverity,
+    pub resource: CommandTarget,
+    pub message: String,
+}
+
+impl PlanIssue {
+    fn error(resource: &CommandTarget, message: impl Into<String>) -> Self {
+        Self {
+            severity: PlanIssueSeverity::Error,
+            resource: resource.clone(),
+            message: message.into(),
+        }
+    }
+
+    fn warning(resource: &CommandTarget, message: impl Into<String>) -> Self {
+        Self {
+            severity: PlanIssueSeverity::Warning,
+            resource: resource.clone(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Static checks of the resource plans. Rules can't be evaluated here, so only the declared
+/// command target and whether a rule is unconditional are taken into account.
+pub fn validate_resource_plans(
+    resource_plans: &[(CommandTarget, Vec<HomeAction>)],
+    executor_targets: &[CommandTarget],
+) -> Vec<PlanIssue> {
+    let mut issues = vec![];
+    let mut seen = HashSet::new();
+
+    for (resource, rules) in resource_plans {
+        if !seen.insert(resource) {
+            issues.push(PlanIssue::error(resource, "resource is planned more than once"));
+        }
+
+        if !executor_targets.contains(resource) {
+            issues.push(PlanIssue::error(resource, "no command executor configured"));
+        }
+
+        if rules.is_empty() {
+            issues.push(PlanIssue::error(resource, "no rules defined"));
+            continue;
+        }
+
+        for action in rules {
+            if let Some(target) = action.command_target()
+                && &target != resource
+            {
+                issues.push(PlanIssue::error(
+                    resource,
+                    format!("{action} produces commands for {target}"),
+                ));
+            }
+        }
+
+        if let Some(pos) = rules.iter().position(|action| action.is_unconditional()) {
+            for action in &rules[pos + 1..] {
+                issues.push(PlanIssue::error(
+                    resource,
+                    format!("{action} is unreachable after unconditional {}", rules[pos]),
+                ));
+            }
+        }
+
+        let has_fallback = rules.last().is_some_and(|action| action.is_unconditional());
+        if !has_fallback && FollowDefaultSetting::new(resource.clone()).has_default() {
+            issues.push(PlanIssue::warning(resource, "last rule is not a fallback rule"));
+        }
+    }
+
+    issues
+}
+
+/// Logs all issues of `resource_plans()` and fails if any of them is an error.
+pub fn check_resource_plans(executor_targets: &[CommandTarget]) -> anyhow::Result<()> {
+    let issues = validate_resource_plans(&super::resource_plans(), executor_targets);
+
+    for issue in &issues {
+        match issue.severity {
+            PlanIssueSeverity::Error => tracing::error!("Resource plan issue: {}", issue),
+            PlanIssueSeverity::Warning => tracing::warn!("Resource plan issue: {}", issue),
+        }
+    }
+
+    let errors = issues.iter().filter(|i| i.severity == PlanIssueSeverity::Error).count();
+    if errors > 0 {
+        anyhow::bail!("Resource plans contain {} error(s)", errors);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub fn assert_valid_resource_plans(
+    resource_plans: &[(CommandTarget, Vec<HomeAction>)],
+    executor_targets: &[CommandTarget],
+) {
+    let issues = validate_resource_plans(resource_plans, executor_targets);
+    assert!(
+        issues.is_empty(),
+        "Invalid resource plans:\n{}",
+        issues.iter().map(|i| i.to_string()).collect::<Vec<_>>().join("\n")
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automation::domain::action::{Dehumidify, PurifyAir, UserTriggerAction};
+    use crate::automation::domain::resource_plans;
+    use crate::command::{Fan, Lock, PowerToggle, executor_command_targets};
+    use crate::trigger::{Door, UserTriggerTarget};
+
+    fn dehumidifier() -> CommandTarget {
+        CommandTarget::SetPower {
+            device: PowerToggle::Dehumidifier,
+        }
+    }
+
+    fn messages(issues: &[PlanIssue]) -> Vec<(PlanIssueSeverity, String)> {
+        issues.iter().map(|i| (i.severity, i.message.clone())).collect()
+    }
+
+    #[test]
+    fn configured_resource_plans_are_valid() {
+        assert_valid_resource_plans(&resource_plans(), &executor_command_targets());
+    }
+
+    #[test]
+    fn missing_executor_is_reported() {
+        let plans = vec![(dehumidifier(), vec![FollowDefaultSetting::new(dehumidifier()).into()])];
+
+        let issues = validate_resource_plans(&plans, &[]);
+
+        assert_eq!(
+            messages(&issues),
+            vec![(PlanIssueSeverity::Error, "no command executor configured".to_string())]
+        );
+    }
+
+    #[test]
+    fn rules_after_unconditional_rule_are_unreachable() {
+        let plans = vec![(
+            dehumidifier(),
+            vec![
+                FollowDefaultSetting::new(dehumidifier()).into(),
+                Dehumidify::Bathroom.into(),
+            ],
+        )];
+
+        let issues = validate_resource_plans(&plans, &[dehumidifier()]);
+
+        assert_eq!(
+            messages(&issues),
+            vec![
+                (
+                    PlanIssueSeverity::Error,
+                    "dehumidify::bathroom is unreachable after unconditional follow_default_setting::set_power::dehumidifier"
+                        .to_string()
+                ),
+                (
+                    PlanIssueSeverity::Warning,
+                    "last rule is not a fallback rule".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn command_target_mismatch_is_reported() {
+        let plans = vec![(
+            dehumidifier(),
+            vec![
+                PurifyAir::LivingRoom.into(),
+                FollowDefaultSetting::new(dehumidifier()).into(),
+            ],
+        )];
+
+        let issues = validate_resource_plans(&plans, &[dehumidifier()]);
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, PlanIssueSeverity::Error);
+        assert!(
+            issues[0].message.contains(
+                &CommandTarget::ControlFan {
+                    device: Fan::LivingRoomAirPurifier
+                }
+                .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn open_door_needs_no_fallback() {
+        let target = CommandTarget::OpenDoor {
+            device: Lock::BuildingEntrance,
+        };
+        let plans = vec![(
+            target.clone(),
+            vec![UserTriggerAction::new(UserTriggerTarget::OpenDoor(Door::Building)).into()],
+        )];
+
+        assert!(validate_resource_plans(&plans, &[target]).is_empty());
+    }
+}
@@ -1,6 +1,6 @@
 use crate::automation::domain::action::{
     AutoTurnOff, BlockAutomation, Dehumidify, FollowDefaultSetting, FollowTargetHeatingDemand, HomeAction,
-    InformWindowOpen, PurifyAir, RemoteTurnOff, UserTriggerAction,
+    InformBatteryLow, InformWindowOpen, PurifyAir, RemoteTurnOff, UserTriggerAction,
 };
 use crate::command::{CommandTarget, EnergySavingDevice, Fan, Notification, NotificationRecipient, PowerToggle};
 use crate::core::domain::Radiator;
@@ -160,6 +160,20 @@ pub fn resource_plans() -> Vec<(CommandTarget, Vec<HomeAction>)> {
                 .into(),
             ],
         ),
+        (
+            CommandTarget::PushNotify {
+                recipient: NotificationRecipient::Dennis,
+                notification: Notification::BatteryLow,
+            },
+            vec![
+                InformBatteryLow::PushNotification(NotificationRecipient::Dennis).into(),
+                FollowDefaultSetting::new(CommandTarget::PushNotify {
+                    recipient: NotificationRecipient::Dennis,
+                    notification: Notification::BatteryLow,
+                })
+                .into(),
+            ],
+        ),
         // --- Door ---
         (
             CommandTarget::OpenDoor {
@@ -167,5 +181,11 @@ pub fn resource_plans() -> Vec<(CommandTarget, Vec<HomeAction>)> {
             },
             vec![UserTriggerAction::new(UserTriggerTarget::OpenDoor(Door::Building)).into()],
         ),
+        (
+            CommandTarget::SetLock {
+                device: crate::command::Lock::ApartmentDoor,
+            },
+            vec![UserTriggerAction::new(UserTriggerTarget::DoorLock(Door::Apartment)).into()],
+        ),
     ]
 }
@@ -0,0 +1,94 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::{HomeAction, resource_plans};
+use crate::command::CommandTarget;
+
+/// What an operator can disable: a single action or all actions of a resource.
+/// Identified by their display name, as used in the planning trace.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, derive_more::Display)]
+#[serde(rename_all = "snake_case")]
+pub enum AutomationSwitchTarget {
+    #[display("action {}", _0)]
+    Action(String),
+    #[display("resource {}", _0)]
+    Resource(String),
+}
+
+impl AutomationSwitchTarget {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AutomationSwitchTarget::Action(_) => "action",
+            AutomationSwitchTarget::Resource(_) => "resource",
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            AutomationSwitchTarget::Action(name) | AutomationSwitchTarget::Resource(name) => name,
+        }
+    }
+
+    pub fn from_db(kind: &str, name: String) -> Option<Self> {
+        match kind {
+            "action" => Some(AutomationSwitchTarget::Action(name)),
+            "resource" => Some(AutomationSwitchTarget::Resource(name)),
+            _ => None,
+        }
+    }
+
+    /// Whether the target refers to an action or resource of the current resource plans
+    pub fn is_planned(&self) -> bool {
+        resource_plans().iter().any(|(resource, rules)| match self {
+            AutomationSwitchTarget::Action(name) => rules.iter().any(|action| action.to_string() == *name),
+            AutomationSwitchTarget::Resource(name) => resource.to_string() == *name,
+        })
+    }
+}
+
+impl From<&CommandTarget> for AutomationSwitchTarget {
+    fn from(resource: &CommandTarget) -> Self {
+        AutomationSwitchTarget::Resource(resource.to_string())
+    }
+}
+
+impl From<&HomeAction> for AutomationSwitchTarget {
+    fn from(action: &HomeAction) -> Self {
+        AutomationSwitchTarget::Action(action.to_string())
+    }
+}
+
+/// Targets currently disabled by an operator. Everything else is enabled.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AutomationSwitches {
+    disabled: HashSet<AutomationSwitchTarget>,
+}
+
+impl AutomationSwitches {
+    pub fn new(disabled: impl IntoIterator<Item = AutomationSwitchTarget>) -> Self {
+        Self {
+            disabled: disabled.into_iter().collect(),
+        }
+    }
+
+    pub fn is_enabled(&self, target: &AutomationSwitchTarget) -> bool {
+        !self.disabled.contains(target)
+    }
+
+    pub fn is_action_disabled(&self, resource: &CommandTarget, action: &HomeAction) -> bool {
+        !self.is_enabled(&resource.into()) || !self.is_enabled(&action.into())
+    }
+
+    pub fn set_enabled(&mut self, target: AutomationSwitchTarget, enabled: bool) {
+        if enabled {
+            self.disabled.remove(&target);
+        } else {
+            self.disabled.insert(target);
+        }
+    }
+
+    pub fn disabled(&self) -> impl Iterator<Item = &AutomationSwitchTarget> {
+        self.disabled.iter()
+    }
+}
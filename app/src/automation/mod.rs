@@ -1,12 +1,23 @@
+mod adapter;
 pub mod domain;
 pub mod planner;
 
+pub use adapter::api::AutomationApi;
 pub use domain::*;
+use std::sync::Arc;
+
+use anyhow::Context as _;
 use infrastructure::EventListener;
+use sqlx::PgPool;
+use tokio::sync::watch;
 
 use crate::{
+    automation::{
+        adapter::db::{AutomationSwitchRepository, PlanningTraceRepository},
+        planner::PlanningTraceRecorder,
+    },
     command::CommandClient,
-    home_state::{HomeStateEvent, StateSnapshot},
+    home_state::{HomeStateClient, HomeStateEvent, StateSnapshot},
     trigger::TriggerClient,
 };
 
@@ -16,38 +27,119 @@ pub struct AutomationModule {
     home_state_rx: EventListener<HomeStateEvent>,
     command_client: CommandClient,
     trigger_client: TriggerClient,
+    home_state_client: HomeStateClient,
+    latest_snapshot: watch::Sender<Option<StateSnapshot>>,
+    trace_repo: PlanningTraceRepository,
+    trace_recorder: PlanningTraceRecorder,
+    switch_client: AutomationSwitchClient,
+}
+
+/// Operator switches to disable actions or resources at runtime
+#[derive(Clone)]
+pub struct AutomationSwitchClient {
+    repo: AutomationSwitchRepository,
+    switches: Arc<watch::Sender<AutomationSwitches>>,
 }
 
 impl AutomationModule {
-    pub fn new(
+    pub async fn new(
         home_state_rx: EventListener<HomeStateEvent>,
         command_client: CommandClient,
         trigger_client: TriggerClient,
-    ) -> Self {
-        Self {
+        home_state_client: HomeStateClient,
+        pool: PgPool,
+    ) -> anyhow::Result<Self> {
+        let trace_repo = PlanningTraceRepository::new(pool.clone());
+        let switch_client = AutomationSwitchClient::new(AutomationSwitchRepository::new(pool))
+            .await
+            .context("Error loading automation switches")?;
+
+        Ok(Self {
             home_state_rx,
             command_client,
             trigger_client,
-        }
+            home_state_client,
+            latest_snapshot: watch::Sender::new(None),
+            trace_recorder: PlanningTraceRecorder::new(trace_repo.clone()),
+            trace_repo,
+            switch_client,
+        })
+    }
+
+    pub fn switch_client(&self) -> AutomationSwitchClient {
+        self.switch_client.clone()
+    }
+
+    pub fn api(&self) -> AutomationApi {
+        AutomationApi::new(
+            self.latest_snapshot.subscribe(),
+            self.command_client.clone(),
+            self.home_state_client.clone(),
+            self.trace_repo.clone(),
+            self.switch_client.clone(),
+        )
     }
 
     pub async fn run(mut self) {
         let mut timer = tokio::time::interval(std::time::Duration::from_secs(30));
-        let mut last_snapshot: Option<StateSnapshot> = None;
 
         loop {
             tokio::select! {
                 _ = timer.tick() => {
+                    let last_snapshot = self.latest_snapshot.borrow().clone();
                     if let Some(snapshot) = &last_snapshot {
-                        plan_for_home(snapshot, &self.command_client, &self.trigger_client).await;
+                        self.plan(snapshot).await;
                     }
                 },
 
                 event = self.home_state_rx.recv() => if let Some(HomeStateEvent::SnapshotUpdated(new_snapshot)) = event {
-                    plan_for_home(&new_snapshot, &self.command_client, &self.trigger_client).await;
-                    last_snapshot = Some(new_snapshot);
+                    self.plan(&new_snapshot).await;
+                    self.latest_snapshot.send_replace(Some(new_snapshot));
                 },
             };
         }
     }
+
+    async fn plan(&mut self, snapshot: &StateSnapshot) {
+        plan_for_home(
+            snapshot,
+            &self.switch_client.current(),
+            &self.command_client,
+            &self.trigger_client,
+            &mut self.trace_recorder,
+        )
+        .await;
+    }
+}
+
+impl AutomationSwitchClient {
+    async fn new(repo: AutomationSwitchRepository) -> anyhow::Result<Self> {
+        let switches = AutomationSwitches::new(repo.get_disabled().await?);
+
+        Ok(Self {
+            repo,
+            switches: Arc::new(watch::Sender::new(switches)),
+        })
+    }
+
+    pub fn current(&self) -> AutomationSwitches {
+        self.switches.borrow().clone()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<AutomationSwitches> {
+        self.switches.subscribe()
+    }
+
+    pub async fn set_enabled(&self, target: AutomationSwitchTarget, enabled: bool) -> anyhow::Result<()> {
+        if !target.is_planned() {
+            anyhow::bail!("Unknown automation switch target {}", target);
+        }
+
+        self.repo.set_enabled(&target, enabled).await?;
+        tracing::info!("Automation switch of {} set to enabled={}", target, enabled);
+
+        self.switches
+            .send_modify(|switches| switches.set_enabled(target, enabled));
+        Ok(())
+    }
 }
@@ -0,0 +1,127 @@
+use crate::automation::HomeAction;
+use crate::command::{Command, CommandExecution, CommandState, CommandTarget};
+use crate::core::id::ExternalId;
+use crate::core::time::DateTime;
+use crate::trigger::UserTriggerId;
+
+use super::trace::{PlanningStepStatus, PlanningTraceStep, ResourcePlanningTrace};
+
+/// Why a resource is in its current state: the last command sent and the planning decision
+/// behind it, as recorded in the latest planning trace.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ResourceExplanation {
+    pub resource: String,
+    pub latest_command: Option<ExplainedCommand>,
+    pub decided_at: Option<DateTime>,
+    pub trace_id: Option<String>,
+    pub winner: Option<PlanningTraceStep>,
+    /// Higher-priority rules that were evaluated but did not apply
+    pub skipped: Vec<PlanningTraceStep>,
+    /// Lower-priority rules not evaluated because the winner took precedence
+    pub shadowed: Vec<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ExplainedCommand {
+    pub command: Command,
+    pub state: CommandState,
+    pub created: DateTime,
+    pub source: ExternalId,
+    pub user_trigger_id: Option<UserTriggerId>,
+}
+
+impl From<CommandExecution> for ExplainedCommand {
+    fn from(execution: CommandExecution) -> Self {
+        Self {
+            command: execution.command,
+            state: execution.state,
+            created: execution.created,
+            source: execution.source,
+            user_trigger_id: execution.user_trigger_id,
+        }
+    }
+}
+
+pub fn explain(
+    resource: &CommandTarget,
+    rules: &[HomeAction],
+    trace: Option<ResourcePlanningTrace>,
+    latest_command: Option<CommandExecution>,
+) -> ResourceExplanation {
+    let mut explanation = ResourceExplanation {
+        resource: resource.to_string(),
+        latest_command: latest_command.map(ExplainedCommand::from),
+        decided_at: None,
+        trace_id: None,
+        winner: None,
+        skipped: Vec::new(),
+        shadowed: Vec::new(),
+    };
+
+    let Some(trace) = trace else {
+        return explanation;
+    };
+
+    explanation.decided_at = Some(trace.timestamp);
+    explanation.trace_id = trace.correlation_id.as_ref().map(|id| id.trace_id());
+
+    for step in trace.steps {
+        if step.status() == PlanningStepStatus::Won {
+            explanation.winner = Some(step);
+        } else if explanation.winner.is_none() {
+            explanation.skipped.push(step);
+        }
+    }
+
+    if let Some(winner) = &explanation.winner {
+        explanation.shadowed = rules
+            .iter()
+            .map(|action| action.to_string())
+            .skip_while(|action| *action != winner.action)
+            .skip(1)
+            .collect();
+    }
+
+    explanation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automation::resource_plans;
+    use crate::command::PowerToggle;
+    use crate::t;
+
+    #[test]
+    fn rules_after_winner_are_shadowed() {
+        let resource = CommandTarget::SetPower {
+            device: PowerToggle::Dehumidifier,
+        };
+        let rules = resource_plans()
+            .into_iter()
+            .find_map(|(r, rules)| (r == resource).then_some(rules))
+            .unwrap_or_default();
+        assert!(rules.len() > 2, "test needs at least three rules");
+
+        let mut skipped = PlanningTraceStep::new(&rules[0], &resource);
+        skipped.fulfilled = Some(false);
+        let mut winner = PlanningTraceStep::new(&rules[1], &resource);
+        winner.fulfilled = Some(true);
+
+        let trace = ResourcePlanningTrace {
+            timestamp: t!(5 minutes ago),
+            resource: resource.to_string(),
+            correlation_id: None,
+            steps: vec![skipped.clone(), winner.clone()],
+        };
+
+        let explanation = explain(&resource, &rules, Some(trace), None);
+
+        assert_eq!(explanation.winner, Some(winner));
+        assert_eq!(explanation.skipped, vec![skipped]);
+        assert_eq!(
+            explanation.shadowed,
+            rules[2..].iter().map(|r| r.to_string()).collect::<Vec<_>>()
+        );
+    }
+}
@@ -1,28 +1,67 @@
 mod action;
+mod explain;
 mod processor;
+mod replay;
 mod trace;
 
-use trace::display_planning_trace;
+use infrastructure::TraceContext;
 
 use crate::{
-    automation::domain::resource_plans, command::CommandClient, home_state::StateSnapshot, trigger::TriggerClient,
+    automation::domain::{AutomationSwitches, resource_plans},
+    command::CommandClient,
+    core::time::DateTimeRange,
+    home_state::{HomeStateClient, StateSnapshot},
+    trigger::TriggerClient,
 };
 
 pub use action::ActionEvaluationResult;
-pub use trace::PlanningTrace;
+pub use explain::explain;
+pub use processor::DryRunResult;
+pub use replay::CommandTimelineEntry;
+pub use trace::{PlanningStepStatus, PlanningTrace, PlanningTraceRecorder, PlanningTraceStep, ResourcePlanningTrace};
 
 #[tracing::instrument(skip_all)]
-pub async fn plan_for_home(snapshot: &StateSnapshot, command_client: &CommandClient, trigger_client: &TriggerClient) {
+pub async fn plan_for_home(
+    snapshot: &StateSnapshot,
+    switches: &AutomationSwitches,
+    command_client: &CommandClient,
+    trigger_client: &TriggerClient,
+    trace_recorder: &mut PlanningTraceRecorder,
+) {
     tracing::info!("Start planning");
     let plans = resource_plans();
-    let res = processor::plan_and_execute(&plans, snapshot.clone(), command_client, trigger_client).await;
+    let res = processor::plan_and_execute(&plans, snapshot.clone(), switches, command_client, trigger_client).await;
 
     match res {
         Ok(res) => {
             tracing::info!("Planning done");
-            display_planning_trace(&res);
+            trace_recorder
+                .record(snapshot.timestamp(), TraceContext::current().correlation_id(), &res)
+                .await;
         }
 
         Err(e) => tracing::error!("Error during planning: {:?}", e),
     }
 }
+
+#[tracing::instrument(skip_all)]
+pub async fn dry_run_for_home(
+    snapshot: &StateSnapshot,
+    switches: &AutomationSwitches,
+    command_client: &CommandClient,
+) -> anyhow::Result<DryRunResult> {
+    tracing::info!("Start dry-run planning");
+    let plans = resource_plans();
+    processor::plan_dry_run(&plans, snapshot.clone(), switches, command_client).await
+}
+
+#[tracing::instrument(skip(home_state_client))]
+pub async fn replay_for_home(
+    range: DateTimeRange,
+    home_state_client: &HomeStateClient,
+) -> anyhow::Result<Vec<CommandTimelineEntry>> {
+    tracing::info!("Start replaying automation rules");
+    let plans = resource_plans();
+    let mut snapshots = home_state_client.snapshot_iter(range);
+    replay::replay(&plans, &mut snapshots).await
+}
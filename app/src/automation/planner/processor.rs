@@ -9,18 +9,89 @@ use crate::home_state::StateSnapshot;
 use crate::t;
 use crate::trigger::{TriggerClient, UserTriggerId};
 
-use crate::automation::{HomeAction, RuleEvaluationContext};
+use crate::automation::{AutomationSwitches, HomeAction, RuleEvaluationContext};
 
 use super::PlanningTrace;
 use super::action::ActionEvaluationResult;
 use super::trace::PlanningTraceStep;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlanningMode {
+    Execute,
+    DryRun,
+}
+
+/// Command produced by the winning action of a resource, independent of whether it was sent.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PlannedCommand {
+    pub resource: CommandTarget,
+    pub action: String,
+    pub command: Command,
+    pub source: ExternalId,
+    pub user_trigger_id: Option<UserTriggerId>,
+    pub would_execute: Option<bool>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DryRunResult {
+    pub timestamp: DateTime,
+    pub commands: Vec<PlannedCommand>,
+    pub trace: PlanningTrace,
+}
+
+struct PlanningOutcome {
+    trace: PlanningTrace,
+    commands: Vec<PlannedCommand>,
+    used_triggers: Vec<UserTriggerId>,
+}
+
 pub async fn plan_and_execute(
     resource_plans: &[(CommandTarget, Vec<HomeAction>)],
     snapshot: StateSnapshot,
+    switches: &AutomationSwitches,
     command_client: &CommandClient,
     trigger_client: &TriggerClient,
 ) -> Result<PlanningTrace> {
+    let planning_data_timestamp = snapshot.timestamp();
+    let outcome = plan(
+        resource_plans,
+        snapshot,
+        switches,
+        command_client,
+        PlanningMode::Execute,
+    )
+    .await?;
+
+    handle_trigger_updates(planning_data_timestamp, outcome.used_triggers, trigger_client).await?;
+
+    Ok(outcome.trace)
+}
+
+/// Evaluates all resource plans including the execution checks, but never sends a command
+/// and leaves user triggers untouched.
+pub async fn plan_dry_run(
+    resource_plans: &[(CommandTarget, Vec<HomeAction>)],
+    snapshot: StateSnapshot,
+    switches: &AutomationSwitches,
+    command_client: &CommandClient,
+) -> Result<DryRunResult> {
+    let timestamp = snapshot.timestamp();
+    let outcome = plan(resource_plans, snapshot, switches, command_client, PlanningMode::DryRun).await?;
+
+    Ok(DryRunResult {
+        timestamp,
+        commands: outcome.commands,
+        trace: outcome.trace,
+    })
+}
+
+async fn plan(
+    resource_plans: &[(CommandTarget, Vec<HomeAction>)],
+    snapshot: StateSnapshot,
+    switches: &AutomationSwitches,
+    command_client: &CommandClient,
+    mode: PlanningMode,
+) -> Result<PlanningOutcome> {
     debug_assert_eq!(
         resource_plans
             .iter()
@@ -31,19 +102,45 @@ pub async fn plan_and_execute(
         "resource_plans contains duplicate CommandTarget keys"
     );
 
-    let planning_data_timestamp = snapshot.timestamp();
     let ctx = RuleEvaluationContext::new(snapshot);
 
     let mut steps = Vec::new();
+    let mut commands = Vec::new();
     let mut used_triggers = Vec::new();
 
     for (resource, rules) in resource_plans {
-        evaluate_resource_plan(resource, rules, &ctx, command_client, &mut steps, &mut used_triggers).await?;
+        evaluate_resource_plan(
+            resource,
+            rules,
+            &ctx,
+            switches,
+            command_client,
+            mode,
+            &mut steps,
+            &mut commands,
+            &mut used_triggers,
+        )
+        .await?;
     }
 
-    handle_trigger_updates(planning_data_timestamp, used_triggers, trigger_client).await?;
+    Ok(PlanningOutcome {
+        trace: PlanningTrace::new(steps),
+        commands,
+        used_triggers,
+    })
+}
 
-    Ok(PlanningTrace::new(steps))
+fn ensure_resource_target(result: ActionEvaluationResult, resource: &CommandTarget) -> Result<ActionEvaluationResult> {
+    match &result {
+        ActionEvaluationResult::Execute(command, _) | ActionEvaluationResult::ExecuteTrigger(command, _, _) => {
+            let target = CommandTarget::from(command);
+            if &target != resource {
+                anyhow::bail!("Command targets {} instead of resource {}", target, resource);
+            }
+            Ok(result)
+        }
+        ActionEvaluationResult::Skip => Ok(result),
+    }
 }
 
 #[tracing::instrument(skip_all, fields(resource = %resource, otel.name = %resource))]
@@ -51,18 +148,35 @@ async fn evaluate_resource_plan(
     resource: &CommandTarget,
     rules: &[HomeAction],
     ctx: &RuleEvaluationContext,
+    switches: &AutomationSwitches,
     command_client: &CommandClient,
+    mode: PlanningMode,
     steps: &mut Vec<PlanningTraceStep>,
+    commands: &mut Vec<PlannedCommand>,
     used_triggers: &mut Vec<UserTriggerId>,
 ) -> Result<()> {
     for action in rules {
+        if switches.is_action_disabled(resource, action) {
+            tracing::info!("Action {} of {} disabled by operator, skipping", action, resource);
+            let mut trace = PlanningTraceStep::new(action, resource);
+            trace.fulfilled = Some(false);
+            trace.disabled = true;
+            steps.push(trace);
+            continue;
+        }
+
         let action_span = tracing::info_span!("process_action", %action, otel.name = %action);
 
         // Synchronous evaluation — span guard is safe here (no .await)
         let (mut trace, result) = action_span.in_scope(|| {
             let mut trace = PlanningTraceStep::new(action, resource);
             trace.correlation_id = TraceContext::current().correlation_id();
-            let result = action.evaluate(ctx);
+            let ((result, inputs), attributes) = TraceContext::capture_attributes(|| {
+                RuleEvaluationContext::capture_state_reads(|| action.evaluate(ctx))
+            });
+            trace.inputs = inputs;
+            trace.attributes = attributes.into_iter().collect();
+            let result = result.and_then(|result| ensure_resource_target(result, resource));
             (trace, result)
         });
 
@@ -70,20 +184,44 @@ async fn evaluate_resource_plan(
             Ok(ActionEvaluationResult::Execute(command, source)) => {
                 trace.fulfilled = Some(true);
                 // Async execution — use .instrument() to avoid holding span guard across .await
-                execute_command(&mut trace, command, source, None, command_client, ctx)
+                execute_command(&mut trace, &command, &source, None, command_client, ctx, mode)
                     .instrument(action_span.clone())
                     .await;
                 finalize_action_span(&action_span, action, &trace);
+                commands.push(PlannedCommand {
+                    resource: resource.clone(),
+                    action: trace.action.clone(),
+                    command,
+                    source,
+                    user_trigger_id: None,
+                    would_execute: trace.triggered,
+                });
                 steps.push(trace);
                 return Ok(());
             }
             Ok(ActionEvaluationResult::ExecuteTrigger(command, source, trigger_id)) => {
                 trace.fulfilled = Some(true);
                 used_triggers.push(trigger_id.clone());
-                execute_command(&mut trace, command, source, Some(trigger_id), command_client, ctx)
-                    .instrument(action_span.clone())
-                    .await;
+                execute_command(
+                    &mut trace,
+                    &command,
+                    &source,
+                    Some(trigger_id.clone()),
+                    command_client,
+                    ctx,
+                    mode,
+                )
+                .instrument(action_span.clone())
+                .await;
                 finalize_action_span(&action_span, action, &trace);
+                commands.push(PlannedCommand {
+                    resource: resource.clone(),
+                    action: trace.action.clone(),
+                    command,
+                    source,
+                    user_trigger_id: Some(trigger_id),
+                    would_execute: trace.triggered,
+                });
                 steps.push(trace);
                 return Ok(());
             }
@@ -96,6 +234,7 @@ async fn evaluate_resource_plan(
                     tracing::error!("Error evaluating action {}: {:?}", action, e);
                     TraceContext::current().set_error(e.to_string());
                 });
+                trace.error = Some(e.to_string());
                 steps.push(trace);
             }
         }
@@ -177,21 +316,29 @@ async fn should_execute(
 #[tracing::instrument(skip_all)]
 async fn execute_command(
     trace: &mut PlanningTraceStep,
-    command: Command,
-    source: ExternalId,
+    command: &Command,
+    source: &ExternalId,
     user_trigger_id: Option<UserTriggerId>,
     command_client: &CommandClient,
     ctx: &RuleEvaluationContext,
+    mode: PlanningMode,
 ) {
-    let target: CommandTarget = command.clone().into();
+    let target: CommandTarget = command.into();
 
-    match should_execute(&command, &source, command_client, ctx).await {
-        Ok(true) => match command_client.execute(command, source, user_trigger_id).await {
+    match should_execute(command, source, command_client, ctx).await {
+        Ok(true) if mode == PlanningMode::DryRun => {
+            tracing::info!("Command {} would be executed via action {} (dry run)", target, trace.action);
+            trace.triggered = Some(true);
+        }
+        Ok(true) => match command_client.execute(command.clone(), source.clone(), user_trigger_id).await {
             Ok(_) => {
                 tracing::info!("Command {} executed via action {}", target, trace.action);
                 trace.triggered = Some(true);
             }
-            Err(e) => tracing::error!("Error executing command for {}: {:?}", target, e),
+            Err(e) => {
+                tracing::error!("Error executing command for {}: {:?}", target, e);
+                trace.error = Some(format!("Error executing command: {}", e));
+            }
         },
         Ok(false) => {
             tracing::trace!("Skipped execution command {} via action {}", target, trace.action);
@@ -204,6 +351,7 @@ async fn execute_command(
                 trace.action,
                 e
             );
+            trace.error = Some(format!("Error checking execution: {}", e));
         }
     }
 }
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+
+use crate::automation::{HomeAction, RuleEvaluationContext};
+use crate::command::{Command, CommandExecution, CommandTarget};
+use crate::core::id::ExternalId;
+use crate::core::time::DateTime;
+use crate::home_state::StateSnapshotIterator;
+
+use super::action::ActionEvaluationResult;
+
+/// Command a resource received (or would have received) at a point in time. Replayed and stored
+/// commands share this shape so both timelines can be diffed directly.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct CommandTimelineEntry {
+    pub timestamp: DateTime,
+    pub resource: CommandTarget,
+    pub command: Option<Command>,
+    pub source: Option<ExternalId>,
+    /// Rules that failed to evaluate, like `error` of a live planning trace step
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+impl From<CommandExecution> for CommandTimelineEntry {
+    fn from(execution: CommandExecution) -> Self {
+        Self {
+            timestamp: execution.created,
+            resource: CommandTarget::from(&execution.command),
+            command: Some(execution.command),
+            source: Some(execution.source),
+            errors: vec![],
+        }
+    }
+}
+
+/// Steps through all snapshots and evaluates the resource plans at every step. An entry is
+/// emitted whenever the winning command of a resource or its evaluation errors change. Execution checks like cooldowns
+/// or state reflection are not applied, as they depend on the commands actually sent.
+pub async fn replay(
+    resource_plans: &[(CommandTarget, Vec<HomeAction>)],
+    snapshots: &mut StateSnapshotIterator,
+) -> Result<Vec<CommandTimelineEntry>> {
+    let mut timeline = CommandTimeline::default();
+
+    while let Some(snapshot) = snapshots.next().await? {
+        let timestamp = snapshot.timestamp();
+        let ctx = RuleEvaluationContext::new(snapshot);
+
+        timestamp
+            .eval_timeshifted(async {
+                for (resource, rules) in resource_plans {
+                    // Rules log on every evaluation, which would flood the logs for long ranges
+                    let evaluation =
+                        tracing::subscriber::with_default(tracing::subscriber::NoSubscriber::default(), || {
+                            evaluate_rules(rules, &ctx)
+                        });
+                    timeline.observe(timestamp, resource, evaluation);
+                }
+            })
+            .await;
+    }
+
+    Ok(timeline.entries)
+}
+
+#[derive(Debug, Default)]
+struct RulesEvaluation {
+    winner: Option<(Command, ExternalId)>,
+    errors: Vec<String>,
+}
+
+/// Like the live planning, a failing rule doesn't stop the evaluation of the following rules
+fn evaluate_rules(rules: &[HomeAction], ctx: &RuleEvaluationContext) -> RulesEvaluation {
+    let mut evaluation = RulesEvaluation::default();
+
+    for action in rules {
+        match action.evaluate(ctx) {
+            Ok(ActionEvaluationResult::Execute(command, source))
+            | Ok(ActionEvaluationResult::ExecuteTrigger(command, source, _)) => {
+                evaluation.winner = Some((command, source));
+                break;
+            }
+            Ok(ActionEvaluationResult::Skip) => {}
+            Err(e) => evaluation.errors.push(format!("{}: {}", action, e)),
+        }
+    }
+
+    evaluation
+}
+
+#[derive(Default)]
+struct CommandTimeline {
+    current: HashMap<CommandTarget, (Option<Command>, Vec<String>)>,
+    entries: Vec<CommandTimelineEntry>,
+}
+
+impl CommandTimeline {
+    fn observe(&mut self, timestamp: DateTime, resource: &CommandTarget, evaluation: RulesEvaluation) {
+        let command = evaluation.winner.as_ref().map(|(command, _)| command.clone());
+        let current = (command.clone(), evaluation.errors.clone());
+        let previous = self.current.insert(resource.clone(), current.clone());
+
+        let changed = match previous {
+            Some(previous) => previous != current,
+            None => command.is_some() || !evaluation.errors.is_empty(),
+        };
+
+        if changed {
+            self.entries.push(CommandTimelineEntry {
+                timestamp,
+                resource: resource.clone(),
+                command,
+                source: evaluation.winner.map(|(_, source)| source),
+                errors: evaluation.errors,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::PowerToggle;
+
+    fn power(on: bool) -> Option<(Command, ExternalId)> {
+        Some((
+            Command::SetPower {
+                device: PowerToggle::Dehumidifier,
+                power_on: on,
+            },
+            ExternalId::new("test", "rule"),
+        ))
+    }
+
+    fn winner(winner: Option<(Command, ExternalId)>) -> RulesEvaluation {
+        RulesEvaluation { winner, errors: vec![] }
+    }
+
+    #[test]
+    fn only_changes_of_winning_command_are_recorded() {
+        let resource = CommandTarget::SetPower {
+            device: PowerToggle::Dehumidifier,
+        };
+        let mut timeline = CommandTimeline::default();
+
+        timeline.observe(DateTime::from_static_iso("2026-01-01T10:00:00Z"), &resource, winner(None));
+        timeline.observe(
+            DateTime::from_static_iso("2026-01-01T10:00:30Z"),
+            &resource,
+            winner(power(true)),
+        );
+        timeline.observe(
+            DateTime::from_static_iso("2026-01-01T10:01:00Z"),
+            &resource,
+            winner(power(true)),
+        );
+        timeline.observe(
+            DateTime::from_static_iso("2026-01-01T10:01:30Z"),
+            &resource,
+            winner(power(false)),
+        );
+        timeline.observe(DateTime::from_static_iso("2026-01-01T10:02:00Z"), &resource, winner(None));
+
+        let commands: Vec<_> = timeline.entries.iter().map(|e| e.command.clone()).collect();
+        assert_eq!(
+            commands,
+            vec![power(true).map(|(c, _)| c), power(false).map(|(c, _)| c), None]
+        );
+        assert_eq!(
+            timeline.entries[0].timestamp,
+            DateTime::from_static_iso("2026-01-01T10:00:30Z")
+        );
+    }
+
+    #[test]
+    fn evaluation_errors_are_recorded() {
+        let resource = CommandTarget::SetPower {
+            device: PowerToggle::Dehumidifier,
+        };
+        let mut timeline = CommandTimeline::default();
+        let failed = || RulesEvaluation {
+            winner: None,
+            errors: vec!["rule: missing state".to_string()],
+        };
+
+        timeline.observe(DateTime::from_static_iso("2026-01-01T10:00:00Z"), &resource, failed());
+        timeline.observe(DateTime::from_static_iso("2026-01-01T10:00:30Z"), &resource, failed());
+        timeline.observe(DateTime::from_static_iso("2026-01-01T10:01:00Z"), &resource, winner(None));
+
+        assert_eq!(timeline.entries.len(), 2);
+        assert_eq!(timeline.entries[0].errors, vec!["rule: missing state".to_string()]);
+        assert!(timeline.entries[1].errors.is_empty());
+    }
+}
@@ -1,8 +1,16 @@
-use std::{fmt::Display, sync::Mutex};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+};
 
 use infrastructure::CorrelationId;
 
-#[derive(Clone, Debug)]
+use crate::{
+    automation::{StateRead, adapter::db::PlanningTraceRepository},
+    core::time::DateTime,
+};
+
+#[derive(Clone, Debug, serde::Serialize)]
 pub struct PlanningTrace {
     pub steps: Vec<PlanningTraceStep>,
 }
@@ -16,6 +24,36 @@ pub struct PlanningTraceStep {
     pub triggered: Option<bool>,
 
     pub correlation_id: Option<CorrelationId>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disabled: bool,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<StateRead>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanningStepStatus {
+    Won,
+    Skipped,
+    SkippedByOperator,
+    Failed,
+}
+
+/// Persisted planning result of a single resource
+#[derive(Clone, Debug)]
+pub struct ResourcePlanningTrace {
+    pub timestamp: DateTime,
+    pub resource: String,
+    pub correlation_id: Option<CorrelationId>,
+    pub steps: Vec<PlanningTraceStep>,
 }
 
 impl PartialEq for PlanningTraceStep {
@@ -24,6 +62,8 @@ impl PartialEq for PlanningTraceStep {
             && self.resource == other.resource
             && self.fulfilled == other.fulfilled
             && self.triggered == other.triggered
+            && self.error == other.error
+            && self.disabled == other.disabled
     }
 }
 
@@ -31,6 +71,20 @@ impl PlanningTrace {
     pub fn new(steps: Vec<PlanningTraceStep>) -> Self {
         Self { steps }
     }
+
+    /// Steps grouped by resource, in planning order
+    pub fn by_resource(&self) -> Vec<(String, Vec<PlanningTraceStep>)> {
+        let mut result: Vec<(String, Vec<PlanningTraceStep>)> = Vec::new();
+
+        for step in &self.steps {
+            match result.last_mut() {
+                Some((resource, steps)) if *resource == step.resource => steps.push(step.clone()),
+                _ => result.push((step.resource.clone(), vec![step.clone()])),
+            }
+        }
+
+        result
+    }
 }
 
 impl PlanningTraceStep {
@@ -41,34 +95,127 @@ impl PlanningTraceStep {
             fulfilled: None,
             triggered: None,
             correlation_id: None,
+            error: None,
+            disabled: false,
+            inputs: Vec::new(),
+            attributes: BTreeMap::new(),
+        }
+    }
+
+    pub fn status(&self) -> PlanningStepStatus {
+        if self.disabled {
+            return PlanningStepStatus::SkippedByOperator;
+        }
+
+        //the step can be fulfilled, but still fail to execute its command
+        if self.error.is_some() {
+            return PlanningStepStatus::Failed;
+        }
+
+        match self.fulfilled {
+            Some(true) => PlanningStepStatus::Won,
+            Some(false) => PlanningStepStatus::Skipped,
+            None => PlanningStepStatus::Failed,
         }
     }
 }
 
-pub fn display_planning_trace(trace: &PlanningTrace) {
-    if planning_trace_has_changed(trace) {
-        tracing::info!("Planning result changed");
-    } else {
-        tracing::debug!("Planning result is unchanged");
+impl ResourcePlanningTrace {
+    pub fn winner(&self) -> Option<&PlanningTraceStep> {
+        self.steps.iter().find(|step| step.status() == PlanningStepStatus::Won)
     }
 }
 
-static PREVIOUS_TRACE: Mutex<Option<Vec<PlanningTraceStep>>> = Mutex::new(None);
-fn planning_trace_has_changed(current: &PlanningTrace) -> bool {
-    match PREVIOUS_TRACE.lock() {
-        Ok(mut previous) => {
-            let current = Some(current.steps.clone());
-            if *previous != current {
-                *previous = current;
-                true
-            } else {
-                false
-            }
+/// Persists the planning result per resource, but only when it differs from the previously recorded one.
+pub struct PlanningTraceRecorder {
+    repo: PlanningTraceRepository,
+    previous: HashMap<String, Vec<PlanningTraceStep>>,
+}
+
+impl PlanningTraceRecorder {
+    pub fn new(repo: PlanningTraceRepository) -> Self {
+        Self {
+            repo,
+            previous: HashMap::new(),
         }
+    }
+
+    pub async fn record(&mut self, timestamp: DateTime, correlation_id: Option<CorrelationId>, trace: &PlanningTrace) {
+        let changed = self.changed_resources(trace);
 
-        Err(e) => {
-            tracing::error!("Error locking previous trace, logging impacted: {:?}", e);
-            false
+        if changed.is_empty() {
+            tracing::debug!("Planning result is unchanged");
+            return;
         }
+
+        tracing::info!("Planning result changed for {} resource(s)", changed.len());
+
+        for (resource, steps) in changed {
+            let resource_trace = ResourcePlanningTrace {
+                timestamp,
+                resource: resource.clone(),
+                correlation_id: correlation_id.clone(),
+                steps: steps.clone(),
+            };
+
+            match self.repo.insert_trace(&resource_trace).await {
+                Ok(()) => {
+                    self.previous.insert(resource, steps);
+                }
+                Err(e) => tracing::error!("Error persisting planning trace of {}: {:?}", resource, e),
+            }
+        }
+    }
+
+    fn changed_resources(&self, trace: &PlanningTrace) -> Vec<(String, Vec<PlanningTraceStep>)> {
+        trace
+            .by_resource()
+            .into_iter()
+            .filter(|(resource, steps)| self.previous.get(resource) != Some(steps))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::t;
+    use anyhow::Context as _;
+
+    #[test]
+    fn fulfilled_step_with_execution_error_is_failed() {
+        let mut step = PlanningTraceStep::new(&"action", &"resource");
+        step.fulfilled = Some(true);
+        step.error = Some("Error executing command".to_string());
+
+        let trace = ResourcePlanningTrace {
+            timestamp: t!(now),
+            resource: "resource".to_string(),
+            correlation_id: None,
+            steps: vec![step],
+        };
+
+        assert_eq!(trace.steps[0].status(), PlanningStepStatus::Failed);
+        assert!(trace.winner().is_none());
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn execution_error_is_recorded_as_change(db_pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = PlanningTraceRepository::new(db_pool);
+        let mut recorder = PlanningTraceRecorder::new(repo.clone());
+
+        let mut step = PlanningTraceStep::new(&"action", &"resource");
+        step.fulfilled = Some(true);
+        recorder
+            .record(t!(10 minutes ago), None, &PlanningTrace::new(vec![step.clone()]))
+            .await;
+
+        step.error = Some("Error executing command".to_string());
+        recorder.record(t!(now), None, &PlanningTrace::new(vec![step])).await;
+
+        let latest = repo.latest_trace("resource").await?.context("No trace recorded")?;
+        assert_eq!(latest.steps[0].status(), PlanningStepStatus::Failed);
+
+        Ok(())
     }
 }
@@ -1,6 +1,9 @@
 use crate::{
     command::{Command, CommandExecution, CommandState, CommandTarget},
-    core::{id::ExternalId, time::DateTimeRange},
+    core::{
+        id::ExternalId,
+        time::{DateTime, DateTimeRange},
+    },
     t,
     trigger::UserTriggerId,
 };
@@ -62,6 +65,8 @@ impl CommandRepository {
             source: source.clone(),
             user_trigger_id,
             correlation_id,
+            attempts: 1,
+            next_retry_at: None,
         })
     }
 
@@ -70,6 +75,8 @@ impl CommandRepository {
             CommandState::Pending => (DbCommandState::Pending, None),
             CommandState::InProgress => (DbCommandState::InProgress, None),
             CommandState::Success => (DbCommandState::Success, None),
+            CommandState::Confirmed => (DbCommandState::Confirmed, None),
+            CommandState::Unconfirmed => (DbCommandState::Unconfirmed, None),
             CommandState::Error(err) => (DbCommandState::Error, Some(err)),
         };
 
@@ -93,21 +100,22 @@ impl CommandRepository {
     ) -> Result<Vec<CommandExecution>> {
         let db_target = target.map(|j| serde_json::json!(j));
 
-        let records = sqlx::query!(
-            r#"(SELECT id as "id!", command as "command!", created as "created", status as "status!: DbCommandState", error, source_type as "source_type!", source_id as "source_id!", correlation_id, user_trigger_id
+        let records = sqlx::query_as!(
+            DbCommandRow,
+            r#"(SELECT id as "id!", command as "command!", created as "created", status as "status!: DbCommandState", error, source_type as "source_type!", source_id as "source_id!", correlation_id, user_trigger_id, attempts as "attempts!", next_retry_at
                 from thing_command 
                 where (command @> $1 or $1 is null)
                 and created >= $2
                 and created <= $3)
             UNION ALL
-            (SELECT id, command, created, status, error, source_type, source_id, correlation_id, user_trigger_id
+            (SELECT id, command, created, status, error, source_type, source_id, correlation_id, user_trigger_id, attempts, next_retry_at
                 from thing_command 
                 where (command @> $1 or $1 is null)
                 and created < $2
                 order by created DESC
                 limit 1)
             UNION ALL
-            (SELECT id, command, created, status, error, source_type, source_id, correlation_id, user_trigger_id
+            (SELECT id, command, created, status, error, source_type, source_id, correlation_id, user_trigger_id, attempts, next_retry_at
                 from thing_command 
                 where (command @> $1 or $1 is null)
                 and created > $3
@@ -121,39 +129,117 @@ impl CommandRepository {
         .fetch_all(&self.pool)
         .await?;
 
-        let commands = records
-            .into_iter()
-            .filter_map(|row| {
-                let source = ExternalId::new(row.source_type, row.source_id);
-                match serde_json::from_value::<Command>(row.command) {
-                    Ok(command) => {
-                        let Some(created) = row.created else {
-                            tracing::warn!(
-                                "Invalid command row with id {}, missing created timestamp, ignoring",
-                                row.id
-                            );
-                            return None;
-                        };
-
-                        Some(CommandExecution {
-                            id: row.id,
-                            command,
-                            state: CommandState::from((row.status, row.error)),
-                            created: created.into(),
-                            source,
-                            user_trigger_id: row.user_trigger_id.map(UserTriggerId::from),
-                            correlation_id: row.correlation_id.map(|id| id.into()),
-                        })
-                    }
-                    Err(e) => {
-                        tracing::warn!("Error mapping command with id {} from database, ignoring: {}", row.id, e);
-                        None
-                    }
-                }
-            })
-            .collect();
-
-        Ok(commands)
+        Ok(records.into_iter().filter_map(DbCommandRow::into_execution).collect())
+    }
+
+    /// Failed commands whose next retry is due
+    pub async fn query_due_retries(&self, now: DateTime) -> Result<Vec<CommandExecution>> {
+        let records = sqlx::query_as!(
+            DbCommandRow,
+            r#"SELECT id, command, created as "created", status as "status!: DbCommandState", error, source_type, source_id, correlation_id, user_trigger_id, attempts, next_retry_at
+                from thing_command
+                where next_retry_at IS NOT NULL
+                and next_retry_at <= $1
+                order by next_retry_at asc"#,
+            now.into_db(),
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records.into_iter().filter_map(DbCommandRow::into_execution).collect())
+    }
+
+    pub async fn schedule_retry(&self, command_id: i64, next_retry_at: DateTime) -> Result<()> {
+        sqlx::query!(
+            r#"UPDATE THING_COMMAND SET next_retry_at = $2 WHERE id = $1"#,
+            command_id,
+            next_retry_at.into_db(),
+        )
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .map_err(Into::into)
+    }
+
+    /// Claims a due retry. Returns false if it was already claimed or cancelled in the meantime.
+    pub async fn start_retry(&self, command_id: i64) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"UPDATE THING_COMMAND
+                SET attempts = attempts + 1, next_retry_at = NULL, status = $2, error = NULL
+                WHERE id = $1
+                AND next_retry_at IS NOT NULL"#,
+            command_id,
+            DbCommandState::InProgress as DbCommandState,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Pending retries are superseded by any newer command for the same target
+    pub async fn cancel_retries(&self, target: &CommandTarget) -> Result<u64> {
+        let result = sqlx::query!(
+            r#"UPDATE THING_COMMAND SET next_retry_at = NULL
+                WHERE next_retry_at IS NOT NULL
+                AND command @> $1"#,
+            serde_json::json!(target),
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+struct DbCommandRow {
+    id: i64,
+    command: serde_json::Value,
+    created: Option<chrono::DateTime<chrono::Utc>>,
+    status: DbCommandState,
+    error: Option<String>,
+    source_type: String,
+    source_id: String,
+    correlation_id: Option<String>,
+    user_trigger_id: Option<i64>,
+    attempts: i32,
+    next_retry_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl DbCommandRow {
+    fn into_execution(self) -> Option<CommandExecution> {
+        let source = ExternalId::new(self.source_type, self.source_id);
+        match serde_json::from_value::<Command>(self.command) {
+            Ok(command) => {
+                let Some(created) = self.created else {
+                    tracing::warn!(
+                        "Invalid command row with id {}, missing created timestamp, ignoring",
+                        self.id
+                    );
+                    return None;
+                };
+
+                Some(CommandExecution {
+                    id: self.id,
+                    command,
+                    state: CommandState::from((self.status, self.error)),
+                    created: created.into(),
+                    source,
+                    user_trigger_id: self.user_trigger_id.map(UserTriggerId::from),
+                    correlation_id: self.correlation_id.map(|id| id.into()),
+                    attempts: u32::try_from(self.attempts).unwrap_or(1),
+                    next_retry_at: self.next_retry_at.map(Into::into),
+                })
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Error mapping command with id {} from database, ignoring: {}",
+                    self.id,
+                    e
+                );
+                None
+            }
+        }
     }
 }
 
@@ -163,6 +249,8 @@ pub enum DbCommandState {
     Pending,
     InProgress,
     Success,
+    Confirmed,
+    Unconfirmed,
     Error,
 }
 
@@ -172,6 +260,8 @@ impl From<(DbCommandState, Option<String>)> for CommandState {
             DbCommandState::Pending => CommandState::Pending,
             DbCommandState::InProgress => CommandState::InProgress,
             DbCommandState::Success => CommandState::Success,
+            DbCommandState::Confirmed => CommandState::Confirmed,
+            DbCommandState::Unconfirmed => CommandState::Unconfirmed,
             DbCommandState::Error => CommandState::Error(error.unwrap_or("unknown error".to_string())),
         }
     }
@@ -253,4 +343,43 @@ mod tests {
 
         Ok(())
     }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn due_retry_is_claimed_once_and_cancelled_by_newer_command(db_pool: PgPool) -> anyhow::Result<()> {
+        let repo = CommandRepository::new(db_pool);
+        let source = ExternalId::new("test", "source");
+        let command = Command::SetPower {
+            device: PowerToggle::Dehumidifier,
+            power_on: true,
+        };
+
+        let first = repo
+            .insert_command_for_processing(&command, &source, None, None)
+            .await?;
+        let second = repo
+            .insert_command_for_processing(&command, &source, None, None)
+            .await?;
+        repo.schedule_retry(first.id, t!(10 seconds ago)).await?;
+        repo.schedule_retry(second.id, t!(in 10 seconds)).await?;
+
+        let due = repo.query_due_retries(t!(now)).await?;
+        assert_eq!(due.iter().map(|c| c.id).collect::<Vec<_>>(), vec![first.id]);
+
+        assert!(repo.start_retry(first.id).await?);
+        assert!(!repo.start_retry(first.id).await?);
+
+        let cancelled = repo.cancel_retries(&CommandTarget::from(&command)).await?;
+        assert_eq!(cancelled, 1);
+        assert!(repo.query_due_retries(t!(in 1 minutes)).await?.is_empty());
+
+        let stored = repo
+            .query_all_commands(
+                Some(CommandTarget::from(&command)),
+                &DateTimeRange::since(t!(1 minutes ago)),
+            )
+            .await?;
+        assert_eq!(stored.iter().find(|c| c.id == first.id).map(|c| c.attempts), Some(2));
+
+        Ok(())
+    }
 }
@@ -24,6 +24,13 @@ pub fn default_ha_command_config() -> Vec<(CommandTarget, HaServiceTarget)> {
             },
             HaServiceTarget::PushNotification("mobile_app_simi_2"),
         ),
+        (
+            CommandTarget::PushNotify {
+                recipient: NotificationRecipient::Dennis,
+                notification: Notification::BatteryLow,
+            },
+            HaServiceTarget::PushNotification("mobile_app_jarvis"),
+        ),
         (
             CommandTarget::SetEnergySaving {
                 device: EnergySavingDevice::LivingRoomTv,
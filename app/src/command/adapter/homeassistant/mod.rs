@@ -1,11 +1,12 @@
 mod config;
 
+use futures::future::BoxFuture;
 use infrastructure::HttpClientConfig;
 use reqwest_middleware::ClientWithMiddleware;
 
 use super::metrics::*;
-use crate::command::adapter::CommandExecutor;
-use crate::command::{Command, CommandTarget};
+use crate::command::adapter::{CommandExecutor, TransientError};
+use crate::command::{Command, CommandTarget, Notification};
 use crate::core::unit::{FanAirflow, FanSpeed};
 use serde_json::json;
 
@@ -39,8 +40,22 @@ impl HomeAssistantCommandExecutor {
 }
 
 impl CommandExecutor for HomeAssistantCommandExecutor {
+    fn system(&self) -> CommandTargetSystem {
+        CommandTargetSystem::HomeAssistant
+    }
+
+    fn command_targets(&self) -> Vec<CommandTarget> {
+        self.config.iter().map(|(target, _)| target.clone()).collect()
+    }
+
+    fn execute_command<'a>(&'a self, command: &'a Command) -> BoxFuture<'a, anyhow::Result<bool>> {
+        Box::pin(self.execute(command))
+    }
+}
+
+impl HomeAssistantCommandExecutor {
     #[tracing::instrument(name = "execute_command HA", ret, skip(self))]
-    async fn execute_command(&self, command: &Command) -> anyhow::Result<bool> {
+    async fn execute(&self, command: &Command) -> anyhow::Result<bool> {
         let command_target: CommandTarget = command.clone().into();
 
         let ha_target = self
@@ -66,19 +81,19 @@ impl HomeAssistantCommandExecutor {
             (
                 PushNotification(mobile_id),
                 Command::PushNotify {
-                    notification: Notification::WindowOpened,
+                    notification,
                     action: NotificationAction::Notify,
                     ..
                 },
-            ) => self.notify_window_opened(mobile_id).await,
+            ) => self.notify(mobile_id, notification).await,
             (
                 PushNotification(mobile_id),
                 Command::PushNotify {
-                    notification: Notification::WindowOpened,
+                    notification,
                     action: NotificationAction::Dismiss,
                     ..
                 },
-            ) => self.dismiss_window_opened_notification(mobile_id).await,
+            ) => self.dismiss_notification(mobile_id, notification).await,
             (LgWebosSmartTv(id), Command::SetEnergySaving { on, .. }) => self.lg_tv_energy_saving_mode(id, *on).await,
             (ComfeeDehumidifier { humidifier_id, fan_id }, Command::ControlFan { speed, .. }) => {
                 self.comfee_fan_speed(humidifier_id, fan_id, speed).await
@@ -184,16 +199,21 @@ impl HomeAssistantCommandExecutor {
         Ok(())
     }
 
-    async fn notify_window_opened(&self, mobile_id: &str) -> anyhow::Result<()> {
+    async fn notify(&self, mobile_id: &str, notification: &Notification) -> anyhow::Result<()> {
+        let (title, message) = match notification {
+            Notification::WindowOpened => ("Fenster offen", "Mindestens ein Fenster ist offen"),
+            Notification::BatteryLow => ("Batterie schwach", "Mindestens ein Sensor braucht eine neue Batterie"),
+        };
+
         self.client
             .call_service(
                 "notify",
                 mobile_id,
                 json!({
-                    "title": "Fenster offen",
-                    "message": "Mindestens ein Fenster ist offen",
+                    "title": title,
+                    "message": message,
                     "data": {
-                        "tag": "window_opened"
+                        "tag": notification.ext_id().variant_name()
                     }
                 }),
             )
@@ -204,7 +224,7 @@ impl HomeAssistantCommandExecutor {
         Ok(())
     }
 
-    async fn dismiss_window_opened_notification(&self, mobile_id: &str) -> anyhow::Result<()> {
+    async fn dismiss_notification(&self, mobile_id: &str, notification: &Notification) -> anyhow::Result<()> {
         self.client
             .call_service(
                 "notify",
@@ -212,7 +232,7 @@ impl HomeAssistantCommandExecutor {
                 json!({
                     "message": "clear_notification",
                     "data": {
-                        "tag": "window_opened"
+                        "tag": notification.ext_id().variant_name()
                     }
                 }),
             )
@@ -352,8 +372,25 @@ impl HaHttpClient {
 
         tracing::info!("Calling HA service {}: {:?}", url, serde_json::to_string(&service_data)?);
 
-        let response = self.client.post(url).json(&service_data).send().await?;
-        tracing::info!("Response: {} - {}", response.status(), response.text().await?);
+        let response = self
+            .client
+            .post(url)
+            .json(&service_data)
+            .send()
+            .await
+            .map_err(|e| TransientError::new(format!("Error calling HA service: {e}")))?;
+
+        let status = response.status();
+        let body = response.text().await?;
+        tracing::info!("Response: {} - {}", status, body);
+
+        if status.is_server_error() {
+            return Err(TransientError::new(format!("HA service call failed with {status}: {body}")).into());
+        }
+
+        if !status.is_success() {
+            anyhow::bail!("HA service call failed with {status}: {body}");
+        }
 
         Ok(())
     }
@@ -1,25 +1,81 @@
 pub mod db;
 mod homeassistant;
+mod mqtt;
 pub mod nuki;
+#[cfg(test)]
+mod recording;
+mod registry;
 mod tasmota;
 pub mod z2m;
 
-use crate::command::Command;
+use crate::command::{Command, CommandTarget};
+use futures::future::BoxFuture;
 
 pub use homeassistant::HomeAssistantCommandExecutor;
+pub use metrics::CommandTargetSystem;
+pub use mqtt::{MqttCommandConfig, MqttCommandExecutor};
 pub use nuki::NukiCommandExecutor;
+#[cfg(test)]
+pub use recording::{ExecutorOutcome, RecordingCommandExecutor};
+pub use registry::CommandExecutorRegistry;
 pub use tasmota::TasmotaCommandExecutor;
 pub use z2m::Z2mCommandExecutor;
 
-pub trait CommandExecutor {
+/// Executors with their default configuration. Nothing is connected until a command is executed.
+#[cfg(test)]
+fn default_executors() -> Vec<Box<dyn CommandExecutor>> {
+    let mqtt = infrastructure::Mqtt::connect("localhost", 1883, "command-executor-test");
+
+    vec![
+        Box::new(TasmotaCommandExecutor::new(mqtt.sender("tasmota"))),
+        Box::new(Z2mCommandExecutor::new(mqtt.sender("zigbee2mqtt"))),
+        Box::new(NukiCommandExecutor::new("http://localhost:8080", "", "2A7F3C14")),
+        Box::new(HomeAssistantCommandExecutor::new("http://localhost:8123", "")),
+    ]
+}
+
+/// All command targets covered by the default configuration of any executor
+#[cfg(test)]
+pub fn executor_command_targets() -> Vec<CommandTarget> {
+    default_executors()
+        .iter()
+        .flat_map(|executor| executor.command_targets())
+        .collect()
+}
+
+/// Backend sending commands to an external system. Dyn-compatible, so that executors can be
+/// registered in a `CommandExecutorRegistry`.
+pub trait CommandExecutor: Send + Sync {
+    fn system(&self) -> CommandTargetSystem;
+
+    /// Targets handled by this executor, used to build the routing table.
+    fn command_targets(&self) -> Vec<CommandTarget>;
+
     //Returns true if command was executed
-    async fn execute_command(&self, command: &Command) -> anyhow::Result<bool>;
+    fn execute_command<'a>(&'a self, command: &'a Command) -> BoxFuture<'a, anyhow::Result<bool>>;
 }
 
-mod metrics {
-    use crate::observability::system_metric_increment;
+/// Failure that might succeed when tried again, like a connection error or a 5xx response.
+/// Everything else is considered permanent and not retried.
+#[derive(Debug, derive_more::Display)]
+pub struct TransientError(String);
+
+impl std::error::Error for TransientError {}
 
-    #[derive(Clone, Copy, derive_more::Display)]
+impl TransientError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+}
+
+pub fn is_transient(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| cause.is::<TransientError>())
+}
+
+pub(super) mod metrics {
+    use crate::observability::{system_metric_increment, system_metric_record};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
     pub enum CommandTargetSystem {
         #[display("TASMOTA")]
         Tasmota,
@@ -29,6 +85,11 @@ mod metrics {
         Nuki,
         #[display("HA")]
         HomeAssistant,
+        #[display("MQTT")]
+        Mqtt,
+        #[cfg(test)]
+        #[display("RECORDING")]
+        Recording,
     }
 
     pub enum CommandMetric {
@@ -36,6 +97,15 @@ mod metrics {
             device_id: String,
             system: CommandTargetSystem,
         },
+        Confirmed {
+            device_id: String,
+            system: CommandTargetSystem,
+            latency_seconds: f64,
+        },
+        Unconfirmed {
+            device_id: String,
+            system: CommandTargetSystem,
+        },
     }
 
     impl CommandMetric {
@@ -48,6 +118,25 @@ mod metrics {
                         &[("device_id", device_id.as_str()), ("system", system.as_str())],
                     );
                 }
+                CommandMetric::Confirmed {
+                    device_id,
+                    system,
+                    latency_seconds,
+                } => {
+                    let system = system.to_string();
+                    system_metric_record(
+                        "command_confirmation_latency_seconds",
+                        *latency_seconds,
+                        &[("device_id", device_id.as_str()), ("system", system.as_str())],
+                    );
+                }
+                CommandMetric::Unconfirmed { device_id, system } => {
+                    let system = system.to_string();
+                    system_metric_increment(
+                        "command_unconfirmed",
+                        &[("device_id", device_id.as_str()), ("system", system.as_str())],
+                    );
+                }
             }
         }
     }
@@ -0,0 +1,135 @@
+mod template;
+
+use std::collections::HashMap;
+
+use anyhow::Context as _;
+use futures::future::BoxFuture;
+use infrastructure::MqttSender;
+use serde::Deserialize;
+
+use super::CommandExecutor;
+use super::metrics::*;
+use crate::command::{Command, CommandTarget};
+
+use template::PayloadTemplate;
+
+/// Command target published as templated payload to an MQTT topic, configured in `config.toml`
+#[derive(Debug, Clone, Deserialize)]
+pub struct MqttCommandConfig {
+    /// Only existing `CommandTarget` variants and devices can be configured. A new device still needs a variant
+    /// in its device enum, e.g. `PowerToggle`, so that automation can refer to it.
+    pub target: CommandTarget,
+    /// Full topic, may contain placeholders like the payload
+    pub topic: String,
+    /// Payload with `{{field}}` placeholders, resolved from the fields of the command
+    pub payload: String,
+    /// Replacements of rendered placeholder values per field, e.g. `true` -> `ON` for `power_on`
+    #[serde(default)]
+    pub values: HashMap<String, HashMap<String, String>>,
+    #[serde(default)]
+    pub retain: bool,
+}
+
+pub struct MqttCommandExecutor {
+    config: Vec<MqttCommandConfig>,
+    sender: MqttSender,
+}
+
+impl MqttCommandExecutor {
+    /// Fails if a template is malformed or refers to a field the commands of its target don't have
+    pub fn new(mqtt_sender: MqttSender, config: Vec<MqttCommandConfig>) -> anyhow::Result<Self> {
+        for entry in &config {
+            for template in [&entry.topic, &entry.payload] {
+                PayloadTemplate::new(template, &entry.values)
+                    .validate(&entry.target)
+                    .with_context(|| format!("Invalid MQTT command config for {}", entry.target))?;
+            }
+        }
+
+        Ok(Self {
+            config,
+            sender: mqtt_sender,
+        })
+    }
+}
+
+impl CommandExecutor for MqttCommandExecutor {
+    fn system(&self) -> CommandTargetSystem {
+        CommandTargetSystem::Mqtt
+    }
+
+    fn command_targets(&self) -> Vec<CommandTarget> {
+        self.config.iter().map(|c| c.target.clone()).collect()
+    }
+
+    fn execute_command<'a>(&'a self, command: &'a Command) -> BoxFuture<'a, anyhow::Result<bool>> {
+        Box::pin(self.execute(command))
+    }
+}
+
+impl MqttCommandExecutor {
+    #[tracing::instrument(name = "execute_command MQTT", ret, skip(self))]
+    async fn execute(&self, command: &Command) -> anyhow::Result<bool> {
+        let cmd_target: CommandTarget = command.into();
+        let Some(config) = self.config.iter().find(|c| c.target == cmd_target) else {
+            return Ok(false);
+        };
+
+        let topic = PayloadTemplate::new(&config.topic, &config.values).render(command)?;
+        let payload = PayloadTemplate::new(&config.payload, &config.values).render(command)?;
+
+        if config.retain {
+            self.sender.send_retained(&topic, payload).await?;
+        } else {
+            self.sender.send_transient(&topic, payload).await?;
+        }
+
+        CommandMetric::Executed {
+            device_id: topic,
+            system: CommandTargetSystem::Mqtt,
+        }
+        .record();
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use config::{Config, File, FileFormat};
+
+    use super::*;
+    use crate::command::PowerToggle;
+
+    #[derive(Deserialize)]
+    struct Settings {
+        mqtt_commands: Vec<MqttCommandConfig>,
+    }
+
+    #[test]
+    fn config_is_read_from_toml() {
+        let toml = r#"
+            [[mqtt_commands]]
+            target = { type = "set_power", device = "dehumidifier" }
+            topic = "zigbee2mqtt/bathroom/dehumidifier_plug/set"
+            payload = '{"state": "{{power_on}}"}'
+            values.power_on = { true = "ON", false = "OFF" }
+        "#;
+
+        let settings: Settings = Config::builder()
+            .add_source(File::from_str(toml, FileFormat::Toml))
+            .build()
+            .and_then(|c| c.try_deserialize())
+            .unwrap_or_else(|e| panic!("Invalid config: {e}"));
+
+        let config = &settings.mqtt_commands[0];
+        assert_eq!(
+            config.target,
+            CommandTarget::SetPower {
+                device: PowerToggle::Dehumidifier
+            }
+        );
+        assert_eq!(config.values["power_on"]["true"], "ON");
+        assert!(!config.retain);
+    }
+}
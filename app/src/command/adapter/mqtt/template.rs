@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use serde_json::Value;
+
+use crate::command::{Command, CommandTarget};
+
+/// Text with `{{field}}` placeholders. Fields are looked up in the serialized command, nested
+/// fields are separated by dots, like `{{target_state.temperature}}`. Strings are inserted without
+/// quotes, everything else as JSON.
+pub struct PayloadTemplate<'a> {
+    template: &'a str,
+    values: &'a HashMap<String, HashMap<String, String>>,
+}
+
+impl<'a> PayloadTemplate<'a> {
+    pub fn new(template: &'a str, values: &'a HashMap<String, HashMap<String, String>>) -> Self {
+        Self { template, values }
+    }
+
+    /// Checks that the template is well-formed and that every placeholder is a field of the commands of `target`.
+    /// Nested fields are only checked up to the first dot, as their presence depends on the command value.
+    pub fn validate(&self, target: &CommandTarget) -> anyhow::Result<()> {
+        let target_fields = serde_json::to_value(target)?;
+        let mut rest = self.template;
+
+        while let Some((start, end)) = self.next_placeholder(rest)? {
+            let field = rest[start + 2..end].trim();
+            let top_level = field.split('.').next().unwrap_or(field);
+
+            if target_fields.get(top_level).is_none() && !command_fields(target).contains(&top_level) {
+                anyhow::bail!("Commands of {} have no field {} used in template {}", target, field, self.template);
+            }
+
+            rest = &rest[end + 2..];
+        }
+
+        Ok(())
+    }
+
+    pub fn render(&self, command: &Command) -> anyhow::Result<String> {
+        let fields = serde_json::to_value(command)?;
+        let mut result = String::with_capacity(self.template.len());
+        let mut rest = self.template;
+
+        while let Some((start, end)) = self.next_placeholder(rest)? {
+            result.push_str(&rest[..start]);
+            result.push_str(&self.resolve(rest[start + 2..end].trim(), &fields)?);
+            rest = &rest[end + 2..];
+        }
+
+        result.push_str(rest);
+        Ok(result)
+    }
+
+    //start of `{{` and start of `}}` of the next placeholder in `rest`
+    fn next_placeholder(&self, rest: &str) -> anyhow::Result<Option<(usize, usize)>> {
+        let Some(start) = rest.find("{{") else {
+            return Ok(None);
+        };
+
+        let end = rest[start..]
+            .find("}}")
+            .map(|end| start + end)
+            .with_context(|| format!("Unclosed placeholder in template {}", self.template))?;
+
+        Ok(Some((start, end)))
+    }
+
+    fn resolve(&self, field: &str, fields: &Value) -> anyhow::Result<String> {
+        let value = field
+            .split('.')
+            .try_fold(fields, |value, key| value.get(key))
+            .with_context(|| format!("Command {} has no field {}", fields, field))?;
+
+        let rendered = match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+
+        Ok(self
+            .values
+            .get(field)
+            .and_then(|replacements| replacements.get(&rendered))
+            .cloned()
+            .unwrap_or(rendered))
+    }
+}
+
+//Fields of the command on top of the ones identifying the target
+fn command_fields(target: &CommandTarget) -> &'static [&'static str] {
+    match target {
+        CommandTarget::SetPower { .. } => &["power_on"],
+        CommandTarget::SetHeating { .. } => &["target_state"],
+        CommandTarget::PushNotify { .. } => &["action"],
+        CommandTarget::SetEnergySaving { .. } => &["on"],
+        CommandTarget::ControlFan { .. } => &["speed"],
+        CommandTarget::OpenDoor { .. } => &[],
+        CommandTarget::SetLock { .. } => &["locked"],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::{Fan, HeatingTargetState, PowerToggle};
+    use crate::core::domain::Radiator;
+    use crate::core::unit::FanAirflow;
+
+    #[test]
+    fn renders_fields_with_value_replacements() {
+        let values = HashMap::from([(
+            "power_on".to_string(),
+            HashMap::from([
+                ("true".to_string(), "ON".to_string()),
+                ("false".to_string(), "OFF".to_string()),
+            ]),
+        )]);
+        let command = Command::SetPower {
+            device: PowerToggle::Dehumidifier,
+            power_on: true,
+        };
+
+        let rendered = PayloadTemplate::new(r#"{"state": "{{power_on}}", "device": "{{ device }}"}"#, &values)
+            .render(&command)
+            .unwrap_or_else(|e| panic!("Rendering failed: {e}"));
+
+        assert_eq!(rendered, r#"{"state": "ON", "device": "dehumidifier"}"#);
+    }
+
+    #[test]
+    fn renders_nested_fields_and_fails_on_unknown_ones() {
+        let values = HashMap::new();
+        let command = Command::SetHeating {
+            device: Radiator::Bedroom,
+            target_state: HeatingTargetState::Off,
+        };
+
+        let template = PayloadTemplate::new("{{device}}/set:{{target_state.mode}}", &values);
+        assert!(template.render(&command).is_ok_and(|r| r == "bedroom/set:off"));
+
+        let fan = Command::ControlFan {
+            device: Fan::LivingRoomAirPurifier,
+            speed: FanAirflow::Off,
+        };
+        assert!(PayloadTemplate::new("{{power_on}}", &values).render(&fan).is_err());
+    }
+
+    #[test]
+    fn validates_fields_against_target() {
+        let values = HashMap::new();
+        let target = CommandTarget::SetHeating {
+            device: Radiator::Bedroom,
+        };
+
+        assert!(
+            PayloadTemplate::new("{{device}}/set:{{target_state.mode}}", &values)
+                .validate(&target)
+                .is_ok()
+        );
+        assert!(PayloadTemplate::new("{{power_on}}", &values).validate(&target).is_err());
+        assert!(PayloadTemplate::new("{{device", &values).validate(&target).is_err());
+    }
+}
@@ -1,11 +1,19 @@
 use super::NukiCommandTarget;
 use crate::command::{CommandTarget, Lock};
 
-pub fn default_nuki_command_config() -> Vec<(CommandTarget, NukiCommandTarget)> {
-    vec![(
-        CommandTarget::OpenDoor {
-            device: Lock::BuildingEntrance,
-        },
-        NukiCommandTarget::Opener("1CC90CCA"),
-    )]
+pub fn default_nuki_command_config(smart_lock_id: &str) -> Vec<(CommandTarget, NukiCommandTarget)> {
+    vec![
+        (
+            CommandTarget::OpenDoor {
+                device: Lock::BuildingEntrance,
+            },
+            NukiCommandTarget::Opener("1CC90CCA".to_string()),
+        ),
+        (
+            CommandTarget::SetLock {
+                device: Lock::ApartmentDoor,
+            },
+            NukiCommandTarget::SmartLock(smart_lock_id.to_string()),
+        ),
+    ]
 }
@@ -1,14 +1,16 @@
 mod config;
 
 use super::metrics::*;
-use crate::command::adapter::CommandExecutor;
-use crate::command::{Command, CommandTarget, Lock};
+use crate::command::adapter::{CommandExecutor, TransientError};
+use crate::command::{Command, CommandTarget};
+use futures::future::BoxFuture;
 use infrastructure::HttpClientConfig;
 use reqwest_middleware::ClientWithMiddleware;
 
 #[derive(Debug, Clone)]
 enum NukiCommandTarget {
-    Opener(&'static str),
+    Opener(String),
+    SmartLock(String),
 }
 
 pub struct NukiCommandExecutor {
@@ -20,12 +22,12 @@ pub struct NukiCommandExecutor {
 
 impl NukiCommandExecutor {
     #[allow(clippy::expect_used)]
-    pub fn new(bridge_url: &str, token: &str) -> Self {
+    pub fn new(bridge_url: &str, token: &str, smart_lock_id: &str) -> Self {
         let client = HttpClientConfig::new(None)
             .new_tracing_client()
             .expect("Error initializing HTTP client for Nuki Bridge");
 
-        let config = config::default_nuki_command_config();
+        let config = config::default_nuki_command_config(smart_lock_id);
 
         Self {
             client,
@@ -37,37 +39,61 @@ impl NukiCommandExecutor {
 }
 
 impl CommandExecutor for NukiCommandExecutor {
+    fn system(&self) -> CommandTargetSystem {
+        CommandTargetSystem::Nuki
+    }
+
+    fn command_targets(&self) -> Vec<CommandTarget> {
+        self.config.iter().map(|(target, _)| target.clone()).collect()
+    }
+
+    fn execute_command<'a>(&'a self, command: &'a Command) -> BoxFuture<'a, anyhow::Result<bool>> {
+        Box::pin(self.execute(command))
+    }
+}
+
+impl NukiCommandExecutor {
     #[tracing::instrument(name = "execute_command NUKI", ret, skip(self))]
-    async fn execute_command(&self, command: &Command) -> anyhow::Result<bool> {
+    async fn execute(&self, command: &Command) -> anyhow::Result<bool> {
         let cmd_target: CommandTarget = command.into();
         let nuki_target = self
             .config
             .iter()
             .find_map(|(cmd, nuki)| if cmd == &cmd_target { Some(nuki) } else { None });
 
-        let Some(NukiCommandTarget::Opener(nuki_id)) = nuki_target else {
-            return Ok(false);
-        };
-
-        // Confirm command is OpenDoor (compile-time safety)
-        let Command::OpenDoor {
-            device: Lock::BuildingEntrance,
-        } = command
-        else {
-            anyhow::bail!("Mismatch between command and Nuki target {:?}", nuki_target);
+        // deviceType: 2 = opener, 4 = smart lock 3.0; action: 1 = unlock, 2 = lock, 3 = electric strike actuation
+        let (nuki_id, device_type, action) = match (nuki_target, command) {
+            (Some(NukiCommandTarget::Opener(nuki_id)), Command::OpenDoor { .. }) => (nuki_id, 2, 3),
+            (Some(NukiCommandTarget::SmartLock(nuki_id)), Command::SetLock { locked, .. }) => {
+                (nuki_id, 4, if *locked { 2 } else { 1 })
+            }
+            (None, _) => return Ok(false),
+            _ => anyhow::bail!("Mismatch between command and Nuki target {:?}", nuki_target),
         };
 
         let url = format!(
-            "{}/lockAction?nukiId={}&deviceType=2&action=3&token={}",
-            self.bridge_url, nuki_id, self.token
+            "{}/lockAction?nukiId={}&deviceType={}&action={}&token={}",
+            self.bridge_url, nuki_id, device_type, action, self.token
         );
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.client.get(&url).send().await.map_err(|e| {
+            // URL contains the token
+            let e = match e {
+                reqwest_middleware::Error::Reqwest(e) => e.without_url().to_string(),
+                e => e.to_string(),
+            };
+            TransientError::new(format!("Error calling Nuki bridge: {e}"))
+        })?;
+
+        if response.status().is_server_error() {
+            return Err(TransientError::new(format!("Nuki bridge returned {}", response.status())).into());
+        }
+
         let body: serde_json::Value = response.json().await?;
 
         if body.get("success").and_then(|v| v.as_bool()) == Some(true) {
             CommandMetric::Executed {
-                device_id: (*nuki_id).to_string(),
+                device_id: nuki_id.clone(),
                 system: CommandTargetSystem::Nuki,
             }
             .record();
@@ -77,3 +103,38 @@ impl CommandExecutor for NukiCommandExecutor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::Lock;
+    use mockito::{Matcher, Server};
+
+    #[tokio::test]
+    async fn set_lock_sends_lock_action_to_smart_lock() -> anyhow::Result<()> {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/lockAction")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("nukiId".into(), "2A7F3C14".into()),
+                Matcher::UrlEncoded("deviceType".into(), "4".into()),
+                Matcher::UrlEncoded("action".into(), "2".into()),
+            ]))
+            .with_status(200)
+            .with_body(r#"{"success": true, "batteryCritical": false}"#)
+            .create_async()
+            .await;
+
+        let executor = NukiCommandExecutor::new(&server.url(), "secret", "2A7F3C14");
+        let handled = executor
+            .execute(&Command::SetLock {
+                device: Lock::ApartmentDoor,
+                locked: true,
+            })
+            .await?;
+
+        assert!(handled);
+        mock.assert_async().await;
+        Ok(())
+    }
+}
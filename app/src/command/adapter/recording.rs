@@ -0,0 +1,111 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use futures::future::BoxFuture;
+
+use crate::{
+    command::{Command, CommandTarget},
+    core::sync::lock_ignoring_poison,
+};
+
+use super::{CommandExecutor, CommandTargetSystem, TransientError};
+
+/// Scripted result of an execution of the `RecordingCommandExecutor`
+#[derive(Debug, Clone)]
+pub enum ExecutorOutcome {
+    Success,
+    Error(String),
+    TransientError(String),
+    /// Waits for the given duration, then fails with a transient error
+    Timeout(std::time::Duration),
+}
+
+/// In-process executor for tests. Claims all configured command targets, records every command it
+/// receives and answers with scripted outcomes. Clones share recordings and script.
+#[derive(Clone)]
+pub struct RecordingCommandExecutor {
+    targets: Vec<CommandTarget>,
+    inner: Arc<Mutex<RecordingState>>,
+}
+
+struct RecordingState {
+    recorded: Vec<Command>,
+    scripted: VecDeque<ExecutorOutcome>,
+    default_outcome: ExecutorOutcome,
+}
+
+impl Default for RecordingCommandExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RecordingCommandExecutor {
+    pub fn new() -> Self {
+        Self::with_targets(super::executor_command_targets())
+    }
+
+    pub fn with_targets(targets: Vec<CommandTarget>) -> Self {
+        Self {
+            targets,
+            inner: Arc::new(Mutex::new(RecordingState {
+                recorded: vec![],
+                scripted: VecDeque::new(),
+                default_outcome: ExecutorOutcome::Success,
+            })),
+        }
+    }
+
+    /// Outcome of all executions without a scripted outcome
+    pub fn set_default_outcome(&self, outcome: ExecutorOutcome) {
+        self.with_state(|state| state.default_outcome = outcome);
+    }
+
+    /// Outcome of the next execution, queued after outcomes scripted before
+    pub fn script(&self, outcome: ExecutorOutcome) {
+        self.with_state(|state| state.scripted.push_back(outcome));
+    }
+
+    pub fn recorded(&self) -> Vec<Command> {
+        self.with_state(|state| state.recorded.clone())
+    }
+
+    fn with_state<R>(&self, f: impl FnOnce(&mut RecordingState) -> R) -> R {
+        let mut state = lock_ignoring_poison(&self.inner);
+        f(&mut state)
+    }
+}
+
+impl CommandExecutor for RecordingCommandExecutor {
+    fn system(&self) -> CommandTargetSystem {
+        CommandTargetSystem::Recording
+    }
+
+    fn command_targets(&self) -> Vec<CommandTarget> {
+        self.targets.clone()
+    }
+
+    fn execute_command<'a>(&'a self, command: &'a Command) -> BoxFuture<'a, anyhow::Result<bool>> {
+        let outcome = self.with_state(|state| {
+            state.recorded.push(command.clone());
+            state
+                .scripted
+                .pop_front()
+                .unwrap_or_else(|| state.default_outcome.clone())
+        });
+
+        Box::pin(async move {
+            match outcome {
+                ExecutorOutcome::Success => Ok(true),
+                ExecutorOutcome::Error(message) => anyhow::bail!(message),
+                ExecutorOutcome::TransientError(message) => Err(TransientError::new(message).into()),
+                ExecutorOutcome::Timeout(duration) => {
+                    tokio::time::sleep(duration).await;
+                    Err(TransientError::new(format!("Timeout after {duration:?}")).into())
+                }
+            }
+        })
+    }
+}
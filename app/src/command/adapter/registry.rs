@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use crate::command::{Command, CommandTarget};
+
+use super::{CommandExecutor, CommandTargetSystem};
+
+/// Routing table from command target to the executor responsible for it. Built once at startup,
+/// every target must be claimed by at most one executor.
+pub struct CommandExecutorRegistry {
+    executors: Vec<Box<dyn CommandExecutor>>,
+    routes: HashMap<CommandTarget, usize>,
+}
+
+impl CommandExecutorRegistry {
+    pub fn new(executors: Vec<Box<dyn CommandExecutor>>) -> anyhow::Result<Self> {
+        let mut routes: HashMap<CommandTarget, usize> = HashMap::new();
+        let mut conflicts = vec![];
+
+        for (index, executor) in executors.iter().enumerate() {
+            for target in executor.command_targets() {
+                match routes.get(&target) {
+                    Some(&existing) if existing != index => conflicts.push(format!(
+                        "{} claimed by {} and {}",
+                        target,
+                        executors[existing].system(),
+                        executor.system()
+                    )),
+                    Some(_) => {}
+                    None => {
+                        routes.insert(target, index);
+                    }
+                }
+            }
+        }
+
+        if !conflicts.is_empty() {
+            anyhow::bail!("Conflicting command executor routes: {}", conflicts.join(", "));
+        }
+
+        Ok(Self { executors, routes })
+    }
+
+    pub fn executor_for(&self, target: &CommandTarget) -> Option<&dyn CommandExecutor> {
+        self.routes.get(target).map(|&index| self.executors[index].as_ref())
+    }
+
+    pub fn command_targets(&self) -> Vec<CommandTarget> {
+        self.routes.keys().cloned().collect()
+    }
+
+    /// Executes the command via the executor routed for its target. Returns `None` if no executor
+    /// is responsible or the executor did not handle the command.
+    pub async fn execute(&self, command: &Command) -> Option<(CommandTargetSystem, anyhow::Result<()>)> {
+        let executor = self.executor_for(&CommandTarget::from(command))?;
+
+        match executor.execute_command(command).await {
+            Ok(true) => Some((executor.system(), Ok(()))),
+            Ok(false) => None,
+            Err(e) => Some((executor.system(), Err(e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::BoxFuture;
+
+    use super::*;
+    use crate::command::PowerToggle;
+
+    struct StaticExecutor {
+        system: CommandTargetSystem,
+        targets: Vec<CommandTarget>,
+    }
+
+    impl CommandExecutor for StaticExecutor {
+        fn system(&self) -> CommandTargetSystem {
+            self.system
+        }
+
+        fn command_targets(&self) -> Vec<CommandTarget> {
+            self.targets.clone()
+        }
+
+        fn execute_command<'a>(&'a self, _: &'a Command) -> BoxFuture<'a, anyhow::Result<bool>> {
+            Box::pin(async { Ok(true) })
+        }
+    }
+
+    fn executor(system: CommandTargetSystem, targets: Vec<CommandTarget>) -> Box<dyn CommandExecutor> {
+        Box::new(StaticExecutor { system, targets })
+    }
+
+    #[tokio::test]
+    async fn routes_command_to_claiming_executor() {
+        let registry = CommandExecutorRegistry::new(vec![
+            executor(
+                CommandTargetSystem::Tasmota,
+                vec![CommandTarget::SetPower {
+                    device: PowerToggle::Dehumidifier,
+                }],
+            ),
+            executor(
+                CommandTargetSystem::HomeAssistant,
+                vec![CommandTarget::SetPower {
+                    device: PowerToggle::InfraredHeater,
+                }],
+            ),
+        ])
+        .unwrap_or_else(|e| panic!("Unexpected conflict: {e}"));
+
+        let res = registry
+            .execute(&Command::SetPower {
+                device: PowerToggle::InfraredHeater,
+                power_on: true,
+            })
+            .await;
+
+        assert!(matches!(res, Some((CommandTargetSystem::HomeAssistant, Ok(())))));
+    }
+
+    #[test]
+    fn default_executor_configs_do_not_conflict() {
+        let targets = super::super::executor_command_targets();
+        let unique: std::collections::HashSet<_> = targets.iter().collect();
+
+        assert_eq!(unique.len(), targets.len());
+    }
+
+    #[test]
+    fn target_claimed_twice_is_a_conflict() {
+        let target = CommandTarget::SetPower {
+            device: PowerToggle::Dehumidifier,
+        };
+
+        let result = CommandExecutorRegistry::new(vec![
+            executor(CommandTargetSystem::Tasmota, vec![target.clone()]),
+            executor(CommandTargetSystem::Z2M, vec![target]),
+        ]);
+
+        assert!(result.is_err_and(|e| e.to_string().contains("claimed by TASMOTA and Z2M")));
+    }
+}
@@ -1,6 +1,7 @@
 mod config;
 
 use super::CommandExecutor;
+use futures::future::BoxFuture;
 
 use crate::command::{Command, CommandTarget};
 
@@ -29,8 +30,22 @@ impl TasmotaCommandExecutor {
 }
 
 impl CommandExecutor for TasmotaCommandExecutor {
+    fn system(&self) -> CommandTargetSystem {
+        CommandTargetSystem::Tasmota
+    }
+
+    fn command_targets(&self) -> Vec<CommandTarget> {
+        self.config.iter().map(|(target, _)| target.clone()).collect()
+    }
+
+    fn execute_command<'a>(&'a self, command: &'a Command) -> BoxFuture<'a, anyhow::Result<bool>> {
+        Box::pin(self.execute(command))
+    }
+}
+
+impl TasmotaCommandExecutor {
     #[tracing::instrument(name = "execute_command TASMOTA", ret, skip(self))]
-    async fn execute_command(&self, command: &Command) -> anyhow::Result<bool> {
+    async fn execute(&self, command: &Command) -> anyhow::Result<bool> {
         let cmd_target: CommandTarget = command.into();
         let tasmota_target = self
             .config
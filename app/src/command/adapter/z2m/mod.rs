@@ -11,8 +11,9 @@ use crate::{
             metrics::{CommandMetric, CommandTargetSystem},
         },
     },
-    core::math::round_to_one_decimal,
+    core::{ExpectedZ2mDevice, math::round_to_one_decimal},
 };
+use futures::future::BoxFuture;
 use infrastructure::MqttSender;
 use serde_json::json;
 
@@ -37,9 +38,58 @@ impl Z2mCommandExecutor {
     }
 }
 
+impl Z2mCommandTarget {
+    fn friendly_name(&self) -> &'static str {
+        match self {
+            Z2mCommandTarget::SonoffThermostat(name) | Z2mCommandTarget::PowerPlug(name) => name,
+        }
+    }
+
+    /// Properties set by the executor
+    fn required_features(&self) -> &'static [&'static str] {
+        match self {
+            Z2mCommandTarget::SonoffThermostat(_) => &[
+                "system_mode",
+                "occupied_heating_setpoint",
+                "valve_opening_degree",
+                "valve_closing_degree",
+                "temperature_accuracy",
+            ],
+            Z2mCommandTarget::PowerPlug(_) => &["state"],
+        }
+    }
+}
+
+pub fn expected_devices() -> Vec<ExpectedZ2mDevice> {
+    config::default_z2m_command_config()
+        .into_iter()
+        .map(|(target, z2m_target)| {
+            ExpectedZ2mDevice::new(
+                z2m_target.friendly_name(),
+                format!("command {}", target),
+                z2m_target.required_features(),
+            )
+        })
+        .collect()
+}
+
 impl CommandExecutor for Z2mCommandExecutor {
+    fn system(&self) -> CommandTargetSystem {
+        CommandTargetSystem::Z2M
+    }
+
+    fn command_targets(&self) -> Vec<CommandTarget> {
+        self.config.iter().map(|(target, _)| target.clone()).collect()
+    }
+
+    fn execute_command<'a>(&'a self, command: &'a Command) -> BoxFuture<'a, anyhow::Result<bool>> {
+        Box::pin(self.execute(command))
+    }
+}
+
+impl Z2mCommandExecutor {
     #[tracing::instrument(name = "execute_command Z2M", ret, skip(self))]
-    async fn execute_command(&self, command: &Command) -> anyhow::Result<bool> {
+    async fn execute(&self, command: &Command) -> anyhow::Result<bool> {
         let cmd_target: CommandTarget = command.into();
         let z2m_target = self
             .config
@@ -0,0 +1,100 @@
+use std::sync::Mutex;
+
+use crate::{
+    command::{CommandExecution, CommandTarget, adapter::metrics::CommandTargetSystem},
+    core::{sync::lock_ignoring_poison, time::DateTime},
+};
+
+/// Successfully sent command waiting to be reflected in home state
+#[derive(Debug, Clone)]
+pub struct PendingConfirmation {
+    pub command_exec: CommandExecution,
+    pub system: CommandTargetSystem,
+    pub sent_at: DateTime,
+    pub deadline: DateTime,
+}
+
+/// Commands awaiting confirmation. A newer command for the same target replaces the older one,
+/// which then keeps its transport-level state.
+#[derive(Default)]
+pub struct ConfirmationTracker {
+    pending: Mutex<Vec<PendingConfirmation>>,
+}
+
+impl ConfirmationTracker {
+    pub fn add(&self, confirmation: PendingConfirmation) {
+        let target = CommandTarget::from(&confirmation.command_exec.command);
+
+        self.with_pending(|pending| {
+            pending.retain(|p| CommandTarget::from(&p.command_exec.command) != target);
+            pending.push(confirmation);
+        });
+    }
+
+    pub fn take_all(&self) -> Vec<PendingConfirmation> {
+        self.with_pending(std::mem::take)
+    }
+
+    /// Puts back unresolved confirmations, unless a newer command for the same target was added meanwhile
+    pub fn restore(&self, confirmations: Vec<PendingConfirmation>) {
+        self.with_pending(|pending| {
+            for confirmation in confirmations {
+                let target = CommandTarget::from(&confirmation.command_exec.command);
+                if !pending
+                    .iter()
+                    .any(|p| CommandTarget::from(&p.command_exec.command) == target)
+                {
+                    pending.push(confirmation);
+                }
+            }
+        });
+    }
+
+    fn with_pending<R>(&self, f: impl FnOnce(&mut Vec<PendingConfirmation>) -> R) -> R {
+        let mut pending = lock_ignoring_poison(&self.pending);
+        f(&mut pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::{Command, CommandState, PowerToggle};
+    use crate::core::id::ExternalId;
+    use crate::t;
+
+    fn pending(id: i64, power_on: bool) -> PendingConfirmation {
+        PendingConfirmation {
+            command_exec: CommandExecution {
+                id,
+                command: Command::SetPower {
+                    device: PowerToggle::Dehumidifier,
+                    power_on,
+                },
+                state: CommandState::Success,
+                created: t!(now),
+                source: ExternalId::new("test", "source"),
+                user_trigger_id: None,
+                correlation_id: None,
+                attempts: 1,
+                next_retry_at: None,
+            },
+            system: CommandTargetSystem::Tasmota,
+            sent_at: t!(now),
+            deadline: t!(in 1 minutes),
+        }
+    }
+
+    #[test]
+    fn newer_command_for_same_target_replaces_pending_one() {
+        let tracker = ConfirmationTracker::default();
+
+        tracker.add(pending(1, true));
+        let in_check = tracker.take_all();
+        tracker.add(pending(2, false));
+        tracker.restore(in_check);
+
+        let ids: Vec<_> = tracker.take_all().iter().map(|p| p.command_exec.id).collect();
+        assert_eq!(ids, vec![2]);
+    }
+}
@@ -2,14 +2,14 @@ use crate::command::{CommandClient, HeatingTargetState};
 use crate::core::range::Range;
 use crate::core::time::Duration;
 use crate::core::unit::{DegreeCelsius, FanAirflow, Percent};
-use crate::home_state::{FanActivity, HeatingDemandLimit, PowerAvailable, SetPoint, StateSnapshot};
+use crate::home_state::{FanActivity, HeatingDemandLimit, Locked, PowerAvailable, SetPoint, StateSnapshot};
 use crate::t;
 use anyhow::Result;
 
 use crate::home_state::EnergySaving;
 
 use super::{
-    Command, CommandExecution, CommandTarget, EnergySavingDevice, Fan, Notification, NotificationAction,
+    Command, CommandExecution, CommandTarget, EnergySavingDevice, Fan, Lock, Notification, NotificationAction,
     NotificationRecipient, NotificationTarget, PowerToggle, Radiator,
 };
 
@@ -51,6 +51,7 @@ impl Command {
                 //Only a short trigger, no permanent state change
                 Ok(false)
             }
+            Command::SetLock { device, locked } => is_set_lock_reflected_in_state(device, *locked, snapshot),
         }
     }
 
@@ -62,6 +63,24 @@ impl Command {
             Command::ControlFan { .. } => Some(t!(3 minutes)),
             Command::PushNotify { .. } => None,
             Command::OpenDoor { .. } => None,
+            Command::SetLock { .. } => Some(t!(1 minutes)),
+        }
+    }
+
+    /// How long to wait for a successfully sent command to be reflected in home state.
+    /// `None` for commands without an observable state.
+    pub fn confirmation_timeout(&self) -> Option<Duration> {
+        match self {
+            //Thermostats only report on their next wake-up
+            Command::SetHeating { .. } => Some(t!(10 minutes)),
+            Command::SetPower { .. } => Some(t!(1 minutes)),
+            Command::ControlFan { .. } => Some(t!(2 minutes)),
+            //Not reported by the device, only derived from sent commands
+            Command::SetEnergySaving { .. } => None,
+            Command::PushNotify { .. } => None,
+            Command::OpenDoor { .. } => None,
+            //Bridge state is polled, so allow for the polling interval
+            Command::SetLock { .. } => Some(t!(3 minutes)),
         }
     }
 }
@@ -100,6 +119,15 @@ fn is_set_power_reflected_in_state(device: &PowerToggle, power_on: bool, snapsho
     Ok(powered == power_on)
 }
 
+fn is_set_lock_reflected_in_state(device: &Lock, locked: bool, snapshot: &StateSnapshot) -> Result<bool> {
+    let locked_item = match device {
+        Lock::ApartmentDoor => Locked::ApartmentDoor,
+        Lock::BuildingEntrance => anyhow::bail!("Lock state of {} is not available", device),
+    };
+
+    Ok(snapshot.try_get(locked_item)?.value == locked)
+}
+
 async fn is_push_notify_reflected_in_state(
     recipient: &NotificationRecipient,
     notification: &Notification,
@@ -1,4 +1,5 @@
 mod command_state;
+mod retry;
 
 use crate::core::domain::Radiator;
 use crate::core::range::Range;
@@ -38,6 +39,10 @@ pub enum Command {
     OpenDoor {
         device: Lock,
     },
+    SetLock {
+        device: Lock,
+        locked: bool,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, derive_more::Display, Id)]
@@ -63,6 +68,9 @@ pub enum CommandTarget {
 
     #[display("OpenDoor[{}]", device)]
     OpenDoor { device: Lock },
+
+    #[display("SetLock[{}]", device)]
+    SetLock { device: Lock },
 }
 
 impl From<Command> for CommandTarget {
@@ -87,6 +95,7 @@ impl From<&Command> for CommandTarget {
             Command::SetEnergySaving { device, .. } => CommandTarget::SetEnergySaving { device: device.clone() },
             Command::ControlFan { device, .. } => CommandTarget::ControlFan { device: device.clone() },
             Command::OpenDoor { device } => CommandTarget::OpenDoor { device: device.clone() },
+            Command::SetLock { device, .. } => CommandTarget::SetLock { device: device.clone() },
         }
     }
 }
@@ -100,6 +109,8 @@ pub struct CommandExecution {
     pub source: ExternalId,
     pub user_trigger_id: Option<UserTriggerId>,
     pub correlation_id: Option<CorrelationId>,
+    pub attempts: u32,
+    pub next_retry_at: Option<DateTime>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -108,6 +119,10 @@ pub enum CommandState {
     Pending,
     InProgress,
     Success,
+    /// Sent successfully and reflected in home state within the confirmation timeout
+    Confirmed,
+    /// Sent successfully, but not reflected in home state within the confirmation timeout
+    Unconfirmed,
     Error(String),
 }
 
@@ -160,6 +175,7 @@ impl std::fmt::Display for HeatingTargetState {
 #[serde(rename_all = "snake_case")]
 pub enum Notification {
     WindowOpened,
+    BatteryLow,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Display, Id, EnumVariants)]
@@ -211,12 +227,13 @@ pub enum Fan {
 }
 
 //
-// OPEN DOOR
+// OPEN DOOR / SET LOCK
 //
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Display, Id, EnumVariants)]
 #[serde(rename_all = "snake_case")]
 pub enum Lock {
     BuildingEntrance,
+    ApartmentDoor,
 }
 
 #[cfg(test)]
@@ -0,0 +1,61 @@
+use crate::core::time::Duration;
+use crate::t;
+
+use super::Command;
+
+/// How often and how fast a failed command is retried. Only transient errors are retried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32, initial_backoff: Duration) -> Self {
+        Self {
+            max_attempts,
+            initial_backoff,
+        }
+    }
+
+    /// Delay before the next attempt after `attempts` failed ones, or `None` if exhausted.
+    /// Doubles with every attempt.
+    pub fn backoff_after(&self, attempts: u32) -> Option<Duration> {
+        if attempts == 0 || attempts >= self.max_attempts {
+            return None;
+        }
+
+        let factor = 2_i64.pow(attempts - 1);
+        Some(Duration::seconds(self.initial_backoff.as_secs() * factor))
+    }
+}
+
+impl Command {
+    pub fn retry_policy(&self) -> RetryPolicy {
+        match self {
+            Command::SetPower { .. } => RetryPolicy::new(3, t!(5 seconds)),
+            Command::SetHeating { .. } => RetryPolicy::new(4, t!(10 seconds)),
+            Command::SetEnergySaving { .. } => RetryPolicy::new(3, t!(10 seconds)),
+            Command::ControlFan { .. } => RetryPolicy::new(3, t!(10 seconds)),
+            Command::PushNotify { .. } => RetryPolicy::new(3, t!(30 seconds)),
+            //Buzzer is only useful while someone is waiting at the door
+            Command::OpenDoor { .. } => RetryPolicy::new(2, t!(2 seconds)),
+            Command::SetLock { .. } => RetryPolicy::new(2, t!(5 seconds)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_until_attempts_are_exhausted() {
+        let policy = RetryPolicy::new(4, t!(5 seconds));
+
+        assert_eq!(policy.backoff_after(1), Some(t!(5 seconds)));
+        assert_eq!(policy.backoff_after(2), Some(t!(10 seconds)));
+        assert_eq!(policy.backoff_after(3), Some(t!(20 seconds)));
+        assert_eq!(policy.backoff_after(4), None);
+    }
+}
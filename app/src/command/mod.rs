@@ -1,12 +1,19 @@
 mod adapter;
+mod confirmation;
 mod domain;
 mod service;
 
+pub use adapter::MqttCommandConfig;
+#[cfg(test)]
+pub use adapter::executor_command_targets;
+#[cfg(test)]
+pub use adapter::{ExecutorOutcome, RecordingCommandExecutor};
 pub use domain::*;
 
 use std::sync::Arc;
 
-use adapter::db::CommandRepository;
+use adapter::{CommandExecutorRegistry, db::CommandRepository};
+use anyhow::Context as _;
 use infrastructure::{EventBus, EventListener, Mqtt, TraceContext};
 use service::CommandService;
 use sqlx::PgPool;
@@ -24,7 +31,8 @@ pub enum CommandEvent {
 
 pub struct CommandModule {
     service: Arc<CommandService>,
-    z2m_sensor_sync_runner: adapter::z2m::Z2mSensorSyncRunner,
+    z2m_sensor_sync_runner: Option<adapter::z2m::Z2mSensorSyncRunner>,
+    confirmation_listener: EventListener<HomeStateEvent>,
 }
 
 #[derive(Clone)]
@@ -32,6 +40,11 @@ pub struct CommandClient {
     service: Arc<CommandService>,
 }
 
+/// Zigbee2MQTT devices commands are sent to
+pub fn expected_z2m_devices() -> Vec<crate::core::ExpectedZ2mDevice> {
+    adapter::z2m::expected_devices()
+}
+
 impl CommandModule {
     #[allow(clippy::too_many_arguments)]
     pub async fn new(
@@ -44,31 +57,59 @@ impl CommandModule {
         ha_token: &str,
         nuki_url: &str,
         nuki_token: &str,
+        nuki_smart_lock_id: &str,
+        mqtt_commands: &[MqttCommandConfig],
         home_state_listener: EventListener<HomeStateEvent>,
-    ) -> Self {
+        confirmation_listener: EventListener<HomeStateEvent>,
+    ) -> anyhow::Result<Self> {
         let repo = CommandRepository::new(pool);
 
-        let tasmota_executor = adapter::TasmotaCommandExecutor::new(mqtt_client.sender(tasmota_event_topic));
-        let ha_executor = adapter::HomeAssistantCommandExecutor::new(ha_url, ha_token);
-        let z2m_executor = adapter::Z2mCommandExecutor::new(mqtt_client.sender(z2m_event_topic));
-        let nuki_executor = adapter::NukiCommandExecutor::new(nuki_url, nuki_token);
+        let executors = CommandExecutorRegistry::new(vec![
+            Box::new(adapter::TasmotaCommandExecutor::new(
+                mqtt_client.sender(tasmota_event_topic),
+            )),
+            Box::new(adapter::Z2mCommandExecutor::new(mqtt_client.sender(z2m_event_topic))),
+            Box::new(adapter::NukiCommandExecutor::new(nuki_url, nuki_token, nuki_smart_lock_id)),
+            Box::new(adapter::HomeAssistantCommandExecutor::new(ha_url, ha_token)),
+            Box::new(
+                adapter::MqttCommandExecutor::new(mqtt_client.sender(""), mqtt_commands.to_vec())
+                    .context("Invalid MQTT command config")?,
+            ),
+        ])
+        .context("Invalid command executor routing")?;
 
         let z2m_sensor_sync_runner =
             adapter::z2m::Z2mSensorSyncRunner::new(mqtt_client.sender(z2m_event_topic), home_state_listener);
 
+        let service = Arc::new(CommandService::new(repo, executors, event_bus.emitter()));
+
+        Ok(Self {
+            service,
+            z2m_sensor_sync_runner: Some(z2m_sensor_sync_runner),
+            confirmation_listener,
+        })
+    }
+
+    /// Module executing all commands via the given recording executor, without any external system
+    #[cfg(test)]
+    pub fn with_recording_executor(
+        event_bus: EventBus<CommandEvent>,
+        pool: PgPool,
+        executor: RecordingCommandExecutor,
+        confirmation_listener: EventListener<HomeStateEvent>,
+    ) -> anyhow::Result<Self> {
+        let executors = CommandExecutorRegistry::new(vec![Box::new(executor)])?;
         let service = Arc::new(CommandService::new(
-            repo,
-            tasmota_executor,
-            z2m_executor,
-            nuki_executor,
-            ha_executor,
+            CommandRepository::new(pool),
+            executors,
             event_bus.emitter(),
         ));
 
-        Self {
+        Ok(Self {
             service,
-            z2m_sensor_sync_runner,
-        }
+            z2m_sensor_sync_runner: None,
+            confirmation_listener,
+        })
     }
 
     pub fn client(&self) -> CommandClient {
@@ -77,8 +118,25 @@ impl CommandModule {
         }
     }
 
+    /// All command targets routed to an executor
+    pub fn command_targets(&self) -> Vec<CommandTarget> {
+        self.service.command_targets()
+    }
+
     pub async fn run(self) {
-        self.z2m_sensor_sync_runner.run().await;
+        let client = self.client();
+
+        let z2m_sensor_sync = async {
+            if let Some(runner) = self.z2m_sensor_sync_runner {
+                runner.run().await;
+            }
+        };
+
+        tokio::join!(
+            z2m_sensor_sync,
+            run_retries(self.service.clone()),
+            run_confirmations(self.service, client, self.confirmation_listener)
+        );
     }
 }
 
@@ -106,3 +164,27 @@ impl CommandClient {
         self.service.get_all_commands(from, until).await
     }
 }
+
+async fn run_retries(service: Arc<CommandService>) {
+    let mut timer = tokio::time::interval(std::time::Duration::from_secs(2));
+
+    loop {
+        timer.tick().await;
+
+        if let Err(e) = service.retry_due_commands().await {
+            tracing::error!("Error retrying failed commands: {:?}", e);
+        }
+    }
+}
+
+async fn run_confirmations(
+    service: Arc<CommandService>,
+    client: CommandClient,
+    mut home_state_events: EventListener<HomeStateEvent>,
+) {
+    loop {
+        if let Some(HomeStateEvent::SnapshotUpdated(snapshot)) = home_state_events.recv().await {
+            service.check_confirmations(&snapshot, &client).await;
+        }
+    }
+}
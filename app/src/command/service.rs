@@ -2,16 +2,15 @@ use infrastructure::{CorrelationId, EventEmitter};
 
 use crate::{
     command::{
-        Command, CommandEvent, CommandExecution, CommandState, CommandTarget,
-        adapter::{
-            CommandExecutor, HomeAssistantCommandExecutor, NukiCommandExecutor, TasmotaCommandExecutor,
-            Z2mCommandExecutor,
-        },
+        Command, CommandClient, CommandEvent, CommandExecution, CommandState, CommandTarget,
+        adapter::{CommandExecutorRegistry, is_transient, metrics::CommandMetric},
+        confirmation::{ConfirmationTracker, PendingConfirmation},
     },
     core::{
         id::ExternalId,
         time::{DateTime, DateTimeRange},
     },
+    home_state::StateSnapshot,
     t,
     trigger::UserTriggerId,
 };
@@ -20,29 +19,22 @@ use super::adapter::db::CommandRepository;
 
 pub struct CommandService {
     repo: CommandRepository,
-    tasmota_executor: TasmotaCommandExecutor,
-    z2m_executor: Z2mCommandExecutor,
-    nuki_executor: NukiCommandExecutor,
-    ha_executor: HomeAssistantCommandExecutor,
+    executors: CommandExecutorRegistry,
     event_tx: EventEmitter<CommandEvent>,
+    confirmations: ConfirmationTracker,
 }
 
 impl CommandService {
     pub fn new(
         repo: CommandRepository,
-        tasmota_executor: TasmotaCommandExecutor,
-        z2m_executor: Z2mCommandExecutor,
-        nuki_executor: NukiCommandExecutor,
-        ha_executor: HomeAssistantCommandExecutor,
+        executors: CommandExecutorRegistry,
         event_tx: EventEmitter<CommandEvent>,
     ) -> Self {
         Self {
             repo,
-            tasmota_executor,
-            z2m_executor,
-            nuki_executor,
-            ha_executor,
+            executors,
             event_tx,
+            confirmations: ConfirmationTracker::default(),
         }
     }
 
@@ -53,22 +45,61 @@ impl CommandService {
         user_trigger_id: Option<UserTriggerId>,
         correlation_id: Option<CorrelationId>,
     ) -> anyhow::Result<CommandExecution> {
-        let mut command_exec = self
+        let target = CommandTarget::from(&command);
+        match self.repo.cancel_retries(&target).await {
+            Ok(0) => {}
+            Ok(cancelled) => tracing::info!("Cancelled {} pending retries of {}", cancelled, target),
+            Err(e) => tracing::warn!("Failed to cancel pending retries of {}: {}", target, e),
+        }
+
+        let command_exec = self
             .repo
             .insert_command_for_processing(&command, &source, user_trigger_id, correlation_id)
             .await?;
 
+        Ok(self.execute_attempt(command_exec).await)
+    }
+
+    /// Executes all failed commands whose retry is due. Returns the number of retried commands.
+    pub async fn retry_due_commands(&self) -> anyhow::Result<usize> {
+        let due = self.repo.query_due_retries(t!(now)).await?;
+        let mut retried = 0;
+
+        for mut command_exec in due {
+            if !self.repo.start_retry(command_exec.id).await? {
+                continue;
+            }
+
+            command_exec.attempts += 1;
+            command_exec.next_retry_at = None;
+            tracing::info!(
+                "Retrying command {} ({:?}), attempt {}",
+                command_exec.id,
+                command_exec.command,
+                command_exec.attempts
+            );
+
+            self.execute_attempt(command_exec).await;
+            retried += 1;
+        }
+
+        Ok(retried)
+    }
+
+    async fn execute_attempt(&self, mut command_exec: CommandExecution) -> CommandExecution {
         let command_id = command_exec.id;
 
-        let res = match self.execute_via(&self.tasmota_executor, &command).await {
-            Some(r) => Some(r),
-            None => match self.execute_via(&self.z2m_executor, &command).await {
-                Some(r) => Some(r),
-                None => match self.execute_via(&self.nuki_executor, &command).await {
-                    Some(r) => Some(r),
-                    None => self.execute_via(&self.ha_executor, &command).await,
-                },
-            },
+        let res = self.executors.execute(&command_exec.command).await;
+        let system = res.as_ref().map(|(system, _)| *system);
+        let res = res.map(|(_, r)| r);
+
+        let next_retry_at = match &res {
+            Some(Err(e)) if is_transient(e) => command_exec
+                .command
+                .retry_policy()
+                .backoff_after(command_exec.attempts)
+                .map(|backoff| t!(now) + backoff),
+            _ => None,
         };
 
         let final_state = match res {
@@ -78,6 +109,7 @@ impl CommandService {
         };
 
         command_exec.state = final_state.clone();
+        command_exec.next_retry_at = next_retry_at;
 
         if let Err(e) = self.repo.set_command_state(command_id, final_state.clone()).await {
             tracing::warn!(
@@ -88,19 +120,105 @@ impl CommandService {
             );
         }
 
+        if let Some(next_retry_at) = next_retry_at {
+            tracing::info!("Scheduling retry of command {} at {}", command_id, next_retry_at);
+            if let Err(e) = self.repo.schedule_retry(command_id, next_retry_at).await {
+                tracing::warn!("Failed to schedule retry of command {}: {}", command_id, e);
+            }
+        }
+
+        if let (CommandState::Success, Some(system), Some(timeout)) =
+            (&command_exec.state, system, command_exec.command.confirmation_timeout())
+        {
+            let now = t!(now);
+            self.confirmations.add(PendingConfirmation {
+                command_exec: command_exec.clone(),
+                system,
+                sent_at: now,
+                deadline: now + timeout,
+            });
+        }
+
         self.event_tx.send(CommandEvent::CommandExecuted(command_exec.clone()));
 
-        Ok(command_exec)
+        command_exec
     }
 
-    async fn execute_via(&self, executor: &impl CommandExecutor, command: &Command) -> Option<anyhow::Result<()>> {
-        match executor.execute_command(command).await {
-            Ok(true) => Some(Ok(())),
-            Ok(false) => None,
-            Err(e) => Some(Err(e)),
+    /// Checks pending confirmations against the snapshot. Reflected commands end as `Confirmed`,
+    /// those past their deadline as `Unconfirmed`.
+    pub async fn check_confirmations(&self, snapshot: &StateSnapshot, command_client: &CommandClient) {
+        let mut unresolved = Vec::new();
+
+        for pending in self.confirmations.take_all() {
+            let command = &pending.command_exec.command;
+
+            let reflected = match command.is_reflected_in_state(snapshot, command_client).await {
+                Ok(reflected) => reflected,
+                Err(e) => {
+                    tracing::debug!("Error checking if command {:?} is reflected in state: {}", command, e);
+                    false
+                }
+            };
+
+            if reflected {
+                self.finish_confirmation(pending, CommandState::Confirmed).await;
+            } else if t!(now) >= pending.deadline {
+                self.finish_confirmation(pending, CommandState::Unconfirmed).await;
+            } else {
+                unresolved.push(pending);
+            }
+        }
+
+        self.confirmations.restore(unresolved);
+    }
+
+    async fn finish_confirmation(&self, pending: PendingConfirmation, state: CommandState) {
+        let device_id = CommandTarget::from(&pending.command_exec.command).to_string();
+
+        match state {
+            CommandState::Confirmed => {
+                let latency = pending.sent_at.elapsed();
+                tracing::info!("Command {} confirmed after {}", pending.command_exec.id, latency);
+                CommandMetric::Confirmed {
+                    device_id,
+                    system: pending.system,
+                    latency_seconds: latency.as_secs_f64(),
+                }
+                .record();
+            }
+            _ => {
+                tracing::warn!(
+                    "Command {} ({:?}) not reflected in state until {}",
+                    pending.command_exec.id,
+                    pending.command_exec.command,
+                    pending.deadline
+                );
+                CommandMetric::Unconfirmed {
+                    device_id,
+                    system: pending.system,
+                }
+                .record();
+            }
+        }
+
+        if let Err(e) = self
+            .repo
+            .set_command_state(pending.command_exec.id, state.clone())
+            .await
+        {
+            tracing::warn!(
+                "Failed to update command state of {} to {:?} in DB: {}",
+                pending.command_exec.id,
+                state,
+                e
+            );
         }
     }
 
+    pub fn command_targets(&self) -> Vec<CommandTarget> {
+        self.executors.command_targets()
+    }
+
     pub async fn get_latest_command(
         &self,
         target: CommandTarget,
@@ -2,6 +2,7 @@ pub mod domain;
 pub mod id;
 pub mod math;
 pub mod range;
+pub mod sync;
 pub mod time;
 pub mod timeseries;
 pub mod unit;
@@ -33,3 +34,21 @@ where
         }
     }
 }
+
+/// Zigbee2MQTT device referenced by one of the module configs
+#[derive(Debug, Clone)]
+pub struct ExpectedZ2mDevice {
+    pub friendly_name: String,
+    pub usage: String,
+    pub features: Vec<&'static str>,
+}
+
+impl ExpectedZ2mDevice {
+    pub fn new(friendly_name: &str, usage: impl Into<String>, features: &[&'static str]) -> Self {
+        Self {
+            friendly_name: friendly_name.to_string(),
+            usage: usage.into(),
+            features: features.to_vec(),
+        }
+    }
+}
@@ -0,0 +1,6 @@
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Locks the mutex even if another holder panicked, a single panic shouldn't take down every later caller
+pub fn lock_ignoring_poison<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
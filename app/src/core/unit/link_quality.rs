@@ -0,0 +1,24 @@
+use derive_more::derive::AsRef;
+use std::fmt::Display;
+
+/// Zigbee link quality indicator, 0 to 255
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, AsRef)]
+pub struct Lqi(pub i64);
+
+impl Display for Lqi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} lqi", self.0)
+    }
+}
+
+impl From<&Lqi> for f64 {
+    fn from(value: &Lqi) -> Self {
+        value.0 as f64
+    }
+}
+
+impl From<f64> for Lqi {
+    fn from(value: f64) -> Self {
+        Self(value as i64)
+    }
+}
@@ -6,6 +6,7 @@ mod fan;
 mod heating;
 mod kwh;
 mod light;
+mod link_quality;
 mod liquid;
 mod percent;
 mod probability;
@@ -19,6 +20,7 @@ pub use fan::*;
 pub use heating::HeatingUnit;
 pub use kwh::KiloWattHours;
 pub use light::Lux;
+pub use link_quality::Lqi;
 pub use liquid::KiloCubicMeter;
 pub use percent::Percent;
 pub use probability::Probability;
@@ -0,0 +1,102 @@
+use std::{
+    path::Path,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use actix_web::{
+    Error, HttpRequest, HttpResponse,
+    web::{self, Query},
+};
+use futures::StreamExt as _;
+use serde::Deserialize;
+use tokio::io::AsyncWriteExt as _;
+
+use crate::device_state::{DeviceStateClient, ImportFormat};
+
+static NEXT_UPLOAD_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Clone)]
+pub struct DeviceStateApi {
+    client: DeviceStateClient,
+}
+
+impl DeviceStateApi {
+    pub fn new(client: DeviceStateClient) -> Self {
+        Self { client }
+    }
+
+    pub fn routes(&self) -> actix_web::Scope {
+        web::scope("/device-state")
+            .route("/import", web::post().to(start_import_handler))
+            .route("/import/{id}", web::get().to(import_progress_handler))
+            .route("/retention", web::get().to(retention_reports_handler))
+            .app_data(web::Data::new(self.clone()))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ImportQuery {
+    format: Option<ImportFormat>,
+}
+
+async fn start_import_handler(
+    api: web::Data<DeviceStateApi>,
+    request: HttpRequest,
+    query: Query<ImportQuery>,
+    mut body: web::Payload,
+) -> Result<HttpResponse, Error> {
+    let format = query.format.unwrap_or_else(|| format_from_content_type(&request));
+
+    //exports of several years don't fit into memory, the upload is spooled to a file and imported from there
+    let file_path = std::env::temp_dir().join(format!(
+        "device-state-import-{}-{}",
+        std::process::id(),
+        NEXT_UPLOAD_ID.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    if let Err(e) = save_upload(&mut body, &file_path).await {
+        let _ = tokio::fs::remove_file(&file_path).await;
+        return Err(actix_web::error::ErrorInternalServerError(format!(
+            "Error saving import file: {:#}",
+            e
+        )));
+    }
+
+    let id = api.client.start_import(format, file_path);
+
+    Ok(HttpResponse::Accepted().json(serde_json::json!({ "id": id })))
+}
+
+async fn save_upload(body: &mut web::Payload, file_path: &Path) -> anyhow::Result<()> {
+    let mut file = tokio::fs::File::create(file_path).await?;
+
+    while let Some(chunk) = body.next().await {
+        file.write_all(&chunk?).await?;
+    }
+
+    file.flush().await?;
+    Ok(())
+}
+
+fn format_from_content_type(request: &HttpRequest) -> ImportFormat {
+    match request.headers().get("content-type").and_then(|v| v.to_str().ok()) {
+        Some(content_type) if content_type.starts_with("text/csv") => ImportFormat::Csv,
+        _ => ImportFormat::Jsonl,
+    }
+}
+
+async fn import_progress_handler(api: web::Data<DeviceStateApi>, id: web::Path<u64>) -> Result<HttpResponse, Error> {
+    match api.client.get_import_progress(id.into_inner()) {
+        Some(progress) => Ok(HttpResponse::Ok().json(progress)),
+        None => Ok(HttpResponse::NotFound().finish()),
+    }
+}
+
+async fn retention_reports_handler(api: web::Data<DeviceStateApi>) -> Result<HttpResponse, Error> {
+    let reports =
+        api.client.get_retention_reports().await.map_err(|e| {
+            actix_web::error::ErrorInternalServerError(format!("Error fetching retention reports: {}", e))
+        })?;
+
+    Ok(HttpResponse::Ok().json(reports))
+}
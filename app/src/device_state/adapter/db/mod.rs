@@ -1,3 +1,5 @@
+mod retention;
+
 use anyhow::{Context as _, Result};
 use moka::future::Cache;
 use sqlx::{PgPool, postgres::types::PgInterval};
@@ -52,6 +54,117 @@ impl DeviceStateRepository {
         Ok(result.rows_affected() > 0)
     }
 
+    /// Inserts historical values, skipping those with an existing value for the same item and timestamp.
+    /// Like `save`, only changes are stored: values equal to the previous value, imported or stored, are skipped
+    /// and a stored value made redundant by an imported one is removed.
+    /// Missing monthly partitions are created. Returns the number of inserted rows.
+    pub async fn import_batch(&self, dps: &[DataPoint<DeviceStateValue>]) -> Result<u64> {
+        if dps.is_empty() {
+            return Ok(0);
+        }
+
+        let mut tag_ids = Vec::with_capacity(dps.len());
+        let mut values = Vec::with_capacity(dps.len());
+        let mut timestamps = Vec::with_capacity(dps.len());
+
+        for dp in dps {
+            tag_ids.push(self.get_tag_id(&DeviceStateId::from(&dp.value)).await? as i32);
+            values.push(f64::from(&dp.value));
+            timestamps.push(dp.timestamp.into_db());
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"SELECT create_thing_value_partition(month) as "created: ()"
+               FROM (
+                   SELECT DISTINCT to_char(ts AT TIME ZONE 'UTC', 'YYYY-MM') AS month
+                   FROM UNNEST($1::timestamptz[]) AS ts
+               ) months"#,
+            &timestamps
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .context("Error creating thing_value partitions")?;
+
+        //stored rows of the imported range and their direct neighbours decide whether a value is a change
+        let result = sqlx::query!(
+            r#"WITH input AS (
+                   SELECT DISTINCT ON (i.tag_id, i.timestamp) i.tag_id, i.value, i.timestamp
+                   FROM UNNEST($1::int4[], $2::float8[], $3::timestamptz[]) AS i(tag_id, value, timestamp)
+                   ORDER BY i.tag_id, i.timestamp
+               ),
+               bounds AS (
+                   SELECT tag_id, min(timestamp) AS first_ts, max(timestamp) AS last_ts FROM input GROUP BY tag_id
+               ),
+               stored AS (
+                   SELECT tv.id, tv.tag_id, tv.value, tv.timestamp
+                   FROM bounds b
+                   CROSS JOIN LATERAL (
+                       (SELECT id, tag_id, value, timestamp FROM thing_value
+                        WHERE tag_id = b.tag_id AND timestamp < b.first_ts
+                        ORDER BY timestamp DESC, id DESC LIMIT 1)
+                       UNION ALL
+                       (SELECT id, tag_id, value, timestamp FROM thing_value
+                        WHERE tag_id = b.tag_id AND timestamp BETWEEN b.first_ts AND b.last_ts)
+                       UNION ALL
+                       (SELECT id, tag_id, value, timestamp FROM thing_value
+                        WHERE tag_id = b.tag_id AND timestamp > b.last_ts
+                        ORDER BY timestamp, id LIMIT 1)
+                   ) tv
+               ),
+               merged AS (
+                   SELECT id, tag_id, value, timestamp,
+                       lag(value) OVER w AS prev_value,
+                       lag(id IS NULL) OVER w AS prev_imported
+                   FROM (
+                       SELECT id, tag_id, value, timestamp FROM stored
+                       UNION ALL
+                       SELECT NULL::bigint, i.tag_id, i.value, i.timestamp FROM input i
+                       WHERE NOT EXISTS (SELECT 1 FROM stored s WHERE s.tag_id = i.tag_id AND s.timestamp = i.timestamp)
+                   ) rows
+                   WINDOW w AS (PARTITION BY tag_id ORDER BY timestamp, id)
+               ),
+               redundant AS (
+                   DELETE FROM thing_value tv
+                   USING merged m
+                   WHERE m.prev_imported AND m.value = m.prev_value
+                     AND tv.id = m.id AND tv.timestamp = m.timestamp
+               )
+               INSERT INTO thing_value (tag_id, value, timestamp)
+               SELECT tag_id, value, timestamp FROM merged
+               WHERE id IS NULL AND value IS DISTINCT FROM prev_value"#,
+            &tag_ids,
+            &values,
+            &timestamps
+        )
+        .execute(&mut *tx)
+        .await
+        .context("Error importing device state batch")?;
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn save_quarantined(&self, dp: &DataPoint<DeviceStateValue>, reason: &str) -> Result<()> {
+        let id = DeviceStateId::from(&dp.value).ext_id();
+
+        sqlx::query!(
+            r#"INSERT INTO device_state_quarantine (channel, name, value, reason, timestamp)
+               VALUES ($1, $2, $3, $4, $5)"#,
+            id.type_name(),
+            id.variant_name(),
+            f64::from(&dp.value),
+            reason,
+            dp.timestamp.into_db()
+        )
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .context("Error saving quarantined device state")
+    }
+
     pub async fn get_latest_for_device(&self, id: &DeviceStateId) -> Result<Option<DataPoint<DeviceStateValue>>> {
         let tag_id = self.get_tag_id(id).await?;
 
@@ -69,7 +182,7 @@ impl DeviceStateRepository {
         .await?;
 
         Ok(row.map(|r| DataPoint {
-            value: from_f64_value(*id, r.value),
+            value: DeviceStateValue::from_f64(*id, r.value),
             timestamp: r.timestamp.into(),
         }))
     }
@@ -119,7 +232,7 @@ impl DeviceStateRepository {
 
                 match DeviceStateId::try_from(external_id) {
                     Ok(target) => Some(DataPoint {
-                        value: from_f64_value(target, row.value),
+                        value: DeviceStateValue::from_f64(target, row.value),
                         timestamp: row.timestamp.into(),
                     }),
                     Err(_) => {
@@ -143,24 +256,27 @@ impl DeviceStateRepository {
         Ok(dps)
     }
 
-    #[allow(clippy::expect_used)]
     pub async fn update_device_availability(
         &self,
         device_id: &str,
         source: &str,
         last_seen: &DateTime,
         offline: bool,
+        considered_offline_after: &Duration,
     ) -> anyhow::Result<()> {
+        //TODO should just work via chrono::Duration, but doesn't
+        let considered_offline_after = PgInterval::try_from(considered_offline_after.clone().into_db())
+            .map_err(|e| anyhow::anyhow!("Error converting {} to interval: {}", considered_offline_after, e))?;
+
         sqlx::query!(
             r#"INSERT INTO item_availability (source, item, last_seen, marked_offline, considered_offline_after, entry_updated)
                 VALUES ($1, $2, $3, $4, $5, $6)
-                ON CONFLICT (source, item) DO UPDATE SET last_seen = $3, marked_offline = $4, entry_updated = $6"#,
+                ON CONFLICT (source, item) DO UPDATE SET last_seen = $3, marked_offline = $4, considered_offline_after = $5, entry_updated = $6"#,
             source,
             device_id,
             last_seen.into_db(),
             offline,
-            //TODO should just work via chrono::Duration, but doesn't
-            PgInterval::try_from(t!(1 hours).into_db()).expect("PgInterval conversion of hardcoded 1-hour duration cannot fail"),
+            considered_offline_after,
             t!(now).into_db(),
         )
         .execute(&self.pool)
@@ -169,6 +285,18 @@ impl DeviceStateRepository {
         Ok(())
     }
 
+    pub async fn remove_device_availability(&self, device_id: &str, source: &str) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"DELETE FROM item_availability WHERE source = $1 AND item = $2"#,
+            source,
+            device_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn get_offline_items(&self) -> anyhow::Result<Vec<OfflineItem>> {
         let recs = sqlx::query!(
             r#"SELECT source, item, last_seen, marked_offline, considered_offline_after, entry_updated
@@ -215,32 +343,6 @@ fn convert_pginterval_to_duration(pg_interval: &PgInterval) -> Duration {
     Duration::days(total_days as i64) + Duration::millis(total_milliseconds)
 }
 
-fn from_f64_value(id: DeviceStateId, value: f64) -> DeviceStateValue {
-    fn bool_of(f: f64) -> bool {
-        f > f64::EPSILON
-    }
-
-    match id {
-        DeviceStateId::AllergenIndex(id) => DeviceStateValue::AllergenIndex(id, value.into()),
-        DeviceStateId::EnergySaving(id) => DeviceStateValue::EnergySaving(id, bool_of(value)),
-        DeviceStateId::Opened(id) => DeviceStateValue::Opened(id, bool_of(value)),
-        DeviceStateId::ParticulateMatter(id) => DeviceStateValue::ParticulateMatter(id, value.into()),
-        DeviceStateId::PowerAvailable(id) => DeviceStateValue::PowerAvailable(id, bool_of(value)),
-        DeviceStateId::Presence(id) => DeviceStateValue::Presence(id, bool_of(value)),
-        DeviceStateId::CurrentPowerUsage(id) => DeviceStateValue::CurrentPowerUsage(id, value.into()),
-        DeviceStateId::FanActivity(id) => DeviceStateValue::FanActivity(id, value.into()),
-        DeviceStateId::HeatingDemand(id) => DeviceStateValue::HeatingDemand(id, value.into()),
-        DeviceStateId::HeatingDemandLimit(id) => DeviceStateValue::HeatingDemandLimit(id, value.into()),
-        DeviceStateId::LightLevel(id) => DeviceStateValue::LightLevel(id, value.into()),
-        DeviceStateId::RelativeHumidity(id) => DeviceStateValue::RelativeHumidity(id, value.into()),
-        DeviceStateId::SetPoint(id) => DeviceStateValue::SetPoint(id, value.into()),
-        DeviceStateId::Temperature(id) => DeviceStateValue::Temperature(id, value.into()),
-        DeviceStateId::TotalEnergyConsumption(id) => DeviceStateValue::TotalEnergyConsumption(id, value.into()),
-        DeviceStateId::TotalRadiatorConsumption(id) => DeviceStateValue::TotalRadiatorConsumption(id, value.into()),
-        DeviceStateId::TotalWaterConsumption(id) => DeviceStateValue::TotalWaterConsumption(id, value.into()),
-    }
-}
-
 async fn get_or_insert_tag_id_from_db(db_pool: &PgPool, id: &DeviceStateId) -> Result<i64> {
     let id = id.ext_id();
 
@@ -367,6 +469,99 @@ mod tests {
         Ok(())
     }
 
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_import_batch_skips_existing_values(pool: PgPool) -> anyhow::Result<()> {
+        let repo = DeviceStateRepository::new(pool);
+        let historical = DateTime::from_iso("2019-03-10T10:00:00Z")?;
+        let dps = vec![
+            DataPoint::new(
+                DeviceStateValue::Temperature(Temperature::LivingRoom, DegreeCelsius(20.0)),
+                historical,
+            ),
+            DataPoint::new(
+                DeviceStateValue::Temperature(Temperature::LivingRoom, DegreeCelsius(20.0)),
+                historical,
+            ),
+            DataPoint::new(
+                DeviceStateValue::Temperature(Temperature::Bedroom, DegreeCelsius(18.0)),
+                historical,
+            ),
+        ];
+
+        assert_eq!(repo.import_batch(&dps).await?, 2);
+        assert_eq!(repo.import_batch(&dps).await?, 0);
+
+        let dps = repo
+            .get_all_data_points_in_range_ts_asc(DateTimeRange::new(historical, t!(now)))
+            .await?;
+        assert_eq!(dps.len(), 2);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_import_batch_stores_only_changes(pool: PgPool) -> anyhow::Result<()> {
+        let repo = DeviceStateRepository::new(pool);
+        let start = DateTime::from_iso("2019-03-10T10:00:00Z")?;
+        let temperature = |minutes: i64, value: f64| {
+            DataPoint::new(
+                DeviceStateValue::Temperature(Temperature::LivingRoom, DegreeCelsius(value)),
+                start + Duration::minutes(minutes),
+            )
+        };
+
+        repo.import_batch(&[temperature(30, 21.0), temperature(60, 22.0)]).await?;
+
+        //10 and 20 repeat the imported value of 0, 25 makes the stored 30 redundant, 40 repeats the stored 30
+        let imported = repo
+            .import_batch(&[
+                temperature(0, 20.0),
+                temperature(10, 20.0),
+                temperature(20, 20.0),
+                temperature(25, 21.0),
+                temperature(40, 21.0),
+            ])
+            .await?;
+        assert_eq!(imported, 2);
+
+        let dps = repo
+            .get_all_data_points_in_range_ts_asc(DateTimeRange::new(start, start + t!(2 hours)))
+            .await?;
+        assert_eq!(
+            dps.iter().map(|dp| (dp.timestamp, f64::from(&dp.value))).collect::<Vec<_>>(),
+            vec![
+                (start, 20.0),
+                (start + Duration::minutes(25), 21.0),
+                (start + Duration::minutes(60), 22.0),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_get_offline_items_uses_item_offline_duration(pool: PgPool) -> anyhow::Result<()> {
+        let repo = DeviceStateRepository::new(pool);
+        repo.update_device_availability("sensor", "Z2M", &t!(20 minutes ago), false, &t!(1 hours))
+            .await?;
+        repo.update_device_availability(
+            "temperature::bedroom",
+            "STALENESS",
+            &t!(20 minutes ago),
+            true,
+            &t!(15 minutes),
+        )
+        .await?;
+
+        let offline = repo.get_offline_items().await?;
+
+        assert_eq!(offline.len(), 1);
+        assert_eq!(offline[0].item, "temperature::bedroom");
+        assert!(offline[0].duration >= t!(20 minutes));
+
+        Ok(())
+    }
+
     async fn prepare_test_data(repo: &DeviceStateRepository) -> anyhow::Result<()> {
         repo.save(DataPoint::new(
             DeviceStateValue::Temperature(Temperature::LivingRoom, DegreeCelsius(20.5)),
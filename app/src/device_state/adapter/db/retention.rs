@@ -0,0 +1,363 @@
+use anyhow::{Context as _, Result};
+use sqlx::postgres::types::PgInterval;
+
+use crate::{
+    core::time::{DateTime, Duration},
+    device_state::{
+        adapter::db::DeviceStateRepository,
+        retention::{DeviceStateTypes, RetentionReport},
+    },
+    t,
+};
+
+impl DeviceStateRepository {
+    /// Creates the partitions of the current and the next month
+    pub async fn ensure_partitions(&self) -> Result<()> {
+        sqlx::query!(
+            r#"SELECT create_thing_value_partition(to_char(month, 'YYYY-MM')) as "created: ()"
+               FROM (VALUES ($1::timestamptz AT TIME ZONE 'UTC'), ($1::timestamptz AT TIME ZONE 'UTC' + interval '1 month')) AS m(month)"#,
+            t!(now).into_db()
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Error creating upcoming thing_value partitions")?;
+
+        Ok(())
+    }
+
+    /// Monthly partitions ending before `cutoff` that were not downsampled yet or got new rows since, e.g. by an import
+    pub async fn get_partitions_to_downsample(&self, cutoff: DateTime) -> Result<Vec<String>> {
+        let names = sqlx::query_scalar!(
+            r#"SELECT name as "name!" FROM (
+                   SELECT c.relname::text AS name,
+                       to_date(substr(c.relname, 13), 'YYYY_MM')::timestamp AT TIME ZONE 'UTC' AS partition_start,
+                       (to_date(substr(c.relname, 13), 'YYYY_MM') + interval '1 month') AT TIME ZONE 'UTC' AS partition_end
+                   FROM pg_inherits i
+                   JOIN pg_class c ON c.oid = i.inhrelid
+                   JOIN pg_class p ON p.oid = i.inhparent
+                   WHERE p.relname = 'thing_value'
+                     AND c.relname ~ '^thing_value_[0-9]{4}_[0-9]{2}$'
+               ) partitions
+               WHERE partition_end <= $1
+                 AND NOT EXISTS (
+                     SELECT 1 FROM thing_value_retention r
+                     WHERE r.partition_name = partitions.name
+                       AND NOT EXISTS (
+                           SELECT 1 FROM thing_value tv
+                           WHERE tv.timestamp >= partitions.partition_start AND tv.timestamp < partitions.partition_end
+                             AND tv.id > r.max_id
+                       )
+                 )
+               ORDER BY name"#,
+            cutoff.into_db()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(names)
+    }
+
+    /// Replaces the raw values of the partition by first, last, min and max value per item and bucket.
+    /// Consecutive equal values are removed, as values are only stored on change.
+    /// Items of `keep_all_types` keep all their values. Downsampling a partition again only changes rows added since,
+    /// the report accumulates the figures of all runs.
+    pub async fn downsample_partition(
+        &self,
+        partition: &str,
+        bucket: &Duration,
+        keep_all_types: &DeviceStateTypes,
+        archive: bool,
+    ) -> Result<RetentionReport> {
+        //partition names are only taken from the catalog, but never trust dynamic identifiers
+        anyhow::ensure!(
+            partition.starts_with("thing_value_") && partition.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'),
+            "Invalid partition name {}",
+            partition
+        );
+
+        let bucket = PgInterval::try_from(bucket.clone().into_db())
+            .map_err(|e| anyhow::anyhow!("Error converting {} to interval: {}", bucket, e))?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let (raw_rows, raw_bytes): (i64, i64) = sqlx::query_as(&format!(
+            r#"SELECT (SELECT count(*) FROM "{partition}"), pg_total_relation_size('"{partition}"')"#
+        ))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(&format!(
+            r#"CREATE TEMP TABLE thing_value_kept ON COMMIT DROP AS
+               WITH ranked AS (
+                   SELECT tv.id, tv.tag_id, tv.value, tv.timestamp,
+                       t.channel = ANY($1) AS keep_all,
+                       row_number() OVER (PARTITION BY tv.tag_id, tv.bucket ORDER BY tv.timestamp, tv.id) AS first_rn,
+                       row_number() OVER (PARTITION BY tv.tag_id, tv.bucket ORDER BY tv.timestamp DESC, tv.id DESC) AS last_rn,
+                       row_number() OVER (PARTITION BY tv.tag_id, tv.bucket ORDER BY tv.value, tv.timestamp) AS min_rn,
+                       row_number() OVER (PARTITION BY tv.tag_id, tv.bucket ORDER BY tv.value DESC, tv.timestamp) AS max_rn
+                   FROM (
+                       SELECT *, date_bin($2, timestamp, TIMESTAMPTZ '2000-01-01 00:00:00+00') AS bucket
+                       FROM "{partition}"
+                   ) tv
+                   JOIN thing_value_tag t ON t.id = tv.tag_id
+               ),
+               kept AS (
+                   SELECT id, tag_id, value, timestamp FROM ranked
+                   WHERE keep_all OR first_rn = 1 OR last_rn = 1 OR min_rn = 1 OR max_rn = 1
+               )
+               SELECT id, tag_id, value, timestamp FROM (
+                   SELECT *, lag(value) OVER (PARTITION BY tag_id ORDER BY timestamp, id) AS prev_value FROM kept
+               ) k
+               WHERE prev_value IS DISTINCT FROM value"#
+        ))
+        .bind(keep_all_types.names())
+        .bind(bucket)
+        .execute(&mut *tx)
+        .await
+        .with_context(|| format!("Error downsampling {}", partition))?;
+
+        let mut archived_bytes = 0;
+        if archive {
+            sqlx::query(&format!(
+                r#"CREATE TABLE IF NOT EXISTS thing_value_archive."{partition}"
+                   AS SELECT * FROM "{partition}" WITH NO DATA"#
+            ))
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("Error creating archive of {}", partition))?;
+
+            //kept rows of an earlier run are archived already
+            sqlx::query(&format!(
+                r#"INSERT INTO thing_value_archive."{partition}"
+                   SELECT * FROM "{partition}" p
+                   WHERE NOT EXISTS (SELECT 1 FROM thing_value_archive."{partition}" a WHERE a.id = p.id)"#
+            ))
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("Error archiving {}", partition))?;
+
+            (archived_bytes,) =
+                sqlx::query_as(&format!(r#"SELECT pg_total_relation_size('thing_value_archive."{partition}"')"#))
+                    .fetch_one(&mut *tx)
+                    .await?;
+        }
+
+        sqlx::query(&format!(r#"TRUNCATE "{partition}""#))
+            .execute(&mut *tx)
+            .await?;
+
+        let kept_rows = sqlx::query(&format!(
+            r#"INSERT INTO "{partition}" (id, tag_id, value, timestamp)
+               SELECT id, tag_id, value, timestamp FROM thing_value_kept"#
+        ))
+        .execute(&mut *tx)
+        .await?
+        .rows_affected() as i64;
+
+        let (downsampled_bytes, max_id): (i64, i64) = sqlx::query_as(&format!(
+            r#"SELECT pg_total_relation_size('"{partition}"'), (SELECT coalesce(max(id), 0) FROM "{partition}")"#
+        ))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        //rows kept by an earlier run are counted again in raw rows and size
+        let rec = sqlx::query!(
+            r#"INSERT INTO thing_value_retention AS r
+                   (partition_name, raw_rows, kept_rows, raw_bytes, downsampled_bytes, archived, archived_bytes, max_id,
+                    processed_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+               ON CONFLICT (partition_name) DO UPDATE SET
+                   raw_rows = r.raw_rows + EXCLUDED.raw_rows - r.kept_rows,
+                   kept_rows = EXCLUDED.kept_rows,
+                   raw_bytes = r.raw_bytes + EXCLUDED.raw_bytes - r.downsampled_bytes,
+                   downsampled_bytes = EXCLUDED.downsampled_bytes,
+                   archived = r.archived OR EXCLUDED.archived,
+                   archived_bytes = CASE WHEN EXCLUDED.archived THEN EXCLUDED.archived_bytes ELSE r.archived_bytes END,
+                   max_id = EXCLUDED.max_id,
+                   processed_at = EXCLUDED.processed_at
+               RETURNING partition_name, raw_rows, kept_rows, raw_bytes, downsampled_bytes, archived, archived_bytes,
+                   processed_at"#,
+            partition,
+            raw_rows,
+            kept_rows,
+            raw_bytes,
+            downsampled_bytes,
+            archive,
+            archived_bytes,
+            max_id,
+            t!(now).into_db()
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(RetentionReport {
+            partition: rec.partition_name,
+            raw_rows: rec.raw_rows,
+            kept_rows: rec.kept_rows,
+            raw_bytes: rec.raw_bytes,
+            downsampled_bytes: rec.downsampled_bytes,
+            archived: rec.archived,
+            archived_bytes: rec.archived_bytes,
+            processed_at: rec.processed_at.into(),
+        })
+    }
+
+    pub async fn get_retention_reports(&self) -> Result<Vec<RetentionReport>> {
+        let recs = sqlx::query!(
+            r#"SELECT partition_name, raw_rows, kept_rows, raw_bytes, downsampled_bytes, archived, archived_bytes,
+                   processed_at
+               FROM thing_value_retention
+               ORDER BY partition_name"#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(recs
+            .into_iter()
+            .map(|r| RetentionReport {
+                partition: r.partition_name,
+                raw_rows: r.raw_rows,
+                kept_rows: r.kept_rows,
+                raw_bytes: r.raw_bytes,
+                downsampled_bytes: r.downsampled_bytes,
+                archived: r.archived,
+                archived_bytes: r.archived_bytes,
+                processed_at: r.processed_at.into(),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{
+        core::{time::DateTimeRange, timeseries::DataPoint, unit::DegreeCelsius},
+        device_state::{DeviceStateId, DeviceStateValue, Opened, Temperature},
+    };
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_downsample_partition(pool: PgPool) -> anyhow::Result<()> {
+        let repo = DeviceStateRepository::new(pool);
+        let start = DateTime::from_iso("2019-03-10T10:00:00Z")?;
+
+        let mut dps = vec![];
+        for minute in 0..60 {
+            dps.push(DataPoint::new(
+                DeviceStateValue::Temperature(Temperature::LivingRoom, DegreeCelsius(20.0 + (minute % 7) as f64)),
+                start + Duration::minutes(minute),
+            ));
+        }
+        for step in 0..12 {
+            dps.push(DataPoint::new(
+                DeviceStateValue::Opened(Opened::KitchenWindow, step % 2 == 0),
+                start + Duration::minutes(step * 5),
+            ));
+        }
+        repo.import_batch(&dps).await?;
+
+        let partitions = repo.get_partitions_to_downsample(t!(now)).await?;
+        assert!(partitions.contains(&"thing_value_2019_03".to_string()));
+
+        let report = repo
+            .downsample_partition("thing_value_2019_03", &t!(15 minutes), &keep_all_opened()?, false)
+            .await?;
+
+        assert_eq!(report.raw_rows, 72);
+        assert!(report.kept_rows < report.raw_rows);
+
+        let dps = repo
+            .get_all_data_points_in_range_ts_asc(DateTimeRange::new(start, start + t!(1 hours)))
+            .await?;
+        let of = |id: DeviceStateId| dps.iter().filter(|dp| DeviceStateId::from(&dp.value) == id).count();
+        assert_eq!(of(DeviceStateId::Opened(Opened::KitchenWindow)), 12);
+        assert!(of(DeviceStateId::Temperature(Temperature::LivingRoom)) <= 16);
+        assert_eq!(
+            dps.iter()
+                .rev()
+                .find(|dp| DeviceStateId::from(&dp.value) == DeviceStateId::Temperature(Temperature::LivingRoom))
+                .map(|dp| dp.value.clone()),
+            Some(DeviceStateValue::Temperature(
+                Temperature::LivingRoom,
+                DegreeCelsius(23.0)
+            ))
+        );
+
+        assert!(
+            !repo
+                .get_partitions_to_downsample(t!(now))
+                .await?
+                .contains(&report.partition)
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn test_imported_rows_are_downsampled_again(pool: PgPool) -> anyhow::Result<()> {
+        let repo = DeviceStateRepository::new(pool);
+        let start = DateTime::from_iso("2019-03-10T10:00:00Z")?;
+        let temperatures = |hour: i64| {
+            (0..60)
+                .map(|minute| {
+                    let value = DegreeCelsius(20.0 + (minute % 7) as f64);
+                    DataPoint::new(
+                        DeviceStateValue::Temperature(Temperature::LivingRoom, value),
+                        start + Duration::hours(hour) + Duration::minutes(minute),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        repo.import_batch(&temperatures(0)).await?;
+        let first = repo
+            .downsample_partition("thing_value_2019_03", &t!(15 minutes), &keep_all_opened()?, true)
+            .await?;
+        assert!(first.archived_bytes > 0);
+        assert_eq!(
+            first.reclaimed_bytes(),
+            first.raw_bytes - first.downsampled_bytes - first.archived_bytes
+        );
+
+        repo.import_batch(&temperatures(2)).await?;
+        assert!(
+            repo.get_partitions_to_downsample(t!(now))
+                .await?
+                .contains(&first.partition)
+        );
+
+        let second = repo
+            .downsample_partition("thing_value_2019_03", &t!(15 minutes), &keep_all_opened()?, true)
+            .await?;
+        assert_eq!(second.raw_rows, 120);
+        assert!(second.kept_rows > first.kept_rows && second.kept_rows < 40);
+        assert!(
+            !repo
+                .get_partitions_to_downsample(t!(now))
+                .await?
+                .contains(&second.partition)
+        );
+
+        let (archived_rows,): (i64,) =
+            sqlx::query_as(r#"SELECT count(*) FROM thing_value_archive."thing_value_2019_03""#)
+                .fetch_one(&repo.pool)
+                .await?;
+        assert_eq!(archived_rows, 120);
+
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_state_types_are_rejected() {
+        assert!(keep_all_opened().is_ok());
+        assert!(DeviceStateTypes::try_from(vec!["openend".to_string()]).is_err());
+    }
+
+    fn keep_all_opened() -> anyhow::Result<DeviceStateTypes> {
+        DeviceStateTypes::try_from(vec!["opened".to_string()])
+    }
+}
@@ -1,4 +1,5 @@
 mod config;
+mod websocket;
 
 use infrastructure::{Mqtt, MqttSubscription};
 use serde::Deserialize;
@@ -26,6 +27,7 @@ use crate::device_state::DeviceStateValue;
 
 use crate::core::DeviceConfig;
 use std::sync::Mutex;
+use websocket::HaWebSocketClient;
 
 #[derive(Debug, Default, Clone)]
 struct ComfeeFanCache {
@@ -78,31 +80,56 @@ impl<'de> Deserialize<'de> for StateValue {
     }
 }
 
+/// Where state changes of HA entities are received from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HaEventSource {
+    /// Events forwarded to MQTT by an HA automation, initial state loaded via REST
+    #[default]
+    Mqtt,
+    /// Subscription to HA's WebSocket API, resynced on every reconnect
+    Websocket,
+}
+
+enum HaStateListener {
+    Mqtt {
+        client: HaHttpClient,
+        listener: HaMqttClient,
+        initial_load: Option<Vec<StateChangedEvent>>,
+    },
+    WebSocket(HaWebSocketClient),
+}
+
 pub struct HomeAssistantIncomingDataSource {
-    client: HaHttpClient,
-    listener: HaMqttClient,
+    state_listener: HaStateListener,
     config: DeviceConfig<HaChannel>,
-    initial_load: Option<Vec<StateChangedEvent>>,
     comfee_cache: Mutex<HashMap<FanActivity, ComfeeFanCache>>,
 }
 
 impl HomeAssistantIncomingDataSource {
     #[allow(clippy::expect_used)]
-    pub async fn new(mqtt: &mut Mqtt, event_topic: &str, url: &str, token: &str) -> Self {
+    pub async fn new(mqtt: &mut Mqtt, event_source: HaEventSource, event_topic: &str, url: &str, token: &str) -> Self {
         let config = DeviceConfig::new(&config::default_ha_state_config());
-        let rx = mqtt
-            .subscribe(event_topic, "")
-            .await
-            .expect("Error subscribing to MQTT topic");
 
-        let mqtt_client = HaMqttClient::new(rx);
-        let http_client = HaHttpClient::new(url, token).expect("Error creating HA HTTP client");
+        let state_listener = match event_source {
+            HaEventSource::Mqtt => {
+                let rx = mqtt
+                    .subscribe(event_topic, "")
+                    .await
+                    .expect("Error subscribing to MQTT topic");
+
+                HaStateListener::Mqtt {
+                    client: HaHttpClient::new(url, token).expect("Error creating HA HTTP client"),
+                    listener: HaMqttClient::new(rx),
+                    initial_load: None,
+                }
+            }
+            HaEventSource::Websocket => HaStateListener::WebSocket(HaWebSocketClient::new(url, token)),
+        };
 
         Self {
-            client: http_client,
-            listener: mqtt_client,
+            state_listener,
             config,
-            initial_load: None,
             comfee_cache: Mutex::new(HashMap::new()),
         }
     }
@@ -114,8 +141,17 @@ impl IncomingDataSource<StateChangedEvent, HaChannel> for HomeAssistantIncomingD
     }
 
     async fn recv(&mut self) -> Option<StateChangedEvent> {
-        if self.initial_load.is_none() {
-            self.initial_load = match self.client.get_current_state().await {
+        let (client, listener, initial_load) = match &mut self.state_listener {
+            HaStateListener::Mqtt {
+                client,
+                listener,
+                initial_load,
+            } => (client, listener, initial_load),
+            HaStateListener::WebSocket(ws_client) => return ws_client.recv().await,
+        };
+
+        if initial_load.is_none() {
+            *initial_load = match client.get_current_state().await {
                 Ok(v) => Some(v),
                 Err(e) => {
                     tracing::error!("Error loading initial state for HA: {:?}", e);
@@ -124,9 +160,9 @@ impl IncomingDataSource<StateChangedEvent, HaChannel> for HomeAssistantIncomingD
             };
         }
 
-        match &mut self.initial_load {
+        match initial_load {
             Some(data) if !data.is_empty() => data.pop(),
-            _ => self.listener.recv().await,
+            _ => listener.recv().await,
         }
     }
 
@@ -0,0 +1,381 @@
+use std::time::Duration;
+
+use anyhow::{Context, bail};
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use super::StateChangedEvent;
+
+//get_states returns all entities in a single message
+const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+const SUBSCRIBE_ID: u64 = 1;
+const GET_STATES_ID: u64 = 2;
+
+/// State changes received via HA's WebSocket API. The connection is re-established in the background
+/// and every new connection starts with a resync of all states.
+pub struct HaWebSocketClient {
+    rx: mpsc::Receiver<StateChangedEvent>,
+}
+
+impl HaWebSocketClient {
+    pub fn new(url: &str, token: &str) -> Self {
+        Self::with_reconnect_delay(url, token, Duration::from_secs(1))
+    }
+
+    fn with_reconnect_delay(url: &str, token: &str, initial_delay: Duration) -> Self {
+        let (tx, rx) = mpsc::channel(64);
+
+        // Connection runs in its own task to keep recv cancel-safe when used in select!
+        tokio::spawn(run(url.to_owned(), token.to_owned(), initial_delay, tx));
+
+        Self { rx }
+    }
+
+    pub async fn recv(&mut self) -> Option<StateChangedEvent> {
+        self.rx.recv().await
+    }
+}
+
+async fn run(url: String, token: String, initial_delay: Duration, tx: mpsc::Sender<StateChangedEvent>) {
+    let mut delay = initial_delay;
+
+    loop {
+        let started = tokio::time::Instant::now();
+
+        match session(&url, &token, &tx).await {
+            Ok(()) => tracing::warn!("HA WebSocket connection closed"),
+            Err(e) => tracing::error!("Error in HA WebSocket connection: {:?}", e),
+        }
+
+        if tx.is_closed() {
+            return;
+        }
+
+        if started.elapsed() > MAX_RECONNECT_DELAY {
+            delay = initial_delay;
+        }
+
+        tracing::info!("Reconnecting to HA WebSocket API in {:?}", delay);
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+    }
+}
+
+async fn session(url: &str, token: &str, tx: &mpsc::Sender<StateChangedEvent>) -> anyhow::Result<()> {
+    let mut conn = HaWsConnection::connect(url).await?;
+
+    match conn.next_message().await? {
+        Some(HaWsMessage::AuthRequired) => {}
+        other => bail!("Expected auth_required from HA, got {:?}", other),
+    }
+
+    conn.send_json(json!({"type": "auth", "access_token": token})).await?;
+
+    match conn.next_message().await? {
+        Some(HaWsMessage::AuthOk) => {}
+        Some(HaWsMessage::AuthInvalid { message }) => bail!("HA rejected access token: {}", message),
+        other => bail!("Expected auth_ok from HA, got {:?}", other),
+    }
+
+    //Subscribe before loading states to not miss changes in between
+    conn.send_json(json!({"id": SUBSCRIBE_ID, "type": "subscribe_events", "event_type": "state_changed"}))
+        .await?;
+    conn.send_json(json!({"id": GET_STATES_ID, "type": "get_states"}))
+        .await?;
+
+    tracing::info!("Connected to HA WebSocket API");
+
+    let mut next_id = GET_STATES_ID + 1;
+    let mut awaiting_pong = false;
+
+    loop {
+        let text = match tokio::time::timeout(HEARTBEAT_INTERVAL, conn.next_text()).await {
+            Ok(text) => text?,
+            Err(_) if awaiting_pong => bail!("No response from HA to heartbeat"),
+            Err(_) => {
+                conn.send_json(json!({"id": next_id, "type": "ping"})).await?;
+                next_id += 1;
+                awaiting_pong = true;
+                continue;
+            }
+        };
+
+        awaiting_pong = false;
+
+        let Some(text) = text else {
+            return Ok(());
+        };
+
+        let events = match serde_json::from_str::<HaWsMessage>(&text) {
+            Ok(HaWsMessage::Event { event }) => event.data.new_state.into_iter().collect(),
+            Ok(HaWsMessage::Result {
+                id: GET_STATES_ID,
+                success: true,
+                result,
+                ..
+            }) => serde_json::from_value::<Vec<StateChangedEvent>>(result).context("Error parsing HA states")?,
+            Ok(HaWsMessage::Result {
+                id,
+                success: false,
+                error,
+                ..
+            }) => bail!("HA request {} failed: {:?}", id, error),
+            Ok(_) => vec![],
+            Err(e) => {
+                tracing::error!("Error parsing HA WebSocket message: {}", e);
+                vec![]
+            }
+        };
+
+        for event in events {
+            tx.send_timeout(event, Duration::from_secs(30))
+                .await
+                .context("Error forwarding HA state change")?;
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum HaWsMessage {
+    AuthRequired,
+    AuthOk,
+    AuthInvalid {
+        #[serde(default)]
+        message: String,
+    },
+    Result {
+        id: u64,
+        success: bool,
+        #[serde(default)]
+        result: Value,
+        error: Option<Value>,
+    },
+    Event {
+        event: HaWsEvent,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize, Debug)]
+struct HaWsEvent {
+    data: HaWsStateChangedData,
+}
+
+#[derive(Deserialize, Debug)]
+struct HaWsStateChangedData {
+    //null when the entity was removed
+    new_state: Option<StateChangedEvent>,
+}
+
+struct HaWsConnection<S = MaybeTlsStream<TcpStream>> {
+    stream: WebSocketStream<S>,
+}
+
+impl HaWsConnection {
+    async fn connect(url: &str) -> anyhow::Result<Self> {
+        let mut url = reqwest::Url::parse(url).context("Invalid HA url")?;
+        let scheme = match url.scheme() {
+            "http" | "ws" => "ws",
+            "https" | "wss" => "wss",
+            other => bail!("Unsupported scheme {} for HA WebSocket API", other),
+        };
+        if url.set_scheme(scheme).is_err() {
+            bail!("Error changing scheme of HA url {} to {}", url, scheme);
+        }
+        url.set_path(&format!("{}/api/websocket", url.path().trim_end_matches('/')));
+
+        let config = WebSocketConfig::default().max_message_size(Some(MAX_MESSAGE_SIZE));
+        let (stream, _) = tokio_tungstenite::connect_async_with_config(url.as_str(), Some(config), false)
+            .await
+            .with_context(|| format!("Error connecting to HA WebSocket API at {}", url))?;
+
+        Ok(Self { stream })
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> HaWsConnection<S> {
+    async fn send_json(&mut self, value: Value) -> anyhow::Result<()> {
+        self.stream
+            .send(Message::text(value.to_string()))
+            .await
+            .context("Error sending to HA WebSocket")
+    }
+
+    async fn next_message(&mut self) -> anyhow::Result<Option<HaWsMessage>> {
+        match self.next_text().await? {
+            Some(text) => Ok(Some(serde_json::from_str(&text)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Next text message, pings are answered by the stream. None if the connection was closed.
+    async fn next_text(&mut self) -> anyhow::Result<Option<String>> {
+        while let Some(message) = self.stream.next().await {
+            match message.context("Error reading from HA WebSocket")? {
+                Message::Text(text) => return Ok(Some(text.to_string())),
+                Message::Close(reason) => {
+                    tracing::info!("HA closed WebSocket connection: {:?}", reason);
+                    return Ok(None);
+                }
+                Message::Binary(_) | Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => {}
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::time::DateTime;
+    use crate::device_state::adapter::homeassistant::StateValue;
+    use tokio::net::TcpListener;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn temperature_state(value: &str) -> Value {
+        json!({
+            "entity_id": "sensor.bathroom_temperature",
+            "state": value,
+            "attributes": {"unit_of_measurement": "°C"},
+            "last_changed": "2025-03-01T18:30:12.123456+00:00",
+            "last_updated": "2025-03-01T18:30:12.123456+00:00",
+            "context": {"id": "01JN", "parent_id": null, "user_id": null}
+        })
+    }
+
+    //Stand-in for the HA side: upgrade, auth, subscription and get_states
+    async fn accept(listener: &TcpListener, token: &str) -> anyhow::Result<Option<HaWsConnection<TcpStream>>> {
+        let (stream, _) = listener.accept().await?;
+
+        let mut conn = HaWsConnection {
+            stream: tokio_tungstenite::accept_async(stream).await?,
+        };
+        conn.send_json(json!({"type": "auth_required", "ha_version": "2025.3.0"}))
+            .await?;
+
+        let auth: Value = serde_json::from_str(&conn.next_text().await?.context("No auth")?)?;
+        assert_eq!(auth["type"], "auth");
+        if auth["access_token"] != token {
+            conn.send_json(json!({"type": "auth_invalid", "message": "Invalid access token"}))
+                .await?;
+            return Ok(None);
+        }
+        conn.send_json(json!({"type": "auth_ok", "ha_version": "2025.3.0"}))
+            .await?;
+
+        Ok(Some(conn))
+    }
+
+    async fn serve_session(
+        conn: &mut HaWsConnection<TcpStream>,
+        states: Vec<Value>,
+        changes: Vec<Value>,
+    ) -> anyhow::Result<()> {
+        let subscribe: Value = serde_json::from_str(&conn.next_text().await?.context("No subscribe")?)?;
+        assert_eq!(subscribe["type"], "subscribe_events");
+        assert_eq!(subscribe["event_type"], "state_changed");
+        conn.send_json(json!({"id": subscribe["id"], "type": "result", "success": true, "result": null}))
+            .await?;
+
+        let get_states: Value = serde_json::from_str(&conn.next_text().await?.context("No get_states")?)?;
+        assert_eq!(get_states["type"], "get_states");
+        conn.send_json(json!({"id": get_states["id"], "type": "result", "success": true, "result": states}))
+            .await?;
+
+        for new_state in changes {
+            conn.send_json(json!({
+                "id": subscribe["id"],
+                "type": "event",
+                "event": {
+                    "event_type": "state_changed",
+                    "data": {"entity_id": "sensor.bathroom_temperature", "old_state": null, "new_state": new_state}
+                }
+            }))
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn recv_state(client: &mut HaWebSocketClient) -> anyhow::Result<StateChangedEvent> {
+        tokio::time::timeout(TIMEOUT, client.recv())
+            .await?
+            .context("Client channel closed")
+    }
+
+    #[tokio::test]
+    async fn forwards_state_changes_and_resyncs_after_reconnect() -> anyhow::Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+
+        let server = tokio::spawn(async move {
+            let mut conn = accept(&listener, "secret").await?.context("Auth failed")?;
+            serve_session(
+                &mut conn,
+                vec![temperature_state("21.5")],
+                vec![temperature_state("22.0"), Value::Null],
+            )
+            .await?;
+            drop(conn);
+
+            let mut conn = accept(&listener, "secret").await?.context("Auth failed")?;
+            serve_session(&mut conn, vec![temperature_state("22.5")], vec![]).await?;
+
+            //Keep the connection open until the client is done
+            conn.next_text().await
+        });
+
+        let mut client = HaWebSocketClient::with_reconnect_delay(&url, "secret", Duration::from_millis(10));
+
+        let mut values = vec![];
+        for _ in 0..3 {
+            let event = recv_state(&mut client).await?;
+            assert_eq!(event.entity_id, "sensor.bathroom_temperature");
+            assert_eq!(
+                event.last_changed,
+                DateTime::from_static_iso("2025-03-01T18:30:12.123456Z")
+            );
+            match event.state {
+                StateValue::Available(value) => values.push(value),
+                StateValue::Unavailable => panic!("Expected available state"),
+            }
+        }
+
+        assert_eq!(values, vec!["21.5", "22.0", "22.5"]);
+
+        drop(client);
+        server.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn invalid_token_fails_session() -> anyhow::Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+
+        let server = tokio::spawn(async move { accept(&listener, "secret").await.map(|conn| conn.is_none()) });
+
+        let (tx, _rx) = mpsc::channel(1);
+        let Err(err) = session(&url, "wrong", &tx).await else {
+            panic!("Expected session to fail for invalid token");
+        };
+
+        assert!(format!("{err:?}").contains("rejected access token"));
+        assert!(server.await??);
+        Ok(())
+    }
+}
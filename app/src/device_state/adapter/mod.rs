@@ -1,7 +1,9 @@
+pub mod api;
 pub mod db;
 pub mod energy_meter;
 pub mod homeassistant;
 pub mod internal;
+pub mod nuki;
 pub mod tasmota;
 pub mod z2m;
 
@@ -0,0 +1,13 @@
+use crate::device_state::{BatteryLevel, Locked, Opened};
+
+use super::NukiChannel;
+
+pub fn default_nuki_state_config(smart_lock_id: &str) -> Vec<(&str, NukiChannel)> {
+    vec![
+        (
+            smart_lock_id,
+            NukiChannel::SmartLock(Locked::ApartmentDoor, BatteryLevel::ApartmentDoorLock),
+        ),
+        (smart_lock_id, NukiChannel::DoorSensor(Opened::ApartmentDoor)),
+    ]
+}
@@ -0,0 +1,337 @@
+mod config;
+
+use crate::core::DeviceConfig;
+use crate::core::time::DateTime;
+use crate::core::timeseries::DataPoint;
+use crate::core::unit::Percent;
+use crate::device_state::adapter::{IncomingData, IncomingDataSource};
+use crate::device_state::{BatteryLevel, DeviceAvailability, DeviceStateValue, Locked, Opened};
+use anyhow::Context;
+use infrastructure::HttpClientConfig;
+use reqwest_middleware::ClientWithMiddleware;
+use serde::Deserialize;
+use tokio::sync::mpsc;
+
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+pub enum NukiChannel {
+    SmartLock(Locked, BatteryLevel),
+    DoorSensor(Opened),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NukiDevice {
+    nuki_id: u64,
+    last_known_state: NukiDeviceState,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NukiDeviceState {
+    state: u8,
+    battery_charge_state: Option<f64>,
+    doorsensor_state: Option<u8>,
+    timestamp: DateTime,
+}
+
+pub struct NukiIncomingDataSource {
+    device_config: DeviceConfig<NukiChannel>,
+    rx: mpsc::Receiver<Vec<NukiDevice>>,
+    pending: Vec<NukiDevice>,
+}
+
+impl NukiIncomingDataSource {
+    #[allow(clippy::expect_used)]
+    pub fn new(bridge_url: &str, token: &str, smart_lock_id: &str) -> Self {
+        let client = NukiBridgeClient::new(bridge_url, token).expect("Error creating Nuki Bridge HTTP client");
+        let (tx, rx) = mpsc::channel(4);
+
+        // Polling runs in its own task to keep recv cancel-safe when used in select!
+        tokio::spawn(poll_bridge(client, tx));
+
+        Self {
+            device_config: DeviceConfig::new(&config::default_nuki_state_config(smart_lock_id)),
+            rx,
+            pending: vec![],
+        }
+    }
+}
+
+impl IncomingDataSource<NukiDevice, NukiChannel> for NukiIncomingDataSource {
+    fn ds_name(&self) -> &str {
+        "Nuki"
+    }
+
+    async fn recv(&mut self) -> Option<NukiDevice> {
+        loop {
+            if let Some(device) = self.pending.pop() {
+                return Some(device);
+            }
+
+            self.pending = self.rx.recv().await?;
+        }
+    }
+
+    fn device_id(&self, msg: &NukiDevice) -> Option<String> {
+        Some(format!("{:X}", msg.nuki_id))
+    }
+
+    fn get_channels(&self, device_id: &str) -> &[NukiChannel] {
+        self.device_config.get(device_id)
+    }
+
+    async fn to_incoming_data(
+        &self,
+        device_id: &str,
+        channel: &NukiChannel,
+        msg: &NukiDevice,
+    ) -> anyhow::Result<Vec<IncomingData>> {
+        Ok(to_incoming_data(device_id, channel, &msg.last_known_state))
+    }
+}
+
+fn to_incoming_data(device_id: &str, channel: &NukiChannel, state: &NukiDeviceState) -> Vec<IncomingData> {
+    let mut result: Vec<IncomingData> = vec![];
+
+    match channel {
+        NukiChannel::SmartLock(locked, battery) => {
+            // 2 = unlocking, 4 = locking, 7 = unlatching: wait for the final state
+            let is_locked = match state.state {
+                1 => Some(true),
+                3 | 5 | 6 => Some(false),
+                _ => None,
+            };
+
+            if let Some(is_locked) = is_locked {
+                result.push(DataPoint::new(DeviceStateValue::Locked(*locked, is_locked), state.timestamp).into());
+            }
+
+            if let Some(charge) = state.battery_charge_state {
+                result.push(
+                    DataPoint::new(
+                        DeviceStateValue::BatteryLevel(*battery, Percent(charge)),
+                        state.timestamp,
+                    )
+                    .into(),
+                );
+            }
+
+            result.push(
+                DeviceAvailability {
+                    source: "Nuki".to_string(),
+                    device_id: device_id.to_string(),
+                    last_seen: state.timestamp,
+                    marked_offline: false,
+                }
+                .into(),
+            );
+        }
+
+        NukiChannel::DoorSensor(opened) => {
+            let is_opened = match state.doorsensor_state {
+                Some(2) => Some(false),
+                Some(3) => Some(true),
+                _ => None,
+            };
+
+            if let Some(is_opened) = is_opened {
+                result.push(DataPoint::new(DeviceStateValue::Opened(*opened, is_opened), state.timestamp).into());
+            }
+        }
+    }
+
+    result
+}
+
+async fn poll_bridge(client: NukiBridgeClient, tx: mpsc::Sender<Vec<NukiDevice>>) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        interval.tick().await;
+
+        let devices = match client.list().await {
+            Ok(devices) => devices,
+            Err(e) => {
+                tracing::error!("Error polling Nuki bridge: {:?}", e);
+                continue;
+            }
+        };
+
+        if let Err(e) = tx.send_timeout(devices, std::time::Duration::from_secs(30)).await {
+            tracing::error!("Error forwarding Nuki bridge state: {:?}", e);
+        }
+    }
+}
+
+struct NukiBridgeClient {
+    client: ClientWithMiddleware,
+    bridge_url: String,
+    token: String,
+}
+
+impl NukiBridgeClient {
+    fn new(bridge_url: &str, token: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            client: HttpClientConfig::new(None).new_tracing_client()?,
+            bridge_url: bridge_url.to_owned(),
+            token: token.to_owned(),
+        })
+    }
+
+    async fn list(&self) -> anyhow::Result<Vec<NukiDevice>> {
+        let response = self
+            .client
+            .get(format!("{}/list?token={}", self.bridge_url, self.token))
+            .send()
+            .await
+            // URL contains the token
+            .map_err(|e| match e {
+                reqwest_middleware::Error::Reqwest(e) => anyhow::anyhow!(e.without_url()),
+                e => anyhow::anyhow!(e),
+            })
+            .context("Error calling Nuki bridge")?;
+
+        response
+            .error_for_status()
+            .map_err(|e| e.without_url())?
+            .json::<Vec<NukiDevice>>()
+            .await
+            .context("Error parsing Nuki bridge device list")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+
+    const LIST_RESPONSE: &str = r#"[
+        {
+            "deviceType": 4,
+            "nukiId": 712981524,
+            "name": "Wohnungstür",
+            "firmwareVersion": "3.5.11",
+            "lastKnownState": {
+                "mode": 2,
+                "state": 1,
+                "stateName": "locked",
+                "batteryCritical": false,
+                "batteryCharging": false,
+                "batteryChargeState": 84,
+                "doorsensorState": 2,
+                "doorsensorStateName": "door closed",
+                "timestamp": "2025-03-01T18:30:12+00:00"
+            }
+        },
+        {
+            "deviceType": 2,
+            "nukiId": 482937034,
+            "name": "Haustür",
+            "lastKnownState": {
+                "mode": 3,
+                "state": 1,
+                "stateName": "online",
+                "batteryCritical": false,
+                "timestamp": "2025-03-01T18:29:55+00:00"
+            }
+        }
+    ]"#;
+
+    #[tokio::test]
+    async fn list_is_mapped_to_lock_door_and_battery_state() -> anyhow::Result<()> {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/list")
+            .match_query(Matcher::UrlEncoded("token".into(), "secret".into()))
+            .with_status(200)
+            .with_body(LIST_RESPONSE)
+            .create_async()
+            .await;
+
+        let client = NukiBridgeClient::new(&server.url(), "secret")?;
+        let devices = client.list().await?;
+        mock.assert_async().await;
+
+        assert_eq!(devices.len(), 2);
+        assert_eq!(format!("{:X}", devices[0].nuki_id), "2A7F3C14");
+
+        let state = &devices[0].last_known_state;
+        let timestamp = DateTime::from_static_iso("2025-03-01T18:30:12Z");
+
+        let lock_data = to_incoming_data(
+            "2A7F3C14",
+            &NukiChannel::SmartLock(Locked::ApartmentDoor, BatteryLevel::ApartmentDoorLock),
+            state,
+        );
+        let values: Vec<_> = lock_data
+            .iter()
+            .filter_map(|d| match d {
+                IncomingData::StateValue(dp) => Some((dp.value.clone(), dp.timestamp)),
+                IncomingData::ItemAvailability(_) => None,
+            })
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                (DeviceStateValue::Locked(Locked::ApartmentDoor, true), timestamp),
+                (
+                    DeviceStateValue::BatteryLevel(BatteryLevel::ApartmentDoorLock, Percent(84.0)),
+                    timestamp
+                ),
+            ]
+        );
+        assert!(
+            lock_data
+                .iter()
+                .any(|d| matches!(d, IncomingData::ItemAvailability(a) if a.device_id == "2A7F3C14"))
+        );
+
+        let door_data = to_incoming_data("2A7F3C14", &NukiChannel::DoorSensor(Opened::ApartmentDoor), state);
+        assert!(matches!(
+            door_data.as_slice(),
+            [IncomingData::StateValue(dp)] if dp.value == DeviceStateValue::Opened(Opened::ApartmentDoor, false)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn transitional_lock_state_is_skipped() {
+        let state = NukiDeviceState {
+            state: 4,
+            battery_charge_state: None,
+            doorsensor_state: None,
+            timestamp: DateTime::from_static_iso("2025-03-01T18:30:12Z"),
+        };
+
+        let data = to_incoming_data(
+            "2A7F3C14",
+            &NukiChannel::SmartLock(Locked::ApartmentDoor, BatteryLevel::ApartmentDoorLock),
+            &state,
+        );
+
+        assert!(data.iter().all(|d| matches!(d, IncomingData::ItemAvailability(_))));
+    }
+
+    #[tokio::test]
+    async fn bridge_error_is_reported() -> anyhow::Result<()> {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/list")
+            .match_query(Matcher::Any)
+            .with_status(401)
+            .create_async()
+            .await;
+
+        let client = NukiBridgeClient::new(&server.url(), "wrong")?;
+        let Err(err) = client.list().await else {
+            panic!("Expected error for unauthorized request");
+        };
+
+        assert!(!format!("{err:?}").contains("wrong"));
+        Ok(())
+    }
+}
@@ -1,11 +1,13 @@
 mod config;
 
 use crate::device_state::adapter::{IncomingData, IncomingDataSource};
-use crate::device_state::{CurrentPowerUsage, DeviceAvailability, PowerAvailable, TotalEnergyConsumption};
+use crate::device_state::{
+    CurrentPowerUsage, DeviceAvailability, DeviceStateId, PowerAvailable, TotalEnergyConsumption,
+};
 
 use crate::core::DeviceConfig;
 
-use crate::core::time::DateTime;
+use crate::core::time::{DateTime, Duration};
 use crate::core::timeseries::DataPoint;
 use crate::core::unit::{KiloWattHours, Watt};
 use crate::device_state::DeviceStateValue;
@@ -19,6 +21,20 @@ pub enum TasmotaChannel {
     PowerToggle(PowerAvailable),
 }
 
+/// Expected report intervals of the items of energy meters, sent every `TelePeriod` (5 minutes by default)
+pub fn periodic_states() -> Vec<(DeviceStateId, Duration)> {
+    config::default_tasmota_state_config()
+        .into_iter()
+        .flat_map(|(_, channel)| match channel {
+            TasmotaChannel::EnergyMeter(power, energy) => vec![
+                (DeviceStateId::CurrentPowerUsage(power), Duration::minutes(5)),
+                (DeviceStateId::TotalEnergyConsumption(energy), Duration::minutes(5)),
+            ],
+            TasmotaChannel::PowerToggle(_) => vec![],
+        })
+        .collect()
+}
+
 pub struct TasmotaIncomingDataSource {
     device_config: DeviceConfig<TasmotaChannel>,
     mqtt_receiver: MqttSubscription,
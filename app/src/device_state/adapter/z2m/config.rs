@@ -190,5 +190,228 @@ pub fn default_z2m_state_config() -> Vec<(&'static str, Z2mChannel)> {
                 Some(PowerAvailable::Dehumidifier),
             ),
         ),
+        //
+        // DEVICE HEALTH
+        //
+        (
+            "living_room/temp_sensor_couch",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::LivingRoomClimateSensor,
+                Some(BatteryLevel::LivingRoomClimateSensor),
+            ),
+        ),
+        (
+            "living_room/temp_sensor_radiator_small",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::RadiatorClimateSensor(Radiator::LivingRoomSmall),
+                Some(BatteryLevel::RadiatorClimateSensor(Radiator::LivingRoomSmall)),
+            ),
+        ),
+        (
+            "living_room/temp_sensor_radiator_big",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::RadiatorClimateSensor(Radiator::LivingRoomBig),
+                Some(BatteryLevel::RadiatorClimateSensor(Radiator::LivingRoomBig)),
+            ),
+        ),
+        (
+            "bedroom/temp_sensor_bed",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::BedroomClimateSensor,
+                Some(BatteryLevel::BedroomClimateSensor),
+            ),
+        ),
+        (
+            "bedroom/temp_sensor_radiator",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::RadiatorClimateSensor(Radiator::Bedroom),
+                Some(BatteryLevel::RadiatorClimateSensor(Radiator::Bedroom)),
+            ),
+        ),
+        (
+            "bedroom/outer_wall",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::BedroomOuterWallClimateSensor,
+                Some(BatteryLevel::BedroomOuterWallClimateSensor),
+            ),
+        ),
+        (
+            "room_of_requirements/temp_sensor_desk",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::RoomOfRequirementsClimateSensor,
+                Some(BatteryLevel::RoomOfRequirementsClimateSensor),
+            ),
+        ),
+        (
+            "room_of_requirements/temp_sensor_radiator",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::RadiatorClimateSensor(Radiator::RoomOfRequirements),
+                Some(BatteryLevel::RadiatorClimateSensor(Radiator::RoomOfRequirements)),
+            ),
+        ),
+        (
+            "bathroom/temp_sensor",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::BathroomClimateSensor,
+                Some(BatteryLevel::BathroomClimateSensor),
+            ),
+        ),
+        (
+            "bathroom/dehumidifier",
+            Z2mChannel::DeviceHealth(LinkQuality::Dehumidifier, None),
+        ),
+        (
+            "bathroom/temp_sensor_radiator",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::RadiatorClimateSensor(Radiator::Bathroom),
+                Some(BatteryLevel::RadiatorClimateSensor(Radiator::Bathroom)),
+            ),
+        ),
+        (
+            "kitchen/temp_sensor",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::KitchenClimateSensor,
+                Some(BatteryLevel::KitchenClimateSensor),
+            ),
+        ),
+        (
+            "kitchen/temp_sensor_outer_wall",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::KitchenOuterWallClimateSensor,
+                Some(BatteryLevel::KitchenOuterWallClimateSensor),
+            ),
+        ),
+        (
+            "kitchen/temp_sensor_radiator",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::RadiatorClimateSensor(Radiator::Kitchen),
+                Some(BatteryLevel::RadiatorClimateSensor(Radiator::Kitchen)),
+            ),
+        ),
+        (
+            "living_room/radiator_thermostat_big_sonoff",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::Thermostat(Radiator::LivingRoomBig),
+                Some(BatteryLevel::Thermostat(Radiator::LivingRoomBig)),
+            ),
+        ),
+        (
+            "living_room/radiator_thermostat_small_sonoff",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::Thermostat(Radiator::LivingRoomSmall),
+                Some(BatteryLevel::Thermostat(Radiator::LivingRoomSmall)),
+            ),
+        ),
+        (
+            "kitchen/radiator_thermostat_sonoff",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::Thermostat(Radiator::Kitchen),
+                Some(BatteryLevel::Thermostat(Radiator::Kitchen)),
+            ),
+        ),
+        (
+            "bedroom/radiator_thermostat_sonoff",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::Thermostat(Radiator::Bedroom),
+                Some(BatteryLevel::Thermostat(Radiator::Bedroom)),
+            ),
+        ),
+        (
+            "room_of_requirements/radiator_thermostat_sonoff",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::Thermostat(Radiator::RoomOfRequirements),
+                Some(BatteryLevel::Thermostat(Radiator::RoomOfRequirements)),
+            ),
+        ),
+        (
+            "bathroom/radiator_thermostat_sonoff",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::Thermostat(Radiator::Bathroom),
+                Some(BatteryLevel::Thermostat(Radiator::Bathroom)),
+            ),
+        ),
+        (
+            "bedroom/window",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::BedroomWindowSensor,
+                Some(BatteryLevel::BedroomWindowSensor),
+            ),
+        ),
+        (
+            "living_room/balcony_door",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::LivingRoomBalconyDoorSensor,
+                Some(BatteryLevel::LivingRoomBalconyDoorSensor),
+            ),
+        ),
+        (
+            "living_room/window_left",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::LivingRoomWindowLeftSensor,
+                Some(BatteryLevel::LivingRoomWindowLeftSensor),
+            ),
+        ),
+        (
+            "living_room/window_right",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::LivingRoomWindowRightSensor,
+                Some(BatteryLevel::LivingRoomWindowRightSensor),
+            ),
+        ),
+        (
+            "living_room/window_side",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::LivingRoomWindowSideSensor,
+                Some(BatteryLevel::LivingRoomWindowSideSensor),
+            ),
+        ),
+        (
+            "kitchen/window",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::KitchenWindowSensor,
+                Some(BatteryLevel::KitchenWindowSensor),
+            ),
+        ),
+        (
+            "room_of_requirements/window_left",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::RoomOfRequirementsWindowLeftSensor,
+                Some(BatteryLevel::RoomOfRequirementsWindowLeftSensor),
+            ),
+        ),
+        (
+            "room_of_requirements/window_right",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::RoomOfRequirementsWindowRightSensor,
+                Some(BatteryLevel::RoomOfRequirementsWindowRightSensor),
+            ),
+        ),
+        (
+            "room_of_requirements/window_side",
+            Z2mChannel::DeviceHealth(
+                LinkQuality::RoomOfRequirementsWindowSideSensor,
+                Some(BatteryLevel::RoomOfRequirementsWindowSideSensor),
+            ),
+        ),
+        (
+            "kitchen/multiplug",
+            Z2mChannel::DeviceHealth(LinkQuality::KitchenMultiPlug, None),
+        ),
+        (
+            "living_room/couch_plug",
+            Z2mChannel::DeviceHealth(LinkQuality::CouchPlug, None),
+        ),
+        (
+            "room_of_requirements/makerspace",
+            Z2mChannel::DeviceHealth(LinkQuality::RoomOfRequirementsDesk, None),
+        ),
+        (
+            "room_of_requirements/desk_monitor",
+            Z2mChannel::DeviceHealth(LinkQuality::RoomOfRequirementsMonitor, None),
+        ),
+        (
+            "bathroom/dehumidifier_plug",
+            Z2mChannel::DeviceHealth(LinkQuality::DehumidifierPlug, None),
+        ),
     ]
 }
@@ -4,13 +4,15 @@ use std::collections::HashMap;
 
 use crate::core::DeviceConfig;
 use crate::core::domain::Radiator;
-use crate::core::time::DateTime;
+use crate::core::time::{DateTime, Duration};
 use crate::core::timeseries::DataPoint;
-use crate::core::unit::{DegreeCelsius, KiloWattHours, Percent, Watt};
+use crate::core::unit::{DegreeCelsius, KiloWattHours, Lqi, Percent, Watt};
 use crate::device_state::adapter::{IncomingData, IncomingDataSource};
 use crate::device_state::{
-    DeviceAvailability, DeviceStateValue, HeatingDemandLimit, PowerAvailable, SetPoint, Temperature,
+    BatteryLevel, DeviceAvailability, DeviceStateId, DeviceStateValue, HeatingDemandLimit, LinkQuality, PowerAvailable,
+    SetPoint, Temperature,
 };
+use crate::core::ExpectedZ2mDevice;
 use infrastructure::{Mqtt, MqttInMessage, MqttSubscription};
 
 use crate::device_state::{CurrentPowerUsage, Opened, RelativeHumidity, TotalEnergyConsumption};
@@ -21,6 +23,53 @@ pub enum Z2mChannel {
     ContactSensor(Opened),
     PowerPlug(CurrentPowerUsage, TotalEnergyConsumption, KiloWattHours, Option<PowerAvailable>),
     SonoffThermostat(Radiator),
+    /// Radio and battery status, reported along with the device's other properties
+    DeviceHealth(LinkQuality, Option<BatteryLevel>),
+}
+
+impl Z2mChannel {
+    /// Payload properties the channel relies on
+    fn required_features(&self) -> &'static [&'static str] {
+        match self {
+            Z2mChannel::ClimateSensor(_, _) => &["temperature", "humidity"],
+            Z2mChannel::ContactSensor(_) => &["contact"],
+            Z2mChannel::PowerPlug(_, _, _, _) => &["power", "energy", "state"],
+            Z2mChannel::SonoffThermostat(_) => &[
+                "system_mode",
+                "valve_opening_degree",
+                "valve_closing_degree",
+                "occupied_heating_setpoint",
+                "temperature_accuracy",
+                "local_temperature",
+                "external_temperature_input",
+            ],
+            Z2mChannel::DeviceHealth(_, None) => &["linkquality"],
+            Z2mChannel::DeviceHealth(_, Some(_)) => &["linkquality", "battery"],
+        }
+    }
+}
+
+pub fn expected_devices() -> Vec<ExpectedZ2mDevice> {
+    config::default_z2m_state_config()
+        .into_iter()
+        .map(|(name, channel)| {
+            ExpectedZ2mDevice::new(name, format!("state {:?}", channel), channel.required_features())
+        })
+        .collect()
+}
+
+/// Expected report intervals of the items of climate sensors, they report periodically even without a change
+pub fn periodic_states() -> Vec<(DeviceStateId, Duration)> {
+    config::default_z2m_state_config()
+        .into_iter()
+        .flat_map(|(_, channel)| match channel {
+            Z2mChannel::ClimateSensor(t, h) => vec![
+                (DeviceStateId::Temperature(t), Duration::minutes(10)),
+                (DeviceStateId::RelativeHumidity(h), Duration::minutes(10)),
+            ],
+            _ => vec![],
+        })
+        .collect()
 }
 
 pub struct Z2mIncomingDataSource {
@@ -249,6 +298,33 @@ impl IncomingDataSource<MqttInMessage, Z2mChannel> for Z2mIncomingDataSource {
 
                 items
             }
+
+            Z2mChannel::DeviceHealth(link_quality, battery_level) => {
+                let payload: DeviceHealth = serde_json::from_str(&msg.payload)?;
+                let mut items = vec![];
+
+                if let Some(linkquality) = payload.linkquality {
+                    items.push(
+                        DataPoint::new(
+                            DeviceStateValue::LinkQuality(*link_quality, Lqi(linkquality)),
+                            payload.last_seen,
+                        )
+                        .into(),
+                    );
+                }
+
+                if let (Some(battery_level), Some(battery)) = (battery_level, payload.battery) {
+                    items.push(
+                        DataPoint::new(
+                            DeviceStateValue::BatteryLevel(*battery_level, Percent(battery)),
+                            payload.last_seen,
+                        )
+                        .into(),
+                    );
+                }
+
+                items
+            }
         };
 
         Ok(result)
@@ -288,6 +364,14 @@ struct PowerPlug {
     last_seen: DateTime,
 }
 
+//Both are missing in some messages, e.g. right after a device joined
+#[derive(Debug, Clone, serde::Deserialize)]
+struct DeviceHealth {
+    linkquality: Option<i64>,
+    battery: Option<f64>,
+    last_seen: DateTime,
+}
+
 #[derive(Debug, Clone, serde::Deserialize)]
 struct SonoffThermostatPayload {
     system_mode: Option<String>,
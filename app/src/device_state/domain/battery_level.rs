@@ -0,0 +1,26 @@
+use r#macro::{EnumVariants, Id};
+
+use crate::core::domain::Radiator;
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, EnumVariants, Id)]
+pub enum BatteryLevel {
+    ApartmentDoorLock,
+    LivingRoomClimateSensor,
+    BedroomClimateSensor,
+    BedroomOuterWallClimateSensor,
+    RoomOfRequirementsClimateSensor,
+    BathroomClimateSensor,
+    KitchenClimateSensor,
+    KitchenOuterWallClimateSensor,
+    RadiatorClimateSensor(Radiator),
+    Thermostat(Radiator),
+    KitchenWindowSensor,
+    BedroomWindowSensor,
+    LivingRoomWindowLeftSensor,
+    LivingRoomWindowRightSensor,
+    LivingRoomWindowSideSensor,
+    LivingRoomBalconyDoorSensor,
+    RoomOfRequirementsWindowLeftSensor,
+    RoomOfRequirementsWindowRightSensor,
+    RoomOfRequirementsWindowSideSensor,
+}
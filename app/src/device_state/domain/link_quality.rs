@@ -0,0 +1,31 @@
+use r#macro::{EnumVariants, Id};
+
+use crate::core::domain::Radiator;
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, EnumVariants, Id)]
+pub enum LinkQuality {
+    LivingRoomClimateSensor,
+    BedroomClimateSensor,
+    BedroomOuterWallClimateSensor,
+    RoomOfRequirementsClimateSensor,
+    BathroomClimateSensor,
+    KitchenClimateSensor,
+    KitchenOuterWallClimateSensor,
+    RadiatorClimateSensor(Radiator),
+    Dehumidifier,
+    Thermostat(Radiator),
+    KitchenWindowSensor,
+    BedroomWindowSensor,
+    LivingRoomWindowLeftSensor,
+    LivingRoomWindowRightSensor,
+    LivingRoomWindowSideSensor,
+    LivingRoomBalconyDoorSensor,
+    RoomOfRequirementsWindowLeftSensor,
+    RoomOfRequirementsWindowRightSensor,
+    RoomOfRequirementsWindowSideSensor,
+    KitchenMultiPlug,
+    CouchPlug,
+    RoomOfRequirementsDesk,
+    RoomOfRequirementsMonitor,
+    DehumidifierPlug,
+}
@@ -0,0 +1,6 @@
+use r#macro::{EnumVariants, Id};
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, EnumVariants, Id)]
+pub enum Locked {
+    ApartmentDoor,
+}
@@ -3,12 +3,15 @@ use r#macro::StateEnumDerive;
 use crate::core::unit::*;
 
 mod allergen_index;
+mod battery_level;
 mod current_power_usage;
 mod energy_saving;
 mod fan_activity;
 mod heating_demand;
 mod heating_demand_limit;
 mod light_level;
+mod link_quality;
+mod locked;
 mod opened;
 mod particulate_matter;
 mod power_available;
@@ -21,12 +24,15 @@ mod total_radiator_consumption;
 mod total_water_consumption;
 
 pub use allergen_index::AllergenIndex;
+pub use battery_level::BatteryLevel;
 pub use current_power_usage::CurrentPowerUsage;
 pub use energy_saving::EnergySaving;
 pub use fan_activity::FanActivity;
 pub use heating_demand::HeatingDemand;
 pub use heating_demand_limit::HeatingDemandLimit;
 pub use light_level::LightLevel;
+pub use link_quality::LinkQuality;
+pub use locked::Locked;
 pub use opened::Opened;
 pub use particulate_matter::ParticulateMatter;
 pub use power_available::PowerAvailable;
@@ -41,12 +47,15 @@ pub use total_water_consumption::TotalWaterConsumption;
 #[derive(Debug, Clone, PartialEq, StateEnumDerive)]
 pub enum DeviceStateValue {
     AllergenIndex(allergen_index::AllergenIndex, AllergenIndexValue),
+    BatteryLevel(battery_level::BatteryLevel, Percent),
     EnergySaving(energy_saving::EnergySaving, bool),
     CurrentPowerUsage(current_power_usage::CurrentPowerUsage, Watt),
     FanActivity(fan_activity::FanActivity, FanAirflow),
     HeatingDemand(heating_demand::HeatingDemand, Percent),
     HeatingDemandLimit(heating_demand_limit::HeatingDemandLimit, Percent),
     LightLevel(light_level::LightLevel, Lux),
+    LinkQuality(link_quality::LinkQuality, Lqi),
+    Locked(locked::Locked, bool),
     Opened(opened::Opened, bool),
     ParticulateMatter(particulate_matter::ParticulateMatter, MicrogramsPerCubicMeter),
     PowerAvailable(power_available::PowerAvailable, bool),
@@ -63,11 +72,13 @@ impl From<&DeviceStateValue> for f64 {
     fn from(value: &DeviceStateValue) -> Self {
         match value {
             DeviceStateValue::AllergenIndex(_, v) => v.into(),
+            DeviceStateValue::BatteryLevel(_, v) => v.into(),
             DeviceStateValue::CurrentPowerUsage(_, v) => v.into(),
             DeviceStateValue::FanActivity(_, v) => v.into(),
             DeviceStateValue::HeatingDemand(_, v) => v.into(),
             DeviceStateValue::HeatingDemandLimit(_, v) => v.into(),
             DeviceStateValue::LightLevel(_, v) => v.into(),
+            DeviceStateValue::LinkQuality(_, v) => v.into(),
             DeviceStateValue::ParticulateMatter(_, v) => v.into(),
             DeviceStateValue::RelativeHumidity(_, v) => v.into(),
             DeviceStateValue::SetPoint(_, v) => v.into(),
@@ -76,6 +87,7 @@ impl From<&DeviceStateValue> for f64 {
             DeviceStateValue::TotalRadiatorConsumption(_, v) => v.into(),
             DeviceStateValue::TotalWaterConsumption(_, v) => v.into(),
             DeviceStateValue::EnergySaving(_, v)
+            | DeviceStateValue::Locked(_, v)
             | DeviceStateValue::Opened(_, v)
             | DeviceStateValue::PowerAvailable(_, v)
             | DeviceStateValue::Presence(_, v) => {
@@ -88,3 +100,35 @@ impl From<&DeviceStateValue> for f64 {
         }
     }
 }
+
+impl DeviceStateValue {
+    /// Inverse of the conversion to f64, booleans are true for any positive value
+    pub fn from_f64(id: DeviceStateId, value: f64) -> Self {
+        fn bool_of(f: f64) -> bool {
+            f > f64::EPSILON
+        }
+
+        match id {
+            DeviceStateId::AllergenIndex(id) => DeviceStateValue::AllergenIndex(id, value.into()),
+            DeviceStateId::BatteryLevel(id) => DeviceStateValue::BatteryLevel(id, value.into()),
+            DeviceStateId::EnergySaving(id) => DeviceStateValue::EnergySaving(id, bool_of(value)),
+            DeviceStateId::Locked(id) => DeviceStateValue::Locked(id, bool_of(value)),
+            DeviceStateId::Opened(id) => DeviceStateValue::Opened(id, bool_of(value)),
+            DeviceStateId::ParticulateMatter(id) => DeviceStateValue::ParticulateMatter(id, value.into()),
+            DeviceStateId::PowerAvailable(id) => DeviceStateValue::PowerAvailable(id, bool_of(value)),
+            DeviceStateId::Presence(id) => DeviceStateValue::Presence(id, bool_of(value)),
+            DeviceStateId::CurrentPowerUsage(id) => DeviceStateValue::CurrentPowerUsage(id, value.into()),
+            DeviceStateId::FanActivity(id) => DeviceStateValue::FanActivity(id, value.into()),
+            DeviceStateId::HeatingDemand(id) => DeviceStateValue::HeatingDemand(id, value.into()),
+            DeviceStateId::HeatingDemandLimit(id) => DeviceStateValue::HeatingDemandLimit(id, value.into()),
+            DeviceStateId::LightLevel(id) => DeviceStateValue::LightLevel(id, value.into()),
+            DeviceStateId::LinkQuality(id) => DeviceStateValue::LinkQuality(id, value.into()),
+            DeviceStateId::RelativeHumidity(id) => DeviceStateValue::RelativeHumidity(id, value.into()),
+            DeviceStateId::SetPoint(id) => DeviceStateValue::SetPoint(id, value.into()),
+            DeviceStateId::Temperature(id) => DeviceStateValue::Temperature(id, value.into()),
+            DeviceStateId::TotalEnergyConsumption(id) => DeviceStateValue::TotalEnergyConsumption(id, value.into()),
+            DeviceStateId::TotalRadiatorConsumption(id) => DeviceStateValue::TotalRadiatorConsumption(id, value.into()),
+            DeviceStateId::TotalWaterConsumption(id) => DeviceStateValue::TotalWaterConsumption(id, value.into()),
+        }
+    }
+}
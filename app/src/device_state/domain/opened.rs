@@ -11,4 +11,5 @@ pub enum Opened {
     RoomOfRequirementsWindowLeft,
     RoomOfRequirementsWindowRight,
     RoomOfRequirementsWindowSide,
+    ApartmentDoor,
 }
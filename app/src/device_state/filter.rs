@@ -0,0 +1,221 @@
+use std::collections::{HashMap, VecDeque};
+
+use serde::Deserialize;
+
+use crate::{
+    core::{time::DateTime, timeseries::DataPoint},
+    device_state::{DeviceStateId, DeviceStateValue},
+};
+
+/// Plausibility checks of incoming device states, rules are per state type (e.g. `temperature`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlausibilityConfig {
+    /// Store rejected values in `device_state_quarantine`
+    #[serde(default)]
+    pub quarantine: bool,
+    #[serde(default = "default_rules")]
+    pub rules: Vec<PlausibilityRule>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlausibilityRule {
+    #[serde(rename = "type")]
+    pub state_type: String,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub max_change_per_minute: Option<f64>,
+    /// Persist the median of the last N accepted values instead of the raw value
+    pub median_of: Option<usize>,
+}
+
+impl Default for PlausibilityConfig {
+    fn default() -> Self {
+        Self {
+            quarantine: false,
+            rules: default_rules(),
+        }
+    }
+}
+
+fn default_rules() -> Vec<PlausibilityRule> {
+    vec![
+        PlausibilityRule {
+            state_type: "temperature".to_string(),
+            min: Some(-30.0),
+            max: Some(90.0),
+            max_change_per_minute: Some(10.0),
+            median_of: None,
+        },
+        PlausibilityRule {
+            state_type: "relative_humidity".to_string(),
+            min: Some(1.0),
+            max: Some(100.0),
+            max_change_per_minute: Some(25.0),
+            median_of: None,
+        },
+    ]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum RejectReason {
+    #[display("below_min")]
+    BelowMin,
+    #[display("above_max")]
+    AboveMax,
+    #[display("rate_of_change")]
+    RateOfChange,
+}
+
+#[derive(Default)]
+struct ItemHistory {
+    last_accepted: Option<(f64, DateTime)>,
+    window: VecDeque<f64>,
+}
+
+pub struct PlausibilityFilter {
+    rules: HashMap<String, PlausibilityRule>,
+    history: HashMap<DeviceStateId, ItemHistory>,
+}
+
+impl PlausibilityFilter {
+    pub fn new(config: &PlausibilityConfig) -> Self {
+        Self {
+            rules: config
+                .rules
+                .iter()
+                .map(|rule| (rule.state_type.clone(), rule.clone()))
+                .collect(),
+            history: HashMap::new(),
+        }
+    }
+
+    /// Returns the value to persist, smoothed if configured, or the reason for rejecting it.
+    /// The rate of change is compared to the last accepted raw value, so a real jump is accepted
+    /// once enough time has passed.
+    pub fn check(&mut self, dp: DataPoint<DeviceStateValue>) -> Result<DataPoint<DeviceStateValue>, RejectReason> {
+        let id = DeviceStateId::from(&dp.value);
+        let Some(rule) = self.rules.get(id.ext_id().type_name()) else {
+            return Ok(dp);
+        };
+
+        let value = f64::from(&dp.value);
+        if rule.min.is_some_and(|min| value < min) {
+            return Err(RejectReason::BelowMin);
+        }
+        if rule.max.is_some_and(|max| value > max) {
+            return Err(RejectReason::AboveMax);
+        }
+
+        let history = self.history.entry(id).or_default();
+
+        if let (Some(max_change), Some((last_value, last_time))) = (rule.max_change_per_minute, history.last_accepted) {
+            //changes within the first minute are allowed up to the per-minute limit
+            let minutes = dp.timestamp.elapsed_since(last_time).as_minutes_f64().max(1.0);
+            if (value - last_value).abs() / minutes > max_change {
+                return Err(RejectReason::RateOfChange);
+            }
+        }
+
+        history.last_accepted = Some((value, dp.timestamp));
+
+        match rule.median_of {
+            Some(n) if n > 1 => {
+                history.window.push_back(value);
+                while history.window.len() > n {
+                    history.window.pop_front();
+                }
+
+                Ok(DataPoint::new(
+                    DeviceStateValue::from_f64(id, median(&history.window)),
+                    dp.timestamp,
+                ))
+            }
+            _ => Ok(dp),
+        }
+    }
+}
+
+fn median(values: &VecDeque<f64>) -> f64 {
+    let mut sorted: Vec<f64> = values.iter().copied().collect();
+    sorted.sort_by(f64::total_cmp);
+
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::unit::{DegreeCelsius, Percent},
+        device_state::{RelativeHumidity, Temperature},
+        t,
+    };
+
+    fn temperature(value: f64, timestamp: DateTime) -> DataPoint<DeviceStateValue> {
+        DataPoint::new(
+            DeviceStateValue::Temperature(Temperature::LivingRoom, DegreeCelsius(value)),
+            timestamp,
+        )
+    }
+
+    #[test]
+    fn values_out_of_bounds_are_rejected() {
+        let mut filter = PlausibilityFilter::new(&PlausibilityConfig::default());
+
+        assert_eq!(
+            filter.check(temperature(-40.0, t!(now))).err(),
+            Some(RejectReason::BelowMin)
+        );
+        assert_eq!(
+            filter
+                .check(DataPoint::new(
+                    DeviceStateValue::RelativeHumidity(RelativeHumidity::LivingRoom, Percent(0.0)),
+                    t!(now)
+                ))
+                .err(),
+            Some(RejectReason::BelowMin)
+        );
+        assert!(filter.check(temperature(21.0, t!(now))).is_ok());
+    }
+
+    #[test]
+    fn spikes_are_rejected_until_enough_time_passed() {
+        let mut filter = PlausibilityFilter::new(&PlausibilityConfig::default());
+
+        assert!(filter.check(temperature(21.0, t!(10 minutes ago))).is_ok());
+        assert_eq!(
+            filter.check(temperature(45.0, t!(9 minutes ago))).err(),
+            Some(RejectReason::RateOfChange)
+        );
+        assert!(filter.check(temperature(21.5, t!(8 minutes ago))).is_ok());
+        assert!(filter.check(temperature(45.0, t!(now))).is_ok());
+    }
+
+    #[test]
+    fn median_smoothing_is_applied() {
+        let config = PlausibilityConfig {
+            quarantine: false,
+            rules: vec![PlausibilityRule {
+                state_type: "temperature".to_string(),
+                min: None,
+                max: None,
+                max_change_per_minute: None,
+                median_of: Some(3),
+            }],
+        };
+        let mut filter = PlausibilityFilter::new(&config);
+
+        let values: Vec<f64> = [20.0, 30.0, 21.0, 22.0]
+            .into_iter()
+            .filter_map(|v| filter.check(temperature(v, t!(now))).ok())
+            .map(|dp| f64::from(&dp.value))
+            .collect();
+
+        assert_eq!(values, vec![20.0, 25.0, 21.0, 22.0]);
+    }
+}
@@ -0,0 +1,365 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    sync::{
+        Arc, Mutex, MutexGuard,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt as _, BufReader, Lines};
+
+use crate::{
+    core::{
+        id::ExternalId,
+        sync::lock_ignoring_poison,
+        time::{DateTime, Duration},
+        timeseries::DataPoint,
+    },
+    device_state::{DeviceStateId, DeviceStateValue, adapter::db::DeviceStateRepository},
+};
+
+const BATCH_SIZE: usize = 5_000;
+/// Only the first invalid rows are kept in the progress report
+const MAX_REPORTED_ERRORS: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportFormat {
+    /// Header `external_id,value,timestamp`
+    Csv,
+    /// One `{"external_id": ..., "value": ..., "timestamp": ...}` object per line
+    Jsonl,
+}
+
+impl ImportFormat {
+    pub fn from_file_name(name: &str) -> Self {
+        if name.ends_with(".csv") {
+            ImportFormat::Csv
+        } else {
+            ImportFormat::Jsonl
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportProgress {
+    pub status: ImportStatus,
+    pub processed_rows: usize,
+    pub imported: u64,
+    /// Rows with an existing value at the same timestamp or without a change to the previous value
+    pub skipped: u64,
+    /// Number of rows per external id that doesn't resolve to a device state
+    pub unknown_tags: BTreeMap<String, usize>,
+    pub invalid_rows: Vec<String>,
+    pub error: Option<String>,
+    pub finished_at: Option<DateTime>,
+}
+
+impl ImportProgress {
+    fn new() -> Self {
+        Self {
+            status: ImportStatus::Running,
+            processed_rows: 0,
+            imported: 0,
+            skipped: 0,
+            unknown_tags: BTreeMap::new(),
+            invalid_rows: vec![],
+            error: None,
+            finished_at: None,
+        }
+    }
+
+    fn finish(&mut self, status: ImportStatus) {
+        self.status = status;
+        self.finished_at = Some(DateTime::now());
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ImportRecord {
+    external_id: String,
+    value: f64,
+    timestamp: String,
+}
+
+#[derive(Debug, PartialEq)]
+enum RowError {
+    Invalid(String),
+    UnknownTag(String),
+}
+
+/// Background imports, progress is kept in memory until restart or an hour after the import finished
+#[derive(Clone, Default)]
+pub struct ImportJobs {
+    next_id: Arc<AtomicU64>,
+    jobs: Arc<Mutex<HashMap<u64, ImportProgress>>>,
+}
+
+impl ImportJobs {
+    /// Imports the file in the background and removes it afterwards
+    pub fn start(&self, repo: DeviceStateRepository, format: ImportFormat, file: PathBuf) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let jobs = self.clone();
+
+        self.jobs()
+            .retain(|_, progress| progress.finished_at.is_none_or(|at| at.elapsed() < Duration::hours(1)));
+
+        tokio::spawn(async move {
+            let result = match tokio::fs::File::open(&file).await {
+                Ok(reader) => {
+                    import(&repo, format, BufReader::new(reader), |progress| {
+                        jobs.jobs().insert(id, progress.clone());
+                    })
+                    .await
+                }
+                Err(e) => Err(e.into()),
+            };
+
+            if let Err(e) = tokio::fs::remove_file(&file).await {
+                tracing::warn!("Error removing import file {}: {:?}", file.display(), e);
+            }
+
+            match result {
+                Ok(progress) => tracing::info!("Device state import {} completed: {:?}", id, progress),
+                Err(e) => tracing::error!("Device state import {} failed: {:?}", id, e),
+            }
+        });
+
+        id
+    }
+
+    pub fn get(&self, id: u64) -> Option<ImportProgress> {
+        self.jobs().get(&id).cloned()
+    }
+
+    fn jobs(&self) -> MutexGuard<'_, HashMap<u64, ImportProgress>> {
+        lock_ignoring_poison(&self.jobs)
+    }
+}
+
+/// Reads and writes the rows in batches, skipping values that already exist for the same item and timestamp
+/// or don't change the value. Progress is reported after every batch.
+pub async fn import(
+    repo: &DeviceStateRepository,
+    format: ImportFormat,
+    reader: impl AsyncBufRead + Unpin,
+    mut on_progress: impl FnMut(&ImportProgress),
+) -> anyhow::Result<ImportProgress> {
+    let mut rows = RowReader::new(format, reader);
+    let mut progress = ImportProgress::new();
+    on_progress(&progress);
+
+    loop {
+        let chunk = match rows.next_batch().await {
+            Ok(chunk) if chunk.is_empty() => break,
+            Ok(chunk) => chunk,
+            Err(e) => {
+                progress.error = Some(format!("{:#}", e));
+                progress.finish(ImportStatus::Failed);
+                on_progress(&progress);
+                return Err(e);
+            }
+        };
+
+        let mut batch = Vec::with_capacity(chunk.len());
+
+        for row in chunk.iter() {
+            match row {
+                Ok(dp) => batch.push(dp.clone()),
+                Err(RowError::UnknownTag(tag)) => *progress.unknown_tags.entry(tag.clone()).or_default() += 1,
+                Err(RowError::Invalid(msg)) if progress.invalid_rows.len() < MAX_REPORTED_ERRORS => {
+                    progress.invalid_rows.push(msg.clone())
+                }
+                Err(RowError::Invalid(_)) => {}
+            }
+        }
+
+        match repo.import_batch(&batch).await {
+            Ok(imported) => {
+                progress.imported += imported;
+                progress.skipped += batch.len() as u64 - imported;
+            }
+            Err(e) => {
+                progress.error = Some(format!("{:#}", e));
+                progress.finish(ImportStatus::Failed);
+                on_progress(&progress);
+                return Err(e);
+            }
+        }
+
+        progress.processed_rows += chunk.len();
+        on_progress(&progress);
+    }
+
+    progress.finish(ImportStatus::Completed);
+    on_progress(&progress);
+
+    Ok(progress)
+}
+
+/// Parses the input line by line, so that only one batch is held in memory
+struct RowReader<R> {
+    format: ImportFormat,
+    lines: Lines<R>,
+    line_number: usize,
+    csv_header: Option<csv::StringRecord>,
+}
+
+impl<R: AsyncBufRead + Unpin> RowReader<R> {
+    fn new(format: ImportFormat, reader: R) -> Self {
+        Self {
+            format,
+            lines: reader.lines(),
+            line_number: 0,
+            csv_header: None,
+        }
+    }
+
+    /// Up to `BATCH_SIZE` rows, empty at the end of the input
+    async fn next_batch(&mut self) -> anyhow::Result<Vec<Result<DataPoint<DeviceStateValue>, RowError>>> {
+        let mut lines = Vec::with_capacity(BATCH_SIZE);
+
+        while lines.len() < BATCH_SIZE
+            && let Some(line) = self.lines.next_line().await?
+        {
+            self.line_number += 1;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            if self.format == ImportFormat::Csv && self.csv_header.is_none() {
+                self.csv_header = Some(csv_records(&line).next().transpose()?.unwrap_or_default());
+                continue;
+            }
+
+            lines.push((self.line_number, line));
+        }
+
+        Ok(parse_rows(self.format, self.csv_header.as_ref(), &lines))
+    }
+}
+
+fn parse_rows(
+    format: ImportFormat,
+    csv_header: Option<&csv::StringRecord>,
+    lines: &[(usize, String)],
+) -> Vec<Result<DataPoint<DeviceStateValue>, RowError>> {
+    match format {
+        ImportFormat::Csv => {
+            //one record per line, quoted values must not contain line breaks
+            let content = lines.iter().map(|(_, line)| line.as_str()).collect::<Vec<_>>().join("\n");
+
+            csv_records(&content)
+                .zip(lines)
+                .map(|(record, (line_number, _))| {
+                    match record.and_then(|r| r.deserialize::<ImportRecord>(csv_header)) {
+                        Ok(record) => to_data_point(record),
+                        Err(e) => Err(RowError::Invalid(format!("line {}: {}", line_number, e))),
+                    }
+                })
+                .collect()
+        }
+        ImportFormat::Jsonl => lines
+            .iter()
+            .map(|(line_number, line)| match serde_json::from_str::<ImportRecord>(line) {
+                Ok(record) => to_data_point(record),
+                Err(e) => Err(RowError::Invalid(format!("line {}: {}", line_number, e))),
+            })
+            .collect(),
+    }
+}
+
+fn csv_records(content: &str) -> impl Iterator<Item = csv::Result<csv::StringRecord>> + '_ {
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes())
+        .into_records()
+}
+
+fn to_data_point(record: ImportRecord) -> Result<DataPoint<DeviceStateValue>, RowError> {
+    //accept both the displayed form `type::name` and `type/name`
+    let external_id = match record.external_id.split_once("::") {
+        Some((type_name, name)) => ExternalId::new(type_name, name),
+        None => ExternalId::from_slash_separated(&record.external_id)
+            .map_err(|_| RowError::UnknownTag(record.external_id.clone()))?,
+    };
+
+    let id = DeviceStateId::try_from(external_id).map_err(|_| RowError::UnknownTag(record.external_id.clone()))?;
+    let timestamp = DateTime::from_iso(&record.timestamp)
+        .map_err(|e| RowError::Invalid(format!("invalid timestamp {}: {}", record.timestamp, e)))?;
+
+    Ok(DataPoint::new(DeviceStateValue::from_f64(id, record.value), timestamp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::unit::DegreeCelsius,
+        device_state::{Opened, Temperature},
+    };
+
+    #[tokio::test]
+    async fn csv_rows_are_resolved() -> anyhow::Result<()> {
+        let content = "external_id,value,timestamp\n\
+            temperature::living_room,21.5,2024-01-10T10:00:00Z\n\
+            opened/kitchen_window,1,2024-01-10T10:05:00Z\n\
+            temperature::garage,12.0,2024-01-10T10:00:00Z\n\
+            temperature::bedroom,abc,2024-01-10T10:00:00Z\n";
+
+        let mut reader = RowReader::new(ImportFormat::Csv, content.as_bytes());
+        let rows = reader.next_batch().await?;
+
+        assert_eq!(rows.len(), 4);
+        assert_eq!(
+            rows[0].as_ref().map(|dp| dp.value.clone()).ok(),
+            Some(DeviceStateValue::Temperature(
+                Temperature::LivingRoom,
+                DegreeCelsius(21.5)
+            ))
+        );
+        assert_eq!(
+            rows[1].as_ref().map(|dp| dp.value.clone()).ok(),
+            Some(DeviceStateValue::Opened(Opened::KitchenWindow, true))
+        );
+        assert_eq!(
+            rows[2].as_ref().err(),
+            Some(&RowError::UnknownTag("temperature::garage".to_string()))
+        );
+        assert!(matches!(&rows[3], Err(RowError::Invalid(msg)) if msg.starts_with("line 5")));
+        assert!(reader.next_batch().await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn jsonl_rows_are_resolved() -> anyhow::Result<()> {
+        let content = r#"{"external_id": "temperature::bedroom", "value": 19.0, "timestamp": "2024-01-10T10:00:00+01:00"}
+
+{"external_id": "temperature::bedroom", "value": 19.5}"#;
+
+        let rows = RowReader::new(ImportFormat::Jsonl, content.as_bytes()).next_batch().await?;
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(
+            rows[0].as_ref().map(|dp| dp.timestamp).ok(),
+            Some(DateTime::from_static_iso("2024-01-10T09:00:00Z"))
+        );
+        assert!(matches!(&rows[1], Err(RowError::Invalid(msg)) if msg.starts_with("line 3")));
+
+        Ok(())
+    }
+}
@@ -1,13 +1,24 @@
 mod adapter;
 mod domain;
+mod filter;
+mod import;
+mod retention;
 mod service;
+mod staleness;
 
+pub use adapter::api::DeviceStateApi;
+pub use adapter::homeassistant::HaEventSource;
 pub use domain::*;
+pub use filter::PlausibilityConfig;
+pub use import::{ImportFormat, ImportProgress};
 use infrastructure::{EventBus, EventListener, Mqtt};
+pub use retention::{RetentionConfig, RetentionReport};
+pub use staleness::StalenessConfig;
 
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
 use sqlx::PgPool;
+use tokio::io::BufReader;
 
 use crate::{
     command::CommandEvent,
@@ -18,9 +29,11 @@ use crate::{
     device_state::{
         adapter::{
             IncomingDataSource as _, db::DeviceStateRepository, energy_meter::EnergyMeterIncomingDataSource,
-            homeassistant::HomeAssistantIncomingDataSource, internal::InternalDataSource,
-            tasmota::TasmotaIncomingDataSource, z2m::Z2mIncomingDataSource,
+            homeassistant::HomeAssistantIncomingDataSource, internal::InternalDataSource, nuki::NukiIncomingDataSource,
+            tasmota::{self, TasmotaIncomingDataSource},
+            z2m::{self, Z2mIncomingDataSource},
         },
+        retention::RetentionJob,
         service::DeviceStateService,
     },
     frontends::energy_meter::EnergyReading,
@@ -60,7 +73,9 @@ pub struct DeviceStateModule {
     z2m_ds: Z2mIncomingDataSource,
     ha_ds: HomeAssistantIncomingDataSource,
     energy_meter_ds: EnergyMeterIncomingDataSource,
+    nuki_ds: NukiIncomingDataSource,
     internal_ds: InternalDataSource,
+    retention: Option<RetentionJob>,
 }
 
 impl DeviceStateModule {
@@ -69,22 +84,34 @@ impl DeviceStateModule {
         mqtt_client: &mut Mqtt,
         tasmota_event_topic: &str,
         z2m_event_topic: &str,
+        ha_event_source: HaEventSource,
         ha_event_topic: &str,
         ha_url: &str,
         ha_token: &str,
+        nuki_url: &str,
+        nuki_token: &str,
+        nuki_smart_lock_id: &str,
         energy_reading_rx: EventListener<EnergyReading>,
         command_events: EventListener<CommandEvent>,
+        plausibility: &PlausibilityConfig,
+        staleness: &StalenessConfig,
+        retention: &RetentionConfig,
     ) -> Self {
         let repo = DeviceStateRepository::new(pool.clone());
         let tasmota_ds = TasmotaIncomingDataSource::new(mqtt_client, tasmota_event_topic).await;
         let z2m_ds = Z2mIncomingDataSource::new(mqtt_client, z2m_event_topic).await;
-        let ha_ds = HomeAssistantIncomingDataSource::new(mqtt_client, ha_event_topic, ha_url, ha_token).await;
+        let ha_ds =
+            HomeAssistantIncomingDataSource::new(mqtt_client, ha_event_source, ha_event_topic, ha_url, ha_token).await;
         let energy_meter_ds = EnergyMeterIncomingDataSource::new(pool, energy_reading_rx);
+        let nuki_ds = NukiIncomingDataSource::new(nuki_url, nuki_token, nuki_smart_lock_id);
         let internal_ds = InternalDataSource::new(command_events);
 
         let event_bus = EventBus::new(128);
 
-        let service = DeviceStateService::new(repo.clone(), event_bus.emitter());
+        let retention = RetentionJob::new(repo.clone(), retention.clone());
+        let periodic_states = z2m::periodic_states().into_iter().chain(tasmota::periodic_states()).collect();
+        let service =
+            DeviceStateService::new(repo.clone(), event_bus.emitter(), plausibility, staleness, periodic_states);
 
         DeviceStateModule {
             service: Arc::new(service),
@@ -93,7 +120,9 @@ impl DeviceStateModule {
             z2m_ds,
             ha_ds,
             energy_meter_ds,
+            nuki_ds,
             internal_ds,
+            retention: Some(retention),
         }
     }
 
@@ -107,7 +136,18 @@ impl DeviceStateModule {
         self.event_bus.subscribe()
     }
 
+    pub fn api(&self) -> DeviceStateApi {
+        DeviceStateApi::new(self.client())
+    }
+
     pub async fn run(mut self) {
+        if let Some(retention) = self.retention.take() {
+            tokio::spawn(retention.run());
+        }
+
+        self.service.start_staleness_tracking().await;
+        let mut staleness_timer = tokio::time::interval(std::time::Duration::from_secs(60));
+
         loop {
             //TODO expose error like "closed" when data-source gets refactored
             let updates = tokio::select! {
@@ -115,7 +155,12 @@ impl DeviceStateModule {
                 updates = self.z2m_ds.recv_multi() => updates,
                 updates = self.ha_ds.recv_multi() => updates,
                 updates = self.energy_meter_ds.recv_multi() => updates,
+                updates = self.nuki_ds.recv_multi() => updates,
                 updates = self.internal_ds.recv_multi() => updates,
+                _ = staleness_timer.tick() => {
+                    self.service.check_staleness().await;
+                    continue;
+                }
             };
 
             if let Some(updates) = updates {
@@ -136,7 +181,32 @@ impl DeviceStateModule {
     }
 }
 
+/// Zigbee2MQTT devices the state ingestion relies on
+pub fn expected_z2m_devices() -> Vec<crate::core::ExpectedZ2mDevice> {
+    adapter::z2m::expected_devices()
+}
+
 impl DeviceStateClient {
+    /// Client backed by the database only, without any incoming data source
+    #[cfg(test)]
+    pub fn with_pool(pool: PgPool, event_bus: &EventBus<DeviceStateEvent>) -> Self {
+        DeviceStateClient {
+            service: Arc::new(DeviceStateService::new(
+                DeviceStateRepository::new(pool),
+                event_bus.emitter(),
+                &PlausibilityConfig::default(),
+                &StalenessConfig::default(),
+                vec![],
+            )),
+        }
+    }
+
+    /// Processes the data point as if it was received from a device
+    #[cfg(test)]
+    pub async fn receive(&self, dp: DataPoint<DeviceStateValue>) {
+        self.service.handle_state_update(dp).await;
+    }
+
     pub async fn get_current_for_all(&self) -> anyhow::Result<HashMap<DeviceStateId, DataPoint<DeviceStateValue>>> {
         self.service.get_current_for_all().await
     }
@@ -151,6 +221,36 @@ impl DeviceStateClient {
     pub async fn get_offline_items(&self) -> anyhow::Result<Vec<OfflineItem>> {
         self.service.get_offline_items().await
     }
+
+    /// Imports historical values from the file in the background and removes it afterwards.
+    /// Returns the id of the import job
+    pub fn start_import(&self, format: ImportFormat, file: PathBuf) -> u64 {
+        self.service.start_import(format, file)
+    }
+
+    pub async fn get_retention_reports(&self) -> anyhow::Result<Vec<RetentionReport>> {
+        self.service.get_retention_reports().await
+    }
+
+    pub fn get_import_progress(&self, id: u64) -> Option<ImportProgress> {
+        self.service.get_import(id)
+    }
+}
+
+/// Imports historical values from a CSV or JSON lines file and waits for completion
+pub async fn import_file(pool: PgPool, path: &str) -> anyhow::Result<ImportProgress> {
+    let file = tokio::fs::File::open(path).await?;
+    let repo = DeviceStateRepository::new(pool);
+
+    import::import(&repo, ImportFormat::from_file_name(path), BufReader::new(file), |progress| {
+        tracing::info!(
+            "Imported {} rows ({} new, {} skipped)",
+            progress.processed_rows,
+            progress.imported,
+            progress.skipped
+        );
+    })
+    .await
 }
 
 fn group_by_device_id(
@@ -0,0 +1,165 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::time::{DateTime, Duration},
+    device_state::{DeviceStateId, adapter::db::DeviceStateRepository},
+    observability::system_metric_set,
+    t,
+};
+
+/// Downsampling of old `thing_value` partitions. Without `keep_raw`, raw data is kept forever
+#[derive(Debug, Clone, Deserialize)]
+pub struct RetentionConfig {
+    /// ISO 8601, e.g. `P180D`. Only partitions that ended before this window are downsampled
+    pub keep_raw: Option<Duration>,
+    #[serde(default = "default_bucket")]
+    pub bucket: Duration,
+    /// Move the raw partition data to the `thing_value_archive` schema instead of dropping it
+    #[serde(default)]
+    pub archive: bool,
+    /// State types with few, meaningful changes keep every value
+    #[serde(default = "default_keep_all_types")]
+    pub keep_all_types: DeviceStateTypes,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            keep_raw: None,
+            bucket: default_bucket(),
+            archive: false,
+            keep_all_types: default_keep_all_types(),
+        }
+    }
+}
+
+fn default_bucket() -> Duration {
+    Duration::minutes(15)
+}
+
+fn default_keep_all_types() -> DeviceStateTypes {
+    DeviceStateTypes(
+        ["opened", "presence", "locked", "power_available", "energy_saving"]
+            .into_iter()
+            .map(|t| t.to_string())
+            .collect(),
+    )
+}
+
+/// Type names of device states as stored in `thing_value_tag.channel`, e.g. `opened`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "Vec<String>")]
+pub struct DeviceStateTypes(BTreeSet<String>);
+
+impl DeviceStateTypes {
+    pub fn names(&self) -> Vec<String> {
+        self.0.iter().cloned().collect()
+    }
+}
+
+impl TryFrom<Vec<String>> for DeviceStateTypes {
+    type Error = anyhow::Error;
+
+    fn try_from(names: Vec<String>) -> Result<Self, Self::Error> {
+        let known = DeviceStateId::variants()
+            .iter()
+            .map(|id| id.ext_id().type_name().to_string())
+            .collect::<BTreeSet<_>>();
+
+        if let Some(unknown) = names.iter().find(|name| !known.contains(*name)) {
+            anyhow::bail!("Unknown device state type {}, expected one of {:?}", unknown, known);
+        }
+
+        Ok(Self(names.into_iter().collect()))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionReport {
+    pub partition: String,
+    pub raw_rows: i64,
+    pub kept_rows: i64,
+    pub raw_bytes: i64,
+    pub downsampled_bytes: i64,
+    pub archived: bool,
+    pub archived_bytes: i64,
+    pub processed_at: DateTime,
+}
+
+impl RetentionReport {
+    /// Space freed by downsampling, archived raw data still occupies space in its own schema
+    pub fn reclaimed_bytes(&self) -> i64 {
+        self.raw_bytes - self.downsampled_bytes - self.archived_bytes
+    }
+}
+
+pub struct RetentionJob {
+    repo: DeviceStateRepository,
+    config: RetentionConfig,
+}
+
+impl RetentionJob {
+    pub fn new(repo: DeviceStateRepository, config: RetentionConfig) -> Self {
+        Self { repo, config }
+    }
+
+    pub async fn run(self) {
+        let mut timer = tokio::time::interval(std::time::Duration::from_secs(24 * 60 * 60));
+
+        loop {
+            timer.tick().await;
+
+            if let Err(e) = self.execute().await {
+                tracing::error!("Error applying thing_value retention: {:?}", e);
+            }
+        }
+    }
+
+    async fn execute(&self) -> anyhow::Result<()> {
+        self.repo.ensure_partitions().await?;
+
+        let Some(keep_raw) = &self.config.keep_raw else {
+            return Ok(());
+        };
+
+        let partitions = self
+            .repo
+            .get_partitions_to_downsample(t!(now) - keep_raw.clone())
+            .await?;
+
+        for partition in partitions {
+            tracing::info!("Downsampling thing_value partition {}", partition);
+
+            let report = self
+                .repo
+                .downsample_partition(
+                    &partition,
+                    &self.config.bucket,
+                    &self.config.keep_all_types,
+                    self.config.archive,
+                )
+                .await?;
+
+            tracing::info!(
+                "Downsampled {} from {} to {} rows, reclaimed {} bytes",
+                report.partition,
+                report.raw_rows,
+                report.kept_rows,
+                report.reclaimed_bytes()
+            );
+        }
+
+        let reclaimed: i64 = self
+            .repo
+            .get_retention_reports()
+            .await?
+            .iter()
+            .map(|r| r.reclaimed_bytes())
+            .sum();
+        system_metric_set("thing_value_retention_reclaimed_bytes", reclaimed as f64, &[]);
+
+        Ok(())
+    }
+}
@@ -1,33 +1,61 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Mutex, MutexGuard},
+};
 
 use infrastructure::EventEmitter;
 use moka::future::Cache;
 
 use crate::{
     core::{
-        time::{DateTime, DateTimeRange},
+        sync::lock_ignoring_poison,
+        time::{DateTime, DateTimeRange, Duration},
         timeseries::DataPoint,
     },
     device_state::{
         DeviceAvailability, DeviceStateEvent, DeviceStateId, DeviceStateValue, OfflineItem,
         adapter::db::DeviceStateRepository,
+        filter::{PlausibilityConfig, PlausibilityFilter, RejectReason},
+        import::{ImportFormat, ImportJobs, ImportProgress},
+        retention::RetentionReport,
+        staleness::{StalenessConfig, StalenessTracker},
     },
+    observability::system_metric_increment,
+    t,
 };
 
+/// Availability source of items that stopped reporting
+const STALENESS_SOURCE: &str = "STALENESS";
+
 pub struct DeviceStateService {
     repo: DeviceStateRepository,
     event_tx: EventEmitter<DeviceStateEvent>,
     current_cache: Cache<DeviceStateId, DataPoint<DeviceStateValue>>,
+    filter: Mutex<PlausibilityFilter>,
+    quarantine: bool,
+    staleness: Mutex<StalenessTracker>,
+    imports: ImportJobs,
 }
 
 impl DeviceStateService {
-    pub fn new(repo: DeviceStateRepository, event_tx: EventEmitter<DeviceStateEvent>) -> Self {
+    pub fn new(
+        repo: DeviceStateRepository,
+        event_tx: EventEmitter<DeviceStateEvent>,
+        plausibility: &PlausibilityConfig,
+        staleness: &StalenessConfig,
+        periodic_states: Vec<(DeviceStateId, Duration)>,
+    ) -> Self {
         let current_cache = Cache::builder().max_capacity(10_000).build();
 
         Self {
             repo,
             event_tx,
             current_cache,
+            filter: Mutex::new(PlausibilityFilter::new(plausibility)),
+            quarantine: plausibility.quarantine,
+            staleness: Mutex::new(StalenessTracker::new(periodic_states, staleness)),
+            imports: ImportJobs::default(),
         }
     }
 
@@ -38,6 +66,17 @@ impl DeviceStateService {
         }
 
         let id = DeviceStateId::from(&dp.value);
+        //implausible values still show that the device is alive
+        self.staleness().record(id, t!(now));
+
+        let checked = self.filter().check(dp.clone());
+        let dp = match checked {
+            Ok(dp) => dp,
+            Err(reason) => {
+                self.reject(&dp, reason).await;
+                return;
+            }
+        };
 
         let changed = match self.repo.save(dp.clone()).await {
             Ok(changed) => changed,
@@ -57,10 +96,94 @@ impl DeviceStateService {
         }
     }
 
+    async fn reject(&self, dp: &DataPoint<DeviceStateValue>, reason: RejectReason) {
+        let ext_id = DeviceStateId::from(&dp.value).ext_id();
+        tracing::warn!("Rejected implausible device state ({}): {:?}", reason, dp);
+
+        let reason = reason.to_string();
+        system_metric_increment(
+            "device_state_rejected",
+            &[
+                ("item_type", ext_id.type_name()),
+                ("item_name", ext_id.variant_name()),
+                ("reason", reason.as_str()),
+            ],
+        );
+
+        if self.quarantine
+            && let Err(e) = self.repo.save_quarantined(dp, &reason).await
+        {
+            tracing::error!("Error quarantining device state {:?}: {:?}", dp, e);
+        }
+    }
+
+    /// Items with a value are expected to keep reporting after a restart
+    pub async fn start_staleness_tracking(&self) {
+        let ids: Vec<DeviceStateId> = match self.get_current_for_all().await {
+            Ok(current) => current.into_keys().collect(),
+            Err(e) => {
+                tracing::error!("Error loading current device states for staleness tracking: {:?}", e);
+                return;
+            }
+        };
+
+        let mut staleness = self.staleness();
+        for id in ids {
+            staleness.record(id, t!(now));
+        }
+    }
+
+    pub async fn check_staleness(&self) {
+        let items = self.staleness().check(t!(now));
+
+        //only stale items have an entry, an entry that is no longer updated must not turn offline by itself
+        for item in items {
+            let ext_id = item.id.ext_id();
+
+            let result = if item.stale {
+                tracing::info!("No update of {} since {}", ext_id, item.last_seen);
+                self.repo
+                    .update_device_availability(
+                        &ext_id.to_string(),
+                        STALENESS_SOURCE,
+                        &item.last_seen,
+                        true,
+                        &item.offline_after,
+                    )
+                    .await
+            } else {
+                self.repo
+                    .remove_device_availability(&ext_id.to_string(), STALENESS_SOURCE)
+                    .await
+            };
+
+            if let Err(e) = result {
+                tracing::error!("Error updating staleness of {}: {:?}", ext_id, e);
+                continue;
+            }
+
+            self.staleness().reported(&item);
+        }
+    }
+
+    fn staleness(&self) -> MutexGuard<'_, StalenessTracker> {
+        lock_ignoring_poison(&self.staleness)
+    }
+
+    fn filter(&self) -> MutexGuard<'_, PlausibilityFilter> {
+        lock_ignoring_poison(&self.filter)
+    }
+
     pub async fn handle_availability_update(&self, avail: DeviceAvailability) {
         match self
             .repo
-            .update_device_availability(&avail.device_id, &avail.source, &avail.last_seen, avail.marked_offline)
+            .update_device_availability(
+                &avail.device_id,
+                &avail.source,
+                &avail.last_seen,
+                avail.marked_offline,
+                &t!(1 hours),
+            )
             .await
         {
             Ok(_) => {
@@ -124,4 +247,16 @@ impl DeviceStateService {
     pub async fn get_offline_items(&self) -> anyhow::Result<Vec<OfflineItem>> {
         self.repo.get_offline_items().await
     }
+
+    pub async fn get_retention_reports(&self) -> anyhow::Result<Vec<RetentionReport>> {
+        self.repo.get_retention_reports().await
+    }
+
+    pub fn start_import(&self, format: ImportFormat, file: PathBuf) -> u64 {
+        self.imports.start(self.repo.clone(), format, file)
+    }
+
+    pub fn get_import(&self, id: u64) -> Option<ImportProgress> {
+        self.imports.get(id)
+    }
 }
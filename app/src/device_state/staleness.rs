@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::{
+    core::{
+        id::ExternalId,
+        time::{DateTime, Duration},
+    },
+    device_state::DeviceStateId,
+};
+
+/// Number of missed reports after which an item is considered offline
+const MISSED_REPORTS: i64 = 3;
+
+/// Items of periodically reporting Zigbee and Tasmota devices are checked by default, items that only report on
+/// change (e.g. Home Assistant entities) are not
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StalenessConfig {
+    /// Additional or overriding expected report intervals per item
+    #[serde(default)]
+    pub intervals: Vec<ReportInterval>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReportInterval {
+    /// External id as `type/name`, e.g. `temperature/living_room`
+    #[serde(deserialize_with = "device_state_id")]
+    pub item: DeviceStateId,
+    /// ISO 8601, e.g. `PT10M`
+    pub interval: Duration,
+}
+
+fn device_state_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DeviceStateId, D::Error> {
+    let path = String::deserialize(deserializer)?;
+
+    ExternalId::from_slash_separated(&path)
+        .and_then(DeviceStateId::try_from)
+        .map_err(serde::de::Error::custom)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemStaleness {
+    pub id: DeviceStateId,
+    pub last_seen: DateTime,
+    pub offline_after: Duration,
+    pub stale: bool,
+}
+
+pub struct StalenessTracker {
+    offline_after: HashMap<DeviceStateId, Duration>,
+    last_seen: HashMap<DeviceStateId, DateTime>,
+    reported: HashMap<DeviceStateId, bool>,
+}
+
+impl StalenessTracker {
+    /// `periodic` are the expected report intervals of the adapters, the config overrides them
+    pub fn new(periodic: Vec<(DeviceStateId, Duration)>, config: &StalenessConfig) -> Self {
+        let intervals = periodic
+            .into_iter()
+            .chain(config.intervals.iter().map(|i| (i.item, i.interval.clone())));
+
+        Self {
+            offline_after: intervals
+                .map(|(id, interval)| (id, Duration::seconds(interval.as_secs() * MISSED_REPORTS)))
+                .collect(),
+            last_seen: HashMap::new(),
+            reported: HashMap::new(),
+        }
+    }
+
+    /// Only items with an interval are tracked
+    pub fn record(&mut self, id: DeviceStateId, at: DateTime) {
+        if self.offline_after.contains_key(&id) {
+            self.last_seen.insert(id, at);
+        }
+    }
+
+    /// Items whose staleness changed since it was last reported, all items on the first check
+    pub fn check(&self, now: DateTime) -> Vec<ItemStaleness> {
+        self.last_seen
+            .iter()
+            .filter_map(|(id, last_seen)| {
+                let offline_after = self.offline_after.get(id)?.clone();
+                let stale = now.elapsed_since(*last_seen) > offline_after;
+
+                if self.reported.get(id) == Some(&stale) {
+                    return None;
+                }
+
+                Some(ItemStaleness {
+                    id: *id,
+                    last_seen: *last_seen,
+                    stale,
+                    offline_after,
+                })
+            })
+            .collect()
+    }
+
+    pub fn reported(&mut self, item: &ItemStaleness) {
+        self.reported.insert(item.id, item.stale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        device_state::{
+            CurrentPowerUsage, Opened, RelativeHumidity, Temperature,
+            adapter::{tasmota, z2m},
+        },
+        t,
+    };
+
+    #[test]
+    fn items_become_stale_after_missed_reports() {
+        let periodic = vec![
+            (DeviceStateId::Temperature(Temperature::LivingRoom), t!(10 minutes)),
+            (DeviceStateId::CurrentPowerUsage(CurrentPowerUsage::Dehumidifier), t!(5 minutes)),
+        ];
+        let mut tracker = StalenessTracker::new(periodic, &StalenessConfig::default());
+        tracker.record(DeviceStateId::Temperature(Temperature::LivingRoom), t!(20 minutes ago));
+        tracker.record(
+            DeviceStateId::CurrentPowerUsage(CurrentPowerUsage::Dehumidifier),
+            t!(20 minutes ago),
+        );
+        tracker.record(DeviceStateId::Opened(Opened::KitchenWindow), t!(5 hours ago));
+
+        let mut result = tracker.check(t!(now));
+        result.sort_by_key(|r| r.stale);
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].id, DeviceStateId::Temperature(Temperature::LivingRoom));
+        assert!(!result[0].stale);
+        assert_eq!(
+            result[1].id,
+            DeviceStateId::CurrentPowerUsage(CurrentPowerUsage::Dehumidifier)
+        );
+        assert!(result[1].stale);
+        assert_eq!(result[1].offline_after, t!(15 minutes));
+
+        //only changes are reported again
+        result.iter().for_each(|item| tracker.reported(item));
+        assert!(tracker.check(t!(now)).is_empty());
+
+        tracker.record(DeviceStateId::CurrentPowerUsage(CurrentPowerUsage::Dehumidifier), t!(now));
+        let result = tracker.check(t!(now));
+        assert_eq!(result.len(), 1);
+        assert!(!result[0].stale);
+    }
+
+    #[test]
+    fn only_periodic_sensors_are_tracked_by_default() -> anyhow::Result<()> {
+        let periodic = z2m::periodic_states()
+            .into_iter()
+            .chain(tasmota::periodic_states())
+            .collect();
+        let config: StalenessConfig =
+            serde_json::from_str(r#"{"intervals": [{"item": "temperature/outside", "interval": "PT1H"}]}"#)?;
+        let mut tracker = StalenessTracker::new(periodic, &config);
+
+        let tracked = [
+            DeviceStateId::Temperature(Temperature::LivingRoom),
+            DeviceStateId::RelativeHumidity(RelativeHumidity::LivingRoom),
+            DeviceStateId::CurrentPowerUsage(CurrentPowerUsage::Fridge),
+            DeviceStateId::Temperature(Temperature::Outside),
+        ];
+        let untracked = [
+            DeviceStateId::Temperature(Temperature::LivingRoomTado),
+            DeviceStateId::RelativeHumidity(RelativeHumidity::Outside),
+            DeviceStateId::RelativeHumidity(RelativeHumidity::BedroomTado),
+        ];
+        for id in tracked.iter().chain(untracked.iter()) {
+            tracker.record(*id, t!(now));
+        }
+
+        let mut result = tracker.check(t!(now)).into_iter().map(|item| item.id).collect::<Vec<_>>();
+        result.sort_by_key(|id| id.ext_id().to_string());
+        let mut expected = tracked.to_vec();
+        expected.sort_by_key(|id| id.ext_id().to_string());
+        assert_eq!(result, expected);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+//! End-to-end tests driving home state → automation → command, with all commands sent to a
+//! `RecordingCommandExecutor` instead of MQTT, Home Assistant or Nuki.
+
+use infrastructure::{EventBus, EventListener};
+use sqlx::PgPool;
+
+use crate::{
+    automation::AutomationModule,
+    command::{
+        Command, CommandEvent, CommandExecution, CommandModule, CommandState, ExecutorOutcome, PowerToggle,
+        RecordingCommandExecutor,
+    },
+    core::{id::ExternalId, time::DateTimeRange, timeseries::DataPoint},
+    device_state::{DeviceStateClient, DeviceStateValue, PowerAvailable},
+    home_state::{HomeStateEvent, HomeStateModule, StateSnapshot},
+    t,
+    trigger::{OnOffDevice, TriggerModule, UserTrigger},
+};
+
+struct TestHome {
+    executor: RecordingCommandExecutor,
+    command_module: CommandModule,
+    command_events: EventListener<CommandEvent>,
+    home_state_bus: EventBus<HomeStateEvent>,
+    home_state_module: HomeStateModule,
+    device_state: DeviceStateClient,
+    trigger_module: TriggerModule,
+    pool: PgPool,
+}
+
+impl TestHome {
+    fn new(pool: PgPool) -> anyhow::Result<Self> {
+        let executor = RecordingCommandExecutor::new();
+        let command_bus = EventBus::new(16);
+        let command_events = command_bus.subscribe();
+        let home_state_bus = EventBus::new(16);
+
+        let trigger_module = TriggerModule::new(pool.clone());
+        let device_state_bus = EventBus::new(16);
+        let device_state = DeviceStateClient::with_pool(pool.clone(), &device_state_bus);
+        let home_state_module = HomeStateModule::new(
+            t!(1 hours),
+            device_state_bus.subscribe(),
+            trigger_module.subscribe(),
+            trigger_module.client(),
+            device_state.clone(),
+        );
+
+        let command_module = CommandModule::with_recording_executor(
+            command_bus,
+            pool.clone(),
+            executor.clone(),
+            home_state_bus.subscribe(),
+        )?;
+
+        Ok(Self {
+            executor,
+            command_module,
+            command_events,
+            home_state_bus,
+            home_state_module,
+            device_state,
+            trigger_module,
+            pool,
+        })
+    }
+
+    async fn current_snapshot(&self) -> anyhow::Result<StateSnapshot> {
+        let mut snapshots = self
+            .home_state_module
+            .client()
+            .snapshot_iter(DateTimeRange::new(t!(1 minutes ago), t!(now)));
+
+        let mut latest = None;
+        while let Some(snapshot) = snapshots.next().await? {
+            latest = Some(snapshot);
+        }
+
+        latest.ok_or_else(|| anyhow::anyhow!("No snapshot calculated"))
+    }
+
+    async fn next_executed_command(&mut self) -> anyhow::Result<CommandExecution> {
+        let event = tokio::time::timeout(std::time::Duration::from_secs(10), self.command_events.recv()).await?;
+
+        match event {
+            Some(CommandEvent::CommandExecuted(execution)) => Ok(execution),
+            None => anyhow::bail!("Command event channel closed"),
+        }
+    }
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn user_trigger_is_planned_and_sent_to_executor(pool: PgPool) -> anyhow::Result<()> {
+    let mut home = TestHome::new(pool)?;
+
+    home.device_state
+        .receive(DataPoint::new(
+            DeviceStateValue::PowerAvailable(PowerAvailable::Dehumidifier, false),
+            t!(10 minutes ago),
+        ))
+        .await;
+    home.trigger_module
+        .client()
+        .add_trigger(UserTrigger::DevicePower {
+            device: OnOffDevice::Dehumidifier,
+            on: true,
+        })
+        .await?;
+
+    let automation = AutomationModule::new(
+        home.home_state_bus.subscribe(),
+        home.command_module.client(),
+        home.trigger_module.client(),
+        home.home_state_module.client(),
+        home.pool.clone(),
+    )
+    .await?;
+    let automation = tokio::spawn(automation.run());
+
+    let snapshot = home.current_snapshot().await?;
+    home.home_state_bus
+        .emitter()
+        .send(HomeStateEvent::SnapshotUpdated(snapshot));
+
+    let expected = Command::SetPower {
+        device: PowerToggle::Dehumidifier,
+        power_on: true,
+    };
+
+    let execution = loop {
+        let execution = home.next_executed_command().await?;
+        if execution.command == expected {
+            break execution;
+        }
+    };
+    automation.abort();
+
+    assert!(matches!(execution.state, CommandState::Success));
+    assert!(home.executor.recorded().contains(&expected));
+
+    Ok(())
+}
+
+#[sqlx::test(migrations = "../migrations")]
+async fn scripted_executor_outcomes_end_up_in_command_state(pool: PgPool) -> anyhow::Result<()> {
+    let mut home = TestHome::new(pool)?;
+    let client = home.command_module.client();
+    let command = Command::SetPower {
+        device: PowerToggle::InfraredHeater,
+        power_on: false,
+    };
+
+    home.executor
+        .script(ExecutorOutcome::Error("device rejected".to_string()));
+    home.executor
+        .script(ExecutorOutcome::TransientError("connection refused".to_string()));
+    home.executor
+        .set_default_outcome(ExecutorOutcome::Timeout(std::time::Duration::from_millis(10)));
+
+    let failed = client
+        .execute(command.clone(), ExternalId::new("test", "failed"), None)
+        .await?;
+    assert!(matches!(&failed.state, CommandState::Error(e) if e == "device rejected"));
+    assert_eq!(failed.next_retry_at, None);
+
+    let retried = client
+        .execute(command.clone(), ExternalId::new("test", "retried"), None)
+        .await?;
+    assert!(matches!(retried.state, CommandState::Error(_)));
+    assert!(retried.next_retry_at.is_some());
+
+    let timed_out = client
+        .execute(command.clone(), ExternalId::new("test", "timed_out"), None)
+        .await?;
+    assert!(matches!(&timed_out.state, CommandState::Error(e) if e.starts_with("Timeout")));
+
+    home.executor.set_default_outcome(ExecutorOutcome::Success);
+    let succeeded = client
+        .execute(command.clone(), ExternalId::new("test", "succeeded"), None)
+        .await?;
+    assert!(matches!(succeeded.state, CommandState::Success));
+    assert_eq!(home.next_executed_command().await?.id, failed.id);
+
+    assert_eq!(home.executor.recorded(), vec![command; 4]);
+
+    Ok(())
+}
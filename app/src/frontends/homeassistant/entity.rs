@@ -0,0 +1,193 @@
+use crate::{
+    core::math::round_to_one_decimal,
+    home_state::{AdjustmentDirection, HeatingMode, HomeStateId, HomeStateValue},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum HaComponent {
+    Sensor,
+    BinarySensor,
+}
+
+impl HaComponent {
+    pub(super) fn as_str(&self) -> &'static str {
+        match self {
+            HaComponent::Sensor => "sensor",
+            HaComponent::BinarySensor => "binary_sensor",
+        }
+    }
+}
+
+/// Home state item (or one part of it, like the bounds of a range) as HA MQTT discovery entity
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct HaEntity {
+    pub(super) unique_id: String,
+    /// Relative to the configured base topic
+    pub(super) state_topic: String,
+    pub(super) name: String,
+    pub(super) component: HaComponent,
+    pub(super) device_class: Option<&'static str>,
+    pub(super) unit: Option<&'static str>,
+    /// Possible states of an enum sensor
+    pub(super) options: Vec<&'static str>,
+    pub(super) state: String,
+}
+
+pub(super) fn to_entities(value: &HomeStateValue) -> Vec<HaEntity> {
+    let ext_id = HomeStateId::from(value).ext_id();
+    let type_name = ext_id.type_name();
+    let variant_name = ext_id.variant_name().replace("::", "_");
+
+    let entity = |suffix: &str, component, device_class, unit, options: &[&'static str], state: String| {
+        let name = if suffix.is_empty() {
+            variant_name.clone()
+        } else {
+            format!("{variant_name}_{suffix}")
+        };
+
+        HaEntity {
+            unique_id: format!("{type_name}_{name}"),
+            state_topic: format!("{type_name}/{name}"),
+            name: format!("{type_name} {name}").replace('_', " "),
+            component,
+            device_class,
+            unit,
+            options: options.to_vec(),
+            state,
+        }
+    };
+
+    let sensor = |device_class, unit, value: f64| {
+        vec![entity(
+            "",
+            HaComponent::Sensor,
+            device_class,
+            unit,
+            &[],
+            round_to_one_decimal(value).to_string(),
+        )]
+    };
+    let binary_sensor = |device_class, on: bool| {
+        vec![entity(
+            "",
+            HaComponent::BinarySensor,
+            device_class,
+            None,
+            &[],
+            if on { "ON" } else { "OFF" }.to_string(),
+        )]
+    };
+    let enum_sensor = |options: &[&'static str], state: &str| {
+        vec![entity(
+            "",
+            HaComponent::Sensor,
+            Some("enum"),
+            None,
+            options,
+            state.to_string(),
+        )]
+    };
+    let range_sensor = |device_class, unit, min: f64, max: f64| {
+        vec![
+            entity(
+                "min",
+                HaComponent::Sensor,
+                device_class,
+                unit,
+                &[],
+                round_to_one_decimal(min).to_string(),
+            ),
+            entity(
+                "max",
+                HaComponent::Sensor,
+                device_class,
+                unit,
+                &[],
+                round_to_one_decimal(max).to_string(),
+            ),
+        ]
+    };
+
+    match value {
+        HomeStateValue::AbsoluteHumidity(_, v) => sensor(None, Some("g/m³"), v.0),
+        HomeStateValue::AllergenIndex(_, v) => sensor(None, None, f64::from(v)),
+        HomeStateValue::BatteryLevel(_, v) => sensor(Some("battery"), Some("%"), v.0),
+        HomeStateValue::ColdAirComingIn(_, v) => binary_sensor(Some("cold"), *v),
+        HomeStateValue::DewPoint(_, v) => sensor(Some("temperature"), Some("°C"), v.0),
+        HomeStateValue::FeltTemperature(_, v) => sensor(Some("temperature"), Some("°C"), v.0),
+        HomeStateValue::IsRunning(_, v) => binary_sensor(Some("running"), *v),
+        HomeStateValue::LightLevel(_, v) => sensor(Some("illuminance"), Some("lx"), f64::from(v)),
+        //HA lock class is on when unlocked
+        HomeStateValue::Locked(_, v) => binary_sensor(Some("lock"), !*v),
+        HomeStateValue::Occupancy(_, v) => sensor(None, Some("%"), v.factor() * 100.0),
+        HomeStateValue::Opened(_, v) => binary_sensor(Some("opening"), *v),
+        HomeStateValue::ParticulateMatter(_, v) => sensor(Some("pm25"), Some("µg/m³"), v.0),
+        HomeStateValue::Resident(_, v) => binary_sensor(None, *v),
+        HomeStateValue::RiskOfMould(_, v) => binary_sensor(Some("problem"), *v),
+        HomeStateValue::Ventilation(_, v) => binary_sensor(None, *v),
+        HomeStateValue::TargetHeatingAdjustment(_, v) => {
+            let options = AdjustmentDirection::all().map(|direction| direction.as_str());
+            enum_sensor(&options, v.as_str())
+        }
+        HomeStateValue::TargetHeatingMode(_, v) => enum_sensor(&HeatingMode::all_names(), v.as_str()),
+        HomeStateValue::TargetHeatingDemand(_, v) => sensor(None, Some("%"), v.0),
+        HomeStateValue::EnergySaving(_, v) => binary_sensor(None, *v),
+        HomeStateValue::FanActivity(_, v) => enum_sensor(&["off", "low", "medium", "high"], &v.to_string()),
+        HomeStateValue::HeatingDemand(_, v) => sensor(None, Some("%"), v.0),
+        HomeStateValue::HeatingDemandLimit(_, v) => range_sensor(None, Some("%"), v.from().0, v.to().0),
+        HomeStateValue::PowerAvailable(_, v) => binary_sensor(Some("power"), *v),
+        HomeStateValue::Presence(_, v) => binary_sensor(Some("occupancy"), *v),
+        HomeStateValue::RelativeHumidity(_, v) => sensor(Some("humidity"), Some("%"), v.0),
+        HomeStateValue::SetPoint(_, v) => range_sensor(Some("temperature"), Some("°C"), v.from().0, v.to().0),
+        HomeStateValue::Temperature(_, v) => sensor(Some("temperature"), Some("°C"), v.0),
+        HomeStateValue::TemperatureChange(_, v) => sensor(None, Some("°C/h"), v.per_hour().0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::domain::HeatingZone;
+    use crate::core::unit::p;
+    use crate::home_state::{Occupancy, Resident, RiskOfMould, TargetHeatingMode};
+
+    #[test]
+    fn numeric_value_becomes_sensor_with_unit() {
+        let entities = to_entities(&HomeStateValue::Occupancy(Occupancy::LivingRoomCouch, p(0.456)));
+
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].component, HaComponent::Sensor);
+        assert_eq!(entities[0].unit, Some("%"));
+        assert_eq!(entities[0].state, "45.6");
+    }
+
+    #[test]
+    fn bool_value_becomes_binary_sensor() {
+        let mould = to_entities(&HomeStateValue::RiskOfMould(RiskOfMould::Bathroom, true));
+        let sleeping = to_entities(&HomeStateValue::Resident(Resident::AnyoneSleeping, false));
+
+        assert_eq!(mould[0].component, HaComponent::BinarySensor);
+        assert_eq!(mould[0].device_class, Some("problem"));
+        assert_eq!(mould[0].state, "ON");
+        assert_eq!(sleeping[0].unique_id, "resident_anyone_sleeping");
+        assert_eq!(sleeping[0].state_topic, "resident/anyone_sleeping");
+        assert_eq!(sleeping[0].name, "resident anyone sleeping");
+        assert_eq!(sleeping[0].state, "OFF");
+    }
+
+    #[test]
+    fn heating_mode_becomes_enum_sensor() {
+        let entities = to_entities(&HomeStateValue::TargetHeatingMode(
+            TargetHeatingMode::HeatingZone(HeatingZone::Bedroom),
+            HeatingMode::Sleep,
+        ));
+
+        assert_eq!(entities[0].device_class, Some("enum"));
+        assert_eq!(
+            entities[0].options,
+            vec!["energy_saving", "comfort", "sleep", "ventilation", "away", "manual"]
+        );
+        assert_eq!(entities[0].state, "sleep");
+        assert_eq!(entities[0].unit, None);
+    }
+}
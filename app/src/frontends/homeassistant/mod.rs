@@ -0,0 +1,145 @@
+mod entity;
+
+use std::collections::HashSet;
+
+use infrastructure::{EventListener, MqttSender};
+use serde::Deserialize;
+use serde_json::json;
+
+use self::entity::{HaComponent, HaEntity};
+use crate::{
+    Infrastructure,
+    home_state::{HomeStateEvent, HomeStateValue},
+};
+
+/// Publishes derived home state to Home Assistant as MQTT discovery sensors
+#[derive(Clone, Deserialize, Debug)]
+pub struct HaDiscovery {
+    #[serde(default = "default_discovery_prefix")]
+    pub discovery_prefix: String,
+    /// Topic the states are published to
+    pub base_topic: String,
+}
+
+fn default_discovery_prefix() -> String {
+    "homeassistant".to_string()
+}
+
+impl HaDiscovery {
+    pub fn new_runner(
+        &self,
+        infrastructure: &Infrastructure,
+        state_change_rx: EventListener<HomeStateEvent>,
+    ) -> HaDiscoveryRunner {
+        HaDiscoveryRunner {
+            state_change_rx,
+            discovery_sender: infrastructure.mqtt_client.sender(&self.discovery_prefix),
+            state_sender: infrastructure.mqtt_client.sender(&self.base_topic),
+            base_topic: self.base_topic.trim_matches('/').to_string(),
+            announced: HashSet::new(),
+        }
+    }
+}
+
+pub struct HaDiscoveryRunner {
+    state_change_rx: EventListener<HomeStateEvent>,
+    discovery_sender: MqttSender,
+    state_sender: MqttSender,
+    base_topic: String,
+    announced: HashSet<String>,
+}
+
+impl HaDiscoveryRunner {
+    pub async fn run(mut self) {
+        loop {
+            if let Some(HomeStateEvent::Changed(dp)) = self.state_change_rx.recv().await {
+                self.handle_state_change(&dp.value).await;
+            }
+        }
+    }
+
+    async fn handle_state_change(&mut self, value: &HomeStateValue) {
+        for entity in entity::to_entities(value) {
+            //Config is retained, so announcing once per run is enough to survive HA restarts
+            if !self.announced.contains(&entity.unique_id) {
+                if let Err(e) = self.announce(&entity).await {
+                    tracing::error!("Error announcing {} to Home Assistant: {:?}", entity.unique_id, e);
+                    continue;
+                }
+
+                self.announced.insert(entity.unique_id.clone());
+            }
+
+            if let Err(e) = self.state_sender.send_retained(&entity.state_topic, entity.state).await {
+                tracing::error!(
+                    "Error publishing state of {} to Home Assistant: {:?}",
+                    entity.unique_id,
+                    e
+                );
+            }
+        }
+    }
+
+    async fn announce(&self, entity: &HaEntity) -> anyhow::Result<()> {
+        let topic = format!("{}/rusty_home/{}/config", entity.component.as_str(), entity.unique_id);
+        let payload = discovery_config(entity, &self.base_topic);
+
+        self.discovery_sender.send_retained(topic, payload.to_string()).await
+    }
+}
+
+fn discovery_config(entity: &HaEntity, base_topic: &str) -> serde_json::Value {
+    let mut config = json!({
+        "name": entity.name,
+        "unique_id": format!("rusty_home_{}", entity.unique_id),
+        "state_topic": format!("{}/{}", base_topic, entity.state_topic),
+        "device": {
+            "identifiers": ["rusty_home"],
+            "name": "Rusty Home",
+        },
+    });
+
+    if let Some(device_class) = entity.device_class {
+        config["device_class"] = json!(device_class);
+    }
+
+    if let Some(unit) = entity.unit {
+        config["unit_of_measurement"] = json!(unit);
+    }
+
+    if !entity.options.is_empty() {
+        config["options"] = json!(entity.options);
+    } else if entity.component == HaComponent::Sensor {
+        config["state_class"] = json!("measurement");
+    }
+
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::unit::DegreeCelsius;
+    use crate::home_state::DewPoint;
+
+    #[test]
+    fn discovery_config_of_numeric_sensor() {
+        let entities = entity::to_entities(&HomeStateValue::DewPoint(DewPoint::Outside, DegreeCelsius(12.34)));
+
+        let config = discovery_config(&entities[0], "rusty_home/home_state");
+
+        assert_eq!(
+            config,
+            json!({
+                "name": "dew point outside",
+                "unique_id": "rusty_home_dew_point_outside",
+                "state_topic": "rusty_home/home_state/dew_point/outside",
+                "device": {"identifiers": ["rusty_home"], "name": "Rusty Home"},
+                "device_class": "temperature",
+                "unit_of_measurement": "°C",
+                "state_class": "measurement",
+            })
+        );
+        assert_eq!(entities[0].state, "12.3");
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{
+    core::unit::{AllergenIndexValue, MicrogramsPerCubicMeter},
+    frontends::homekit::{HomekitCharacteristic, HomekitEvent, HomekitService, HomekitTarget, HomekitTargetConfig},
+    home_state::{AllergenIndex, HomeStateValue, ParticulateMatter},
+    trigger::UserTrigger,
+};
+
+// AirQuality: 0 = unknown, 1 = excellent, 2 = good, 3 = fair, 4 = inferior, 5 = poor
+pub struct AirQualitySensor {
+    name: String,
+    particulate_matter: ParticulateMatter,
+    allergen_index: AllergenIndex,
+    pm25_quality: Option<u8>,
+    allergen_quality: Option<u8>,
+}
+
+impl AirQualitySensor {
+    pub fn new(name: String, particulate_matter: ParticulateMatter, allergen_index: AllergenIndex) -> Self {
+        Self {
+            name,
+            particulate_matter,
+            allergen_index,
+            pm25_quality: None,
+            allergen_quality: None,
+        }
+    }
+
+    pub fn get_all_targets(&self) -> Vec<HomekitTargetConfig> {
+        vec![
+            self.target(HomekitCharacteristic::AirQuality).into_config(),
+            self.target(HomekitCharacteristic::Pm25Density).into_config(),
+        ]
+    }
+
+    pub fn export_state(&mut self, state: &HomeStateValue) -> Vec<HomekitEvent> {
+        match state {
+            HomeStateValue::ParticulateMatter(pm, value) if *pm == self.particulate_matter => {
+                self.pm25_quality = Some(pm25_air_quality(value));
+
+                vec![
+                    self.event(HomekitCharacteristic::Pm25Density, serde_json::json!(value.0)),
+                    self.air_quality_event(),
+                ]
+            }
+            HomeStateValue::AllergenIndex(index, value) if *index == self.allergen_index => {
+                self.allergen_quality = Some(allergen_air_quality(value));
+                vec![self.air_quality_event()]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn process_trigger(&self, _trigger: &HomekitEvent) -> Option<UserTrigger> {
+        None
+    }
+
+    //Worst of both readings
+    fn air_quality_event(&self) -> HomekitEvent {
+        let quality = self.pm25_quality.max(self.allergen_quality).unwrap_or(0);
+        self.event(HomekitCharacteristic::AirQuality, serde_json::json!(quality))
+    }
+
+    fn target(&self, characteristic: HomekitCharacteristic) -> HomekitTarget {
+        HomekitTarget::new(self.name.to_string(), HomekitService::AirQualitySensor, characteristic)
+    }
+
+    fn event(&self, characteristic: HomekitCharacteristic, value: serde_json::Value) -> HomekitEvent {
+        HomekitEvent {
+            target: self.target(characteristic),
+            value,
+        }
+    }
+}
+
+//Based on the EU annual limit (25) and WHO guideline (15) for PM2.5
+fn pm25_air_quality(value: &MicrogramsPerCubicMeter) -> u8 {
+    match value.0 {
+        v if v <= 5.0 => 1,
+        v if v <= 15.0 => 2,
+        v if v <= 25.0 => 3,
+        v if v <= 50.0 => 4,
+        _ => 5,
+    }
+}
+
+//Philips indoor allergen index: 1-3 good, 4-6 fair, 7-9 poor, 10-12 very poor
+fn allergen_air_quality(value: &AllergenIndexValue) -> u8 {
+    match value.0 {
+        ..=1 => 1,
+        2..=3 => 2,
+        4..=6 => 3,
+        7..=9 => 4,
+        _ => 5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn worst_reading_determines_air_quality() {
+        let mut sensor = AirQualitySensor::new(
+            "test".to_string(),
+            ParticulateMatter::LivingRoomPM25,
+            AllergenIndex::LivingRoom,
+        );
+
+        let events = sensor.export_state(&HomeStateValue::ParticulateMatter(
+            ParticulateMatter::LivingRoomPM25,
+            MicrogramsPerCubicMeter(12.0),
+        ));
+        assert_eq!(events[1].value, serde_json::json!(2));
+
+        let events = sensor.export_state(&HomeStateValue::AllergenIndex(
+            AllergenIndex::LivingRoom,
+            AllergenIndexValue(8),
+        ));
+        assert_eq!(events[0].target.characteristic, HomekitCharacteristic::AirQuality);
+        assert_eq!(events[0].value, serde_json::json!(4));
+    }
+
+    #[test]
+    fn pm25_scale() {
+        assert_eq!(pm25_air_quality(&MicrogramsPerCubicMeter(3.0)), 1);
+        assert_eq!(pm25_air_quality(&MicrogramsPerCubicMeter(25.0)), 3);
+        assert_eq!(pm25_air_quality(&MicrogramsPerCubicMeter(80.0)), 5);
+    }
+}
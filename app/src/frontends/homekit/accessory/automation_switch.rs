@@ -0,0 +1,55 @@
+use crate::{
+    automation::{AutomationSwitchTarget, AutomationSwitches},
+    command::CommandTarget,
+    frontends::homekit::{HomekitCharacteristic, HomekitEvent, HomekitService, HomekitTarget, HomekitTargetConfig},
+};
+
+/// Switch to enable or disable all automatic control of a resource
+pub struct AutomationSwitch {
+    name: String,
+    target: AutomationSwitchTarget,
+}
+
+impl AutomationSwitch {
+    pub fn new(name: String, resource: CommandTarget) -> Self {
+        Self {
+            name,
+            target: AutomationSwitchTarget::from(&resource),
+        }
+    }
+
+    pub fn target(&self) -> &AutomationSwitchTarget {
+        &self.target
+    }
+
+    pub fn get_all_targets(&self) -> Vec<HomekitTargetConfig> {
+        vec![self.homekit_target().into_config()]
+    }
+
+    pub fn export_switches(&self, switches: &AutomationSwitches) -> Vec<HomekitEvent> {
+        vec![HomekitEvent {
+            target: self.homekit_target(),
+            value: serde_json::json!(switches.is_enabled(&self.target)),
+        }]
+    }
+
+    pub fn process_toggle(&self, trigger: &HomekitEvent) -> Option<(AutomationSwitchTarget, bool)> {
+        if trigger.target == self.homekit_target() {
+            if let Some(enabled) = trigger.value.as_bool() {
+                return Some((self.target.clone(), enabled));
+            }
+
+            tracing::warn!(
+                "AutomationSwitch {} received invalid payload: {}",
+                self.name,
+                trigger.value
+            );
+        }
+
+        None
+    }
+
+    fn homekit_target(&self) -> HomekitTarget {
+        HomekitTarget::new(self.name.to_string(), HomekitService::Switch, HomekitCharacteristic::On)
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{
+    frontends::homekit::{HomekitCharacteristic, HomekitEvent, HomekitService, HomekitTarget, HomekitTargetConfig},
+    home_state::{BatteryLevel, HomeStateValue, LOW_BATTERY_LEVEL},
+    trigger::UserTrigger,
+};
+
+/// Battery service, added to the accessory of the battery-powered device by using the same name
+pub struct Battery {
+    name: String,
+    battery_level: BatteryLevel,
+}
+
+impl Battery {
+    pub fn new(name: String, battery_level: BatteryLevel) -> Self {
+        Self { name, battery_level }
+    }
+
+    pub fn get_all_targets(&self) -> Vec<HomekitTargetConfig> {
+        vec![
+            self.target(HomekitCharacteristic::BatteryLevel).into_config(),
+            self.target(HomekitCharacteristic::StatusLowBattery).into_config(),
+        ]
+    }
+
+    pub fn export_state(&self, state: &HomeStateValue) -> Vec<HomekitEvent> {
+        match state {
+            // StatusLowBattery: 0 = normal, 1 = low
+            HomeStateValue::BatteryLevel(battery_level, percent) if *battery_level == self.battery_level => {
+                let low = *percent < LOW_BATTERY_LEVEL;
+
+                vec![
+                    self.event(
+                        HomekitCharacteristic::BatteryLevel,
+                        serde_json::json!(percent.0.clamp(0.0, 100.0).round() as i64),
+                    ),
+                    self.event(
+                        HomekitCharacteristic::StatusLowBattery,
+                        serde_json::json!(if low { 1 } else { 0 }),
+                    ),
+                ]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn process_trigger(&self, _trigger: &HomekitEvent) -> Option<UserTrigger> {
+        None
+    }
+
+    fn target(&self, characteristic: HomekitCharacteristic) -> HomekitTarget {
+        HomekitTarget::new(self.name.to_string(), HomekitService::BatteryService, characteristic)
+    }
+
+    fn event(&self, characteristic: HomekitCharacteristic, value: serde_json::Value) -> HomekitEvent {
+        HomekitEvent {
+            target: self.target(characteristic),
+            value,
+        }
+    }
+}
@@ -5,13 +5,13 @@ use crate::{
 };
 
 pub struct ClimateSensor {
-    name: &'static str,
+    name: String,
     temperature: Temperature,
     humidity: RelativeHumidity,
 }
 
 impl ClimateSensor {
-    pub fn new(name: &'static str, temperature: Temperature, humidity: RelativeHumidity) -> Self {
+    pub fn new(name: String, temperature: Temperature, humidity: RelativeHumidity) -> Self {
         Self {
             name,
             temperature,
@@ -0,0 +1,285 @@
+use anyhow::Context as _;
+use serde::Deserialize;
+
+use crate::{
+    command::{CommandTarget, PowerToggle},
+    core::{domain::HeatingZone, id::ExternalId},
+    frontends::homekit::accessory::{
+        HomekitAccessory, air_quality_sensor::AirQualitySensor, automation_switch::AutomationSwitch, battery::Battery,
+        climate_sensor::ClimateSensor, door_lock::DoorLock, energy_saving_switch::EnergySavingSwitch, fan::Fan,
+        light_sensor::LightSensor, occupancy_sensor::OccupancySensor, power_switch::PowerSwitch,
+        scene_switch::SceneSwitch, smart_lock::SmartLock, thermostat::Thermostat, thermostat::ThermostatBindings,
+        window_sensor::WindowSensor,
+    },
+    home_state::HomeStateId,
+    trigger::{Door, Scene},
+};
+
+const DEFAULT_ACCESSORIES: &str = include_str!("default_accessories.toml");
+
+/// Accessory entry of the `homebridge.accessories` config section.
+/// Home state ids are given as `type/name`, e.g. `temperature/room::living_room`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HomekitAccessoryConfig {
+    AirQualitySensor {
+        name: String,
+        particulate_matter: String,
+        allergen_index: String,
+    },
+    AutomationSwitch {
+        name: String,
+        target: CommandTarget,
+    },
+    Battery {
+        name: String,
+        battery_level: String,
+    },
+    ClimateSensor {
+        name: String,
+        temperature: String,
+        humidity: String,
+    },
+    DoorLock {
+        name: String,
+        door: Door,
+    },
+    EnergySavingSwitch {
+        name: String,
+        energy_saving: String,
+    },
+    Fan {
+        name: String,
+        fan_activity: String,
+    },
+    LightSensor {
+        name: String,
+        light_level: String,
+    },
+    OccupancySensor {
+        name: String,
+        occupancy: String,
+    },
+    PowerSwitch {
+        name: String,
+        device: PowerToggle,
+    },
+    SceneSwitch {
+        name: String,
+        scene: Scene,
+    },
+    SmartLock {
+        name: String,
+        door: Door,
+        locked: String,
+    },
+    /// Bindings default to the room temperature and the first radiator of the zone
+    Thermostat {
+        name: String,
+        zone: HeatingZone,
+        temperature: Option<String>,
+        set_point: Option<String>,
+        heating_mode: Option<String>,
+        heating_demand: Option<String>,
+    },
+    WindowSensor {
+        name: String,
+        opened: String,
+    },
+}
+
+#[derive(Deserialize)]
+struct DefaultAccessories {
+    accessories: Vec<HomekitAccessoryConfig>,
+}
+
+pub(super) fn default_accessories() -> anyhow::Result<Vec<HomekitAccessoryConfig>> {
+    let defaults: DefaultAccessories = config::Config::builder()
+        .add_source(config::File::from_str(DEFAULT_ACCESSORIES, config::FileFormat::Toml))
+        .build()?
+        .try_deserialize()?;
+
+    Ok(defaults.accessories)
+}
+
+pub(super) fn to_accessories(config: &[HomekitAccessoryConfig]) -> anyhow::Result<Vec<HomekitAccessory>> {
+    config
+        .iter()
+        .map(|entry| to_accessory(entry).with_context(|| format!("Invalid HomeKit accessory {}", entry.name())))
+        .collect()
+}
+
+impl HomekitAccessoryConfig {
+    fn name(&self) -> &str {
+        match self {
+            HomekitAccessoryConfig::AirQualitySensor { name, .. }
+            | HomekitAccessoryConfig::AutomationSwitch { name, .. }
+            | HomekitAccessoryConfig::Battery { name, .. }
+            | HomekitAccessoryConfig::ClimateSensor { name, .. }
+            | HomekitAccessoryConfig::DoorLock { name, .. }
+            | HomekitAccessoryConfig::EnergySavingSwitch { name, .. }
+            | HomekitAccessoryConfig::Fan { name, .. }
+            | HomekitAccessoryConfig::LightSensor { name, .. }
+            | HomekitAccessoryConfig::OccupancySensor { name, .. }
+            | HomekitAccessoryConfig::PowerSwitch { name, .. }
+            | HomekitAccessoryConfig::SceneSwitch { name, .. }
+            | HomekitAccessoryConfig::SmartLock { name, .. }
+            | HomekitAccessoryConfig::Thermostat { name, .. }
+            | HomekitAccessoryConfig::WindowSensor { name, .. } => name,
+        }
+    }
+}
+
+fn to_accessory(config: &HomekitAccessoryConfig) -> anyhow::Result<HomekitAccessory> {
+    let accessory = match config.clone() {
+        HomekitAccessoryConfig::AirQualitySensor {
+            name,
+            particulate_matter,
+            allergen_index,
+        } => HomekitAccessory::AirQualitySensor(AirQualitySensor::new(
+            name,
+            state_id(&particulate_matter, |id| match id {
+                HomeStateId::ParticulateMatter(id) => Some(id),
+                _ => None,
+            })?,
+            state_id(&allergen_index, |id| match id {
+                HomeStateId::AllergenIndex(id) => Some(id),
+                _ => None,
+            })?,
+        )),
+        HomekitAccessoryConfig::AutomationSwitch { name, target } => {
+            HomekitAccessory::AutomationSwitch(AutomationSwitch::new(name, target))
+        }
+        HomekitAccessoryConfig::Battery { name, battery_level } => HomekitAccessory::Battery(Battery::new(
+            name,
+            state_id(&battery_level, |id| match id {
+                HomeStateId::BatteryLevel(id) => Some(id),
+                _ => None,
+            })?,
+        )),
+        HomekitAccessoryConfig::ClimateSensor {
+            name,
+            temperature,
+            humidity,
+        } => HomekitAccessory::ClimateSensor(ClimateSensor::new(
+            name,
+            state_id(&temperature, |id| match id {
+                HomeStateId::Temperature(id) => Some(id),
+                _ => None,
+            })?,
+            state_id(&humidity, |id| match id {
+                HomeStateId::RelativeHumidity(id) => Some(id),
+                _ => None,
+            })?,
+        )),
+        HomekitAccessoryConfig::DoorLock { name, door } => HomekitAccessory::DoorLock(DoorLock::new(name, door)),
+        HomekitAccessoryConfig::EnergySavingSwitch { name, energy_saving } => {
+            HomekitAccessory::EnergySavingSwitch(EnergySavingSwitch::new(
+                name,
+                state_id(&energy_saving, |id| match id {
+                    HomeStateId::EnergySaving(id) => Some(id),
+                    _ => None,
+                })?,
+            ))
+        }
+        HomekitAccessoryConfig::Fan { name, fan_activity } => HomekitAccessory::Fan(Fan::new(
+            name,
+            state_id(&fan_activity, |id| match id {
+                HomeStateId::FanActivity(id) => Some(id),
+                _ => None,
+            })?,
+        )),
+        HomekitAccessoryConfig::LightSensor { name, light_level } => HomekitAccessory::LightSensor(LightSensor::new(
+            name,
+            state_id(&light_level, |id| match id {
+                HomeStateId::LightLevel(id) => Some(id),
+                _ => None,
+            })?,
+        )),
+        HomekitAccessoryConfig::OccupancySensor { name, occupancy } => {
+            HomekitAccessory::OccupancySensor(OccupancySensor::new(
+                name,
+                state_id(&occupancy, |id| match id {
+                    HomeStateId::Occupancy(id) => Some(id),
+                    _ => None,
+                })?,
+            ))
+        }
+        HomekitAccessoryConfig::PowerSwitch { name, device } => {
+            HomekitAccessory::PowerSwitch(PowerSwitch::new(name, device))
+        }
+        HomekitAccessoryConfig::SceneSwitch { name, scene } => {
+            HomekitAccessory::SceneSwitch(SceneSwitch::new(name, scene))
+        }
+        HomekitAccessoryConfig::SmartLock { name, door, locked } => HomekitAccessory::SmartLock(SmartLock::new(
+            name,
+            door,
+            state_id(&locked, |id| match id {
+                HomeStateId::Locked(id) => Some(id),
+                _ => None,
+            })?,
+        )),
+        HomekitAccessoryConfig::Thermostat {
+            name,
+            zone,
+            temperature,
+            set_point,
+            heating_mode,
+            heating_demand,
+        } => {
+            let defaults = ThermostatBindings::for_zone(zone);
+            let bindings = ThermostatBindings {
+                temperature: optional_state_id(temperature, defaults.temperature, |id| match id {
+                    HomeStateId::Temperature(id) => Some(id),
+                    _ => None,
+                })?,
+                set_point: optional_state_id(set_point, defaults.set_point, |id| match id {
+                    HomeStateId::SetPoint(id) => Some(id),
+                    _ => None,
+                })?,
+                target_heating_mode: optional_state_id(heating_mode, defaults.target_heating_mode, |id| match id {
+                    HomeStateId::TargetHeatingMode(id) => Some(id),
+                    _ => None,
+                })?,
+                heating_demand: optional_state_id(heating_demand, defaults.heating_demand, |id| match id {
+                    HomeStateId::HeatingDemand(id) => Some(id),
+                    _ => None,
+                })?,
+            };
+
+            HomekitAccessory::Thermostat(Thermostat::new(name, zone, bindings))
+        }
+        HomekitAccessoryConfig::WindowSensor { name, opened } => HomekitAccessory::WindowSensor(WindowSensor::new(
+            name,
+            state_id(&opened, |id| match id {
+                HomeStateId::Opened(id) => Some(id),
+                _ => None,
+            })?,
+        )),
+    };
+
+    Ok(accessory)
+}
+
+//Resolves a `type/name` id to a known home state and narrows it to the type the accessory expects
+fn state_id<T>(path: &str, narrow: impl FnOnce(HomeStateId) -> Option<T>) -> anyhow::Result<T> {
+    let ext_id = ExternalId::from_slash_separated(path)?;
+    let id = HomeStateId::try_from(&ext_id)?;
+
+    if !HomeStateId::variants().contains(&id) {
+        anyhow::bail!("Home state {} is not available", path);
+    }
+
+    narrow(id).with_context(|| format!("Home state {} has the wrong type for this accessory", path))
+}
+
+fn optional_state_id<T>(
+    path: Option<String>,
+    default: T,
+    narrow: impl FnOnce(HomeStateId) -> Option<T>,
+) -> anyhow::Result<T> {
+    match path {
+        Some(path) => state_id(&path, narrow),
+        None => Ok(default),
+    }
+}
@@ -4,13 +4,13 @@ use crate::{
 };
 
 pub struct DoorLock {
-    name: &'static str,
+    name: String,
     door: Door,
     pending_reset: bool,
 }
 
 impl DoorLock {
-    pub fn new(name: &'static str, door: Door) -> Self {
+    pub fn new(name: String, door: Door) -> Self {
         // pending_reset starts true so the first home state event corrects any
         // persisted or default "open" state in homebridge-mqtt on startup
         Self {
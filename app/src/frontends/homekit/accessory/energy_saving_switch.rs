@@ -5,12 +5,12 @@ use crate::{
 };
 
 pub struct EnergySavingSwitch {
-    name: &'static str,
+    name: String,
     target: EnergySaving,
 }
 
 impl EnergySavingSwitch {
-    pub fn new(name: &'static str, target: EnergySaving) -> Self {
+    pub fn new(name: String, target: EnergySaving) -> Self {
         Self { name, target }
     }
 
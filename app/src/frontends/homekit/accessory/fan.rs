@@ -52,13 +52,13 @@ impl FanStatus {
 }
 
 pub struct Fan {
-    name: &'static str,
+    name: String,
     activity: FanActivity,
     status: FanStatus,
 }
 
 impl Fan {
-    pub fn new(name: &'static str, activity: FanActivity) -> Self {
+    pub fn new(name: String, activity: FanActivity) -> Self {
         Self {
             name,
             activity,
@@ -0,0 +1,47 @@
+use crate::{
+    frontends::homekit::{HomekitCharacteristic, HomekitEvent, HomekitService, HomekitTarget, HomekitTargetConfig},
+    home_state::{HomeStateValue, LightLevel},
+    trigger::UserTrigger,
+};
+
+//HomeKit rejects values below its minimum of 0.0001 lux
+const MIN_LUX: f64 = 0.0001;
+
+pub struct LightSensor {
+    name: String,
+    light_level: LightLevel,
+}
+
+impl LightSensor {
+    pub fn new(name: String, light_level: LightLevel) -> Self {
+        Self { name, light_level }
+    }
+
+    pub fn get_all_targets(&self) -> Vec<HomekitTargetConfig> {
+        vec![self.target().into_config()]
+    }
+
+    pub fn export_state(&self, state: &HomeStateValue) -> Vec<HomekitEvent> {
+        match state {
+            HomeStateValue::LightLevel(light_level, lux) if *light_level == self.light_level => {
+                vec![HomekitEvent {
+                    target: self.target(),
+                    value: serde_json::json!(f64::from(lux).max(MIN_LUX)),
+                }]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn process_trigger(&self, _trigger: &HomekitEvent) -> Option<UserTrigger> {
+        None
+    }
+
+    fn target(&self) -> HomekitTarget {
+        HomekitTarget::new(
+            self.name.to_string(),
+            HomekitService::LightSensor,
+            HomekitCharacteristic::CurrentAmbientLightLevel,
+        )
+    }
+}
@@ -1,32 +1,49 @@
-use crate::core::domain::RoomWithWindow;
-use crate::home_state::{EnergySaving, FanActivity, HomeStateValue, Opened, RelativeHumidity, Temperature};
-use crate::trigger::{Door, UserTrigger};
-use crate::{
-    command::PowerToggle,
-    core::domain::{HeatingZone, Room},
-    frontends::homekit::{
-        HomekitEvent, HomekitTargetConfig,
-        accessory::{
-            climate_sensor::ClimateSensor, door_lock::DoorLock, energy_saving_switch::EnergySavingSwitch, fan::Fan,
-            power_switch::PowerSwitch, thermostat::Thermostat, window_sensor::WindowSensor,
-        },
+use std::collections::HashSet;
+
+use crate::automation::{AutomationSwitchTarget, AutomationSwitches, resource_plans};
+use crate::frontends::homekit::{
+    HomekitEvent, HomekitTargetConfig,
+    accessory::{
+        air_quality_sensor::AirQualitySensor, automation_switch::AutomationSwitch, battery::Battery,
+        climate_sensor::ClimateSensor, door_lock::DoorLock, energy_saving_switch::EnergySavingSwitch, fan::Fan,
+        light_sensor::LightSensor, occupancy_sensor::OccupancySensor, power_switch::PowerSwitch,
+        scene_switch::SceneSwitch, smart_lock::SmartLock, thermostat::Thermostat, window_sensor::WindowSensor,
     },
 };
+use crate::home_state::HomeStateValue;
+use crate::trigger::UserTrigger;
+
+pub use config::HomekitAccessoryConfig;
 
+mod air_quality_sensor;
+mod automation_switch;
+mod battery;
 mod climate_sensor;
+mod config;
 mod door_lock;
 mod energy_saving_switch;
 mod fan;
+mod light_sensor;
+mod occupancy_sensor;
 mod power_switch;
+mod scene_switch;
+mod smart_lock;
 mod thermostat;
 mod window_sensor;
 
 enum HomekitAccessory {
+    AirQualitySensor(AirQualitySensor),
+    AutomationSwitch(AutomationSwitch),
+    Battery(Battery),
     ClimateSensor(ClimateSensor),
     DoorLock(DoorLock),
     EnergySavingSwitch(EnergySavingSwitch),
     Fan(Fan),
+    LightSensor(LightSensor),
+    OccupancySensor(OccupancySensor),
     PowerSwitch(PowerSwitch),
+    SceneSwitch(SceneSwitch),
+    SmartLock(SmartLock),
     Thermostat(Thermostat),
     WindowSensor(WindowSensor),
 }
@@ -36,14 +53,59 @@ pub struct HomekitRegistry {
 }
 
 impl HomekitRegistry {
-    fn new(accessories: Vec<HomekitAccessory>) -> Self {
-        Self { accessories }
+    /// Builds the registry from the configured accessories, falling back to the built-in list if none are configured.
+    /// Every resource of the resource plans gets an automation switch, configured entries only override the name.
+    pub fn from_config(config: &[HomekitAccessoryConfig]) -> anyhow::Result<Self> {
+        let mut accessories = if config.is_empty() {
+            config::to_accessories(&config::default_accessories()?)?
+        } else {
+            config::to_accessories(config)?
+        };
+
+        let configured_switches: HashSet<AutomationSwitchTarget> = accessories
+            .iter()
+            .filter_map(|accessory| match accessory {
+                HomekitAccessory::AutomationSwitch(switch) => Some(switch.target().clone()),
+                _ => None,
+            })
+            .collect();
+
+        for (resource, _) in resource_plans() {
+            if !configured_switches.contains(&AutomationSwitchTarget::from(&resource)) {
+                accessories.push(HomekitAccessory::AutomationSwitch(AutomationSwitch::new(
+                    format!("Automatik {}", resource),
+                    resource,
+                )));
+            }
+        }
+
+        let registry = Self { accessories };
+
+        let mut targets = HashSet::new();
+        for target_config in registry.get_device_config() {
+            let target = target_config.target;
+            if !targets.insert(target.clone()) {
+                anyhow::bail!(
+                    "Duplicate HomeKit target {:?}/{:?} of accessory {}",
+                    target.service,
+                    target.characteristic,
+                    target.name
+                );
+            }
+        }
+
+        Ok(registry)
     }
 
     pub fn get_device_config(&self) -> Vec<HomekitTargetConfig> {
         self.accessories
             .iter()
             .flat_map(|accessory| match accessory {
+                HomekitAccessory::AirQualitySensor(sensor) => sensor.get_all_targets(),
+                HomekitAccessory::AutomationSwitch(switch) => switch.get_all_targets(),
+                HomekitAccessory::Battery(battery) => battery.get_all_targets(),
+                HomekitAccessory::LightSensor(sensor) => sensor.get_all_targets(),
+                HomekitAccessory::OccupancySensor(sensor) => sensor.get_all_targets(),
                 HomekitAccessory::ClimateSensor(sensor) => sensor.get_all_targets(),
                 HomekitAccessory::DoorLock(lock) => lock.get_all_targets(),
                 HomekitAccessory::EnergySavingSwitch(switch) => switch.get_all_targets(),
@@ -51,6 +113,8 @@ impl HomekitRegistry {
                 HomekitAccessory::Thermostat(sensor) => sensor.get_all_targets(),
                 HomekitAccessory::WindowSensor(sensor) => sensor.get_all_targets(),
                 HomekitAccessory::PowerSwitch(power_switch) => power_switch.get_all_targets(),
+                HomekitAccessory::SceneSwitch(switch) => switch.get_all_targets(),
+                HomekitAccessory::SmartLock(lock) => lock.get_all_targets(),
             })
             .collect()
     }
@@ -59,6 +123,11 @@ impl HomekitRegistry {
         self.accessories
             .iter_mut()
             .flat_map(|accessory| match accessory {
+                HomekitAccessory::AirQualitySensor(sensor) => sensor.export_state(state),
+                HomekitAccessory::AutomationSwitch(_) => Vec::new(),
+                HomekitAccessory::Battery(battery) => battery.export_state(state),
+                HomekitAccessory::LightSensor(sensor) => sensor.export_state(state),
+                HomekitAccessory::OccupancySensor(sensor) => sensor.export_state(state),
                 HomekitAccessory::ClimateSensor(sensor) => sensor.export_state(state),
                 HomekitAccessory::DoorLock(lock) => lock.export_state(state),
                 HomekitAccessory::EnergySavingSwitch(switch) => switch.export_state(state),
@@ -66,12 +135,19 @@ impl HomekitRegistry {
                 HomekitAccessory::Thermostat(sensor) => sensor.export_state(state),
                 HomekitAccessory::WindowSensor(sensor) => sensor.export_state(state),
                 HomekitAccessory::PowerSwitch(power_switch) => power_switch.export_state(state),
+                HomekitAccessory::SceneSwitch(switch) => switch.export_state(state),
+                HomekitAccessory::SmartLock(lock) => lock.export_state(state),
             })
             .collect()
     }
 
     pub fn process_trigger(&mut self, trigger: &HomekitEvent) -> Option<UserTrigger> {
         self.accessories.iter_mut().find_map(|accessory| match accessory {
+            HomekitAccessory::AirQualitySensor(sensor) => sensor.process_trigger(trigger),
+            HomekitAccessory::AutomationSwitch(_) => None,
+            HomekitAccessory::Battery(battery) => battery.process_trigger(trigger),
+            HomekitAccessory::LightSensor(sensor) => sensor.process_trigger(trigger),
+            HomekitAccessory::OccupancySensor(sensor) => sensor.process_trigger(trigger),
             HomekitAccessory::ClimateSensor(sensor) => sensor.process_trigger(trigger),
             HomekitAccessory::DoorLock(lock) => lock.process_trigger(trigger),
             HomekitAccessory::EnergySavingSwitch(switch) => switch.process_trigger(trigger),
@@ -79,69 +155,124 @@ impl HomekitRegistry {
             HomekitAccessory::Thermostat(sensor) => sensor.process_trigger(trigger),
             HomekitAccessory::WindowSensor(sensor) => sensor.process_trigger(trigger),
             HomekitAccessory::PowerSwitch(power_switch) => power_switch.process_trigger(trigger),
+            HomekitAccessory::SceneSwitch(switch) => switch.process_trigger(trigger),
+            HomekitAccessory::SmartLock(lock) => lock.process_trigger(trigger),
         })
     }
-}
 
-impl Default for HomekitRegistry {
-    fn default() -> Self {
-        Self::new(config())
+    pub fn export_automation_switches(&self, switches: &AutomationSwitches) -> Vec<HomekitEvent> {
+        self.accessories
+            .iter()
+            .flat_map(|accessory| match accessory {
+                HomekitAccessory::AutomationSwitch(switch) => switch.export_switches(switches),
+                _ => Vec::new(),
+            })
+            .collect()
+    }
+
+    pub fn process_automation_toggle(&self, trigger: &HomekitEvent) -> Option<(AutomationSwitchTarget, bool)> {
+        self.accessories.iter().find_map(|accessory| match accessory {
+            HomekitAccessory::AutomationSwitch(switch) => switch.process_toggle(trigger),
+            _ => None,
+        })
     }
 }
 
-fn config() -> Vec<HomekitAccessory> {
-    vec![
-        HomekitAccessory::ClimateSensor(ClimateSensor::new(
-            "Klimasensor Wohnzimmer",
-            Temperature::Room(Room::LivingRoom),
-            RelativeHumidity::Room(Room::LivingRoom),
-        )),
-        HomekitAccessory::ClimateSensor(ClimateSensor::new(
-            "Klimasensor Schlafzimmer",
-            Temperature::Room(Room::Bedroom),
-            RelativeHumidity::Room(Room::Bedroom),
-        )),
-        HomekitAccessory::ClimateSensor(ClimateSensor::new(
-            "Klimasensor Arbeitszimmer",
-            Temperature::Room(Room::RoomOfRequirements),
-            RelativeHumidity::Room(Room::RoomOfRequirements),
-        )),
-        HomekitAccessory::ClimateSensor(ClimateSensor::new(
-            "Klimasensor Küche",
-            Temperature::Room(Room::Kitchen),
-            RelativeHumidity::Room(Room::Kitchen),
-        )),
-        HomekitAccessory::ClimateSensor(ClimateSensor::new(
-            "Klimasensor Bad",
-            Temperature::Room(Room::Bathroom),
-            RelativeHumidity::Room(Room::Bathroom),
-        )),
-        HomekitAccessory::WindowSensor(WindowSensor::new(
-            "Fenstersensor Wohnzimmer",
-            Opened::Room(RoomWithWindow::LivingRoom),
-        )),
-        HomekitAccessory::WindowSensor(WindowSensor::new(
-            "Fenstersensor Schlafzimmer",
-            Opened::Room(RoomWithWindow::Bedroom),
-        )),
-        HomekitAccessory::WindowSensor(WindowSensor::new("Fenstersensor Küche", Opened::Room(RoomWithWindow::Kitchen))),
-        HomekitAccessory::WindowSensor(WindowSensor::new(
-            "Fenstersensor Arbeitszimmer",
-            Opened::Room(RoomWithWindow::RoomOfRequirements),
-        )),
-        HomekitAccessory::Thermostat(Thermostat::new("Thermostat Wohnzimmer", HeatingZone::LivingRoom)),
-        HomekitAccessory::Thermostat(Thermostat::new("Thermostat Schlafzimmer", HeatingZone::Bedroom)),
-        HomekitAccessory::Thermostat(Thermostat::new("Thermostat Arbeitszimmer", HeatingZone::RoomOfRequirements)),
-        HomekitAccessory::Thermostat(Thermostat::new("Thermostat Küche", HeatingZone::Kitchen)),
-        HomekitAccessory::Thermostat(Thermostat::new("Thermostat Bad", HeatingZone::Bathroom)),
-        HomekitAccessory::DoorLock(DoorLock::new("Haustür", Door::Building)),
-        HomekitAccessory::PowerSwitch(PowerSwitch::new("Luftentfeuchter", PowerToggle::Dehumidifier)),
-        HomekitAccessory::PowerSwitch(PowerSwitch::new("Infrarotheizung", PowerToggle::InfraredHeater)),
-        HomekitAccessory::EnergySavingSwitch(EnergySavingSwitch::new(
-            "Wohnzimmer TV Bildqualität",
-            EnergySaving::LivingRoomTv,
-        )),
-        HomekitAccessory::Fan(Fan::new("Entfeuchter Bad", FanActivity::BedroomDehumidifier)),
-        HomekitAccessory::Fan(Fan::new("Luftreiniger Wohnzimmer", FanActivity::LivingRoomAirPurifier)),
-    ]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::domain::{HeatingZone, Radiator};
+    use crate::core::range::Range;
+    use crate::core::unit::DegreeCelsius;
+    use crate::home_state::SetPoint;
+
+    #[test]
+    fn default_accessories_are_valid() -> anyhow::Result<()> {
+        let defaults = config::default_accessories()?;
+        let registry = HomekitRegistry::from_config(&defaults)?;
+
+        //every entry resolves, automation switches are generated on top
+        assert_eq!(registry.accessories.len(), defaults.len() + resource_plans().len());
+
+        let targets = registry.get_device_config();
+        let unique: HashSet<_> = targets.iter().map(|config| &config.target).collect();
+        assert_eq!(unique.len(), targets.len());
+        Ok(())
+    }
+
+    #[test]
+    fn thermostat_bindings_are_configurable() -> anyhow::Result<()> {
+        let thermostat = |heating_demand: &str| HomekitAccessoryConfig::Thermostat {
+            name: "Thermostat Wohnzimmer klein".to_string(),
+            zone: HeatingZone::LivingRoom,
+            temperature: None,
+            set_point: Some("set_point/current::living_room_small".to_string()),
+            heating_mode: None,
+            heating_demand: Some(heating_demand.to_string()),
+        };
+
+        let mut registry = HomekitRegistry::from_config(&[thermostat("heating_demand/radiator::living_room_small")])?;
+        let events = registry.export_state(&HomeStateValue::SetPoint(
+            SetPoint::Current(Radiator::LivingRoomSmall),
+            Range::new(DegreeCelsius(19.0), DegreeCelsius(21.0)),
+        ));
+        assert!(events.iter().any(|event| event.value == serde_json::json!(21.0)));
+
+        assert!(HomekitRegistry::from_config(&[thermostat("temperature/room::living_room")]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn every_resource_has_one_automation_switch() -> anyhow::Result<()> {
+        let renamed = HomekitAccessoryConfig::AutomationSwitch {
+            name: "Automatik Luftentfeuchter".to_string(),
+            target: resource_plans()[0].0.clone(),
+        };
+
+        let registry = HomekitRegistry::from_config(&[renamed])?;
+        let switches: Vec<&AutomationSwitch> = registry
+            .accessories
+            .iter()
+            .filter_map(|accessory| match accessory {
+                HomekitAccessory::AutomationSwitch(switch) => Some(switch),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(switches.len(), resource_plans().len());
+        assert!(
+            switches
+                .iter()
+                .any(|switch| switch.get_all_targets()[0].target.name == "Automatik Luftentfeuchter")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_or_mistyped_home_state_is_rejected() {
+        let unknown = HomekitAccessoryConfig::WindowSensor {
+            name: "Fenstersensor Keller".to_string(),
+            opened: "opened/room::cellar".to_string(),
+        };
+        let mistyped = HomekitAccessoryConfig::WindowSensor {
+            name: "Fenstersensor Küche".to_string(),
+            opened: "temperature/room::kitchen".to_string(),
+        };
+
+        let Err(e) = HomekitRegistry::from_config(&[unknown]) else {
+            panic!("Expected unknown home state to be rejected");
+        };
+        assert!(format!("{:#}", e).contains("Fenstersensor Keller"));
+        assert!(HomekitRegistry::from_config(&[mistyped]).is_err());
+    }
+
+    #[test]
+    fn duplicate_target_is_rejected() {
+        let sensor = HomekitAccessoryConfig::LightSensor {
+            name: "Lichtsensor Küche".to_string(),
+            light_level: "light_level/kitchen".to_string(),
+        };
+
+        assert!(HomekitRegistry::from_config(&[sensor.clone(), sensor]).is_err());
+    }
 }
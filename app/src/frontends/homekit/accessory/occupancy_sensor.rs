@@ -0,0 +1,50 @@
+use crate::{
+    frontends::homekit::{HomekitCharacteristic, HomekitEvent, HomekitService, HomekitTarget, HomekitTargetConfig},
+    home_state::{HomeStateValue, Occupancy},
+    trigger::UserTrigger,
+};
+
+//Same threshold as used for the couch in the air purifier rule
+const OCCUPIED_THRESHOLD: f64 = 0.7;
+
+pub struct OccupancySensor {
+    name: String,
+    occupancy: Occupancy,
+}
+
+impl OccupancySensor {
+    pub fn new(name: String, occupancy: Occupancy) -> Self {
+        Self { name, occupancy }
+    }
+
+    pub fn get_all_targets(&self) -> Vec<HomekitTargetConfig> {
+        vec![self.target().into_config()]
+    }
+
+    pub fn export_state(&self, state: &HomeStateValue) -> Vec<HomekitEvent> {
+        match state {
+            // OccupancyDetected: 0 = not detected, 1 = detected
+            HomeStateValue::Occupancy(occupancy, probability) if *occupancy == self.occupancy => {
+                let detected = probability.factor() > OCCUPIED_THRESHOLD;
+
+                vec![HomekitEvent {
+                    target: self.target(),
+                    value: serde_json::json!(if detected { 1 } else { 0 }),
+                }]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn process_trigger(&self, _trigger: &HomekitEvent) -> Option<UserTrigger> {
+        None
+    }
+
+    fn target(&self) -> HomekitTarget {
+        HomekitTarget::new(
+            self.name.to_string(),
+            HomekitService::OccupancySensor,
+            HomekitCharacteristic::OccupancyDetected,
+        )
+    }
+}
@@ -6,12 +6,12 @@ use crate::{
 };
 
 pub struct PowerSwitch {
-    name: &'static str,
+    name: String,
     power_toggle: PowerToggle,
 }
 
 impl PowerSwitch {
-    pub fn new(name: &'static str, power_toggle: PowerToggle) -> Self {
+    pub fn new(name: String, power_toggle: PowerToggle) -> Self {
         Self { name, power_toggle }
     }
 
@@ -0,0 +1,60 @@
+use crate::{
+    frontends::homekit::{HomekitCharacteristic, HomekitEvent, HomekitService, HomekitTarget, HomekitTargetConfig},
+    trigger::{Scene, UserTrigger},
+};
+
+/// Stateless switch: turning it on activates the scene, afterwards it falls back to off
+pub struct SceneSwitch {
+    name: String,
+    scene: Scene,
+    pending_reset: bool,
+}
+
+impl SceneSwitch {
+    pub fn new(name: String, scene: Scene) -> Self {
+        Self {
+            name,
+            scene,
+            pending_reset: true,
+        }
+    }
+
+    pub fn get_all_targets(&self) -> Vec<HomekitTargetConfig> {
+        vec![self.target().into_config()]
+    }
+
+    pub fn export_state(&mut self, _state: &crate::home_state::HomeStateValue) -> Vec<HomekitEvent> {
+        if self.pending_reset {
+            self.pending_reset = false;
+            return vec![HomekitEvent {
+                target: self.target(),
+                value: serde_json::json!(false),
+            }];
+        }
+        Vec::new()
+    }
+
+    pub fn process_trigger(&mut self, trigger: &HomekitEvent) -> Option<UserTrigger> {
+        if trigger.target != self.target() {
+            return None;
+        }
+
+        match trigger.value.as_bool() {
+            Some(true) => {
+                self.pending_reset = true;
+                Some(UserTrigger::Scene {
+                    scene: self.scene.clone(),
+                })
+            }
+            Some(false) => None,
+            None => {
+                tracing::warn!("SceneSwitch {} received invalid payload: {}", self.name, trigger.value);
+                None
+            }
+        }
+    }
+
+    fn target(&self) -> HomekitTarget {
+        HomekitTarget::new(self.name.to_string(), HomekitService::Switch, HomekitCharacteristic::On)
+    }
+}
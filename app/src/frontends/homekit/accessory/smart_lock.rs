@@ -0,0 +1,75 @@
+use crate::home_state::{HomeStateValue, Locked};
+use crate::{
+    frontends::homekit::{HomekitCharacteristic, HomekitEvent, HomekitService, HomekitTarget, HomekitTargetConfig},
+    trigger::{Door, UserTrigger},
+};
+
+pub struct SmartLock {
+    name: String,
+    door: Door,
+    locked: Locked,
+}
+
+impl SmartLock {
+    pub fn new(name: String, door: Door, locked: Locked) -> Self {
+        Self { name, door, locked }
+    }
+
+    pub fn get_all_targets(&self) -> Vec<HomekitTargetConfig> {
+        vec![
+            self.target(HomekitCharacteristic::LockCurrentState).into_config(),
+            self.target(HomekitCharacteristic::LockTargetState).into_config(),
+        ]
+    }
+
+    pub fn export_state(&self, state: &HomeStateValue) -> Vec<HomekitEvent> {
+        match state {
+            // LockCurrentState and LockTargetState: 0 = unsecured, 1 = secured
+            HomeStateValue::Locked(locked, is_locked) if locked == &self.locked => {
+                let value = serde_json::json!(if *is_locked { 1 } else { 0 });
+                vec![
+                    self.event(HomekitCharacteristic::LockCurrentState, value.clone()),
+                    self.event(HomekitCharacteristic::LockTargetState, value),
+                ]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn process_trigger(&self, trigger: &HomekitEvent) -> Option<UserTrigger> {
+        if trigger.target != self.target(HomekitCharacteristic::LockTargetState) {
+            return None;
+        }
+
+        let value = trigger
+            .value
+            .as_i64()
+            .or_else(|| trigger.value.as_str().and_then(|s| s.parse().ok()));
+
+        match value {
+            Some(v @ (0 | 1)) => Some(UserTrigger::DoorLock {
+                door: self.door.clone(),
+                locked: v == 1,
+            }),
+            _ => {
+                tracing::warn!(
+                    "SmartLock {} received invalid LockTargetState payload: {}",
+                    self.name,
+                    trigger.value
+                );
+                None
+            }
+        }
+    }
+
+    fn target(&self, characteristic: HomekitCharacteristic) -> HomekitTarget {
+        HomekitTarget::new(self.name.to_string(), HomekitService::LockMechanism, characteristic)
+    }
+
+    fn event(&self, characteristic: HomekitCharacteristic, value: serde_json::Value) -> HomekitEvent {
+        HomekitEvent {
+            target: self.target(characteristic),
+            value,
+        }
+    }
+}
@@ -14,7 +14,7 @@ struct ThermostatStatus {
 }
 
 pub struct Thermostat {
-    name: &'static str,
+    name: String,
     zone: HeatingZone,
     temperature: Temperature,
     set_point: SetPoint,
@@ -23,49 +23,44 @@ pub struct Thermostat {
     status: ThermostatStatus,
 }
 
-impl Thermostat {
-    pub fn new(name: &'static str, zone: HeatingZone) -> Self {
-        let (temperature, set_point, target_heating_mode, heating_demand) = match zone {
-            //TODO handle multiple radiators properly
-            HeatingZone::LivingRoom => (
-                zone.room_temperature(),
-                SetPoint::Current(Radiator::LivingRoomBig),
-                TargetHeatingMode::HeatingZone(HeatingZone::LivingRoom),
-                HeatingDemand::Radiator(Radiator::LivingRoomBig),
-            ),
-            HeatingZone::Bedroom => (
-                zone.room_temperature(),
-                SetPoint::Current(Radiator::Bedroom),
-                TargetHeatingMode::HeatingZone(HeatingZone::Bedroom),
-                HeatingDemand::Radiator(Radiator::Bedroom),
-            ),
-            HeatingZone::Kitchen => (
-                zone.room_temperature(),
-                SetPoint::Current(Radiator::Kitchen),
-                TargetHeatingMode::HeatingZone(HeatingZone::Kitchen),
-                HeatingDemand::Radiator(Radiator::Kitchen),
-            ),
-            HeatingZone::RoomOfRequirements => (
-                zone.room_temperature(),
-                SetPoint::Current(Radiator::RoomOfRequirements),
-                TargetHeatingMode::HeatingZone(HeatingZone::RoomOfRequirements),
-                HeatingDemand::Radiator(Radiator::RoomOfRequirements),
-            ),
-            HeatingZone::Bathroom => (
-                zone.room_temperature(),
-                SetPoint::Current(Radiator::Bathroom),
-                TargetHeatingMode::HeatingZone(HeatingZone::Bathroom),
-                HeatingDemand::Radiator(Radiator::Bathroom),
-            ),
+/// Home states shown by a thermostat
+#[derive(Debug, Clone, Copy)]
+pub struct ThermostatBindings {
+    pub temperature: Temperature,
+    pub set_point: SetPoint,
+    pub target_heating_mode: TargetHeatingMode,
+    pub heating_demand: HeatingDemand,
+}
+
+impl ThermostatBindings {
+    /// Room temperature and the first radiator of the zone
+    pub fn for_zone(zone: HeatingZone) -> Self {
+        let radiator = match zone {
+            HeatingZone::LivingRoom => Radiator::LivingRoomBig,
+            HeatingZone::Bedroom => Radiator::Bedroom,
+            HeatingZone::Kitchen => Radiator::Kitchen,
+            HeatingZone::RoomOfRequirements => Radiator::RoomOfRequirements,
+            HeatingZone::Bathroom => Radiator::Bathroom,
         };
 
+        Self {
+            temperature: zone.room_temperature(),
+            set_point: SetPoint::Current(radiator),
+            target_heating_mode: TargetHeatingMode::HeatingZone(zone),
+            heating_demand: HeatingDemand::Radiator(radiator),
+        }
+    }
+}
+
+impl Thermostat {
+    pub fn new(name: String, zone: HeatingZone, bindings: ThermostatBindings) -> Self {
         Self {
             name,
             zone,
-            temperature,
-            set_point,
-            target_heating_mode,
-            heating_demand,
+            temperature: bindings.temperature,
+            set_point: bindings.set_point,
+            target_heating_mode: bindings.target_heating_mode,
+            heating_demand: bindings.heating_demand,
             status: ThermostatStatus::default(),
         }
     }
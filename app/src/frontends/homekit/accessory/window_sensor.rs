@@ -5,12 +5,12 @@ use crate::{
 };
 
 pub struct WindowSensor {
-    name: &'static str,
+    name: String,
     opened_area: Opened,
 }
 
 impl WindowSensor {
-    pub fn new(name: &'static str, opened_area: Opened) -> Self {
+    pub fn new(name: String, opened_area: Opened) -> Self {
         Self { name, opened_area }
     }
 
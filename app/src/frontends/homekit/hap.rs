@@ -3,12 +3,17 @@ use serde::{Deserialize, Serialize};
 // https://github.com/homebridge/HAP-NodeJS/blob/latest/src/lib/definitions/ServiceDefinitions.ts
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum HomekitService {
+    AirQualitySensor,
+    BatteryService,
     ContactSensor,
     Fanv2,
     GarageDoorOpener,
     HumiditySensor,
+    LeakSensor,
     Lightbulb,
+    LightSensor,
     LockMechanism,
+    OccupancySensor,
     TemperatureSensor,
     Switch,
     Thermostat,
@@ -18,17 +23,25 @@ pub enum HomekitService {
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum HomekitCharacteristic {
     Active,
+    AirQuality,
+    BatteryLevel,
     Brightness,
     ContactSensorState,
+    CurrentAmbientLightLevel,
     CurrentDoorState,
     CurrentHeatingCoolingState,
     CurrentRelativeHumidity,
     CurrentTemperature,
+    LeakDetected,
     LockCurrentState,
     LockTargetState,
+    OccupancyDetected,
     On,
+    #[serde(rename = "PM2_5Density")]
+    Pm25Density,
     RotationDirection,
     RotationSpeed,
+    StatusLowBattery,
     TargetDoorState,
     TargetHeatingCoolingState,
     TargetTemperature,
@@ -1,17 +1,20 @@
 mod accessory;
 mod hap;
+mod native;
 mod runtime;
 
+use anyhow::Context as _;
 use infrastructure::EventListener;
 use serde::Deserialize;
 use serde_json::Value;
 
 use self::{
-    accessory::HomekitRegistry,
+    accessory::{HomekitAccessoryConfig, HomekitRegistry},
     hap::{HomekitCharacteristic, HomekitService},
-    runtime::HomekitRunner,
+    native::{HapBridge, HapServerConfig},
+    runtime::{HomekitRunner, HomekitTransport},
 };
-use crate::{Infrastructure, home_state::HomeStateEvent, trigger::TriggerClient};
+use crate::{Infrastructure, automation::AutomationSwitchClient, home_state::HomeStateEvent, trigger::TriggerClient};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct HomekitTarget {
@@ -50,7 +53,7 @@ pub(crate) struct HomekitTargetConfig {
     pub(crate) config: Option<Value>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub(crate) struct HomekitEvent {
     pub(crate) target: HomekitTarget,
     pub(crate) value: serde_json::Value,
@@ -59,28 +62,40 @@ pub(crate) struct HomekitEvent {
 #[derive(Clone, Deserialize, Debug)]
 pub struct Homekit {
     pub base_topic: String,
+    /// Built-in accessory list is used if empty
+    #[serde(default)]
+    pub accessories: Vec<HomekitAccessoryConfig>,
+    /// Serve HomeKit directly instead of via Homebridge
+    #[serde(default)]
+    pub native: Option<HapServerConfig>,
 }
 
 impl Homekit {
-    #[allow(clippy::expect_used)]
     pub async fn new_runner(
         &self,
         infrastructure: &mut Infrastructure,
         trigger_client: TriggerClient,
+        switch_client: AutomationSwitchClient,
         state_change_rx: EventListener<HomeStateEvent>,
-    ) -> HomekitRunner {
-        let mqtt_receiver = infrastructure
-            .mqtt_client
-            .subscribe(&self.base_topic, "from/set")
-            .await
-            .expect("Error subscribing to MQTT topic");
+    ) -> anyhow::Result<HomekitRunner> {
+        let registry = HomekitRegistry::from_config(&self.accessories).context("Invalid HomeKit accessory config")?;
+
+        let transport = match &self.native {
+            Some(config) => HomekitTransport::Native(
+                HapBridge::start(config, infrastructure.db_pool.clone(), registry.get_device_config())
+                    .await
+                    .context("Error starting HAP bridge")?,
+            ),
+            None => HomekitTransport::Homebridge {
+                mqtt_sender: infrastructure.mqtt_client.sender(&self.base_topic),
+                mqtt_receiver: infrastructure
+                    .mqtt_client
+                    .subscribe(&self.base_topic, "from/set")
+                    .await
+                    .context("Error subscribing to MQTT topic")?,
+            },
+        };
 
-        HomekitRunner::new(
-            HomekitRegistry::default(),
-            state_change_rx,
-            infrastructure.mqtt_client.sender(&self.base_topic),
-            mqtt_receiver,
-            trigger_client,
-        )
+        Ok(HomekitRunner::new(registry, state_change_rx, transport, trigger_client, switch_client))
     }
 }
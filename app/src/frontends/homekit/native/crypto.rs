@@ -0,0 +1,148 @@
+use anyhow::Context as _;
+use ring::{
+    aead::{Aad, CHACHA20_POLY1305, LessSafeKey, Nonce, UnboundKey},
+    hkdf,
+};
+
+const TAG_LEN: usize = 16;
+const MAX_FRAME_LEN: usize = 1024;
+
+pub type Key = [u8; 32];
+
+pub fn hkdf_sha512(ikm: &[u8], salt: &str, info: &str) -> anyhow::Result<Key> {
+    let mut key = [0u8; 32];
+
+    hkdf::Salt::new(hkdf::HKDF_SHA512, salt.as_bytes())
+        .extract(ikm)
+        .expand(&[info.as_bytes()], &CHACHA20_POLY1305)
+        .and_then(|okm| okm.fill(&mut key))
+        .map_err(|_| anyhow::anyhow!("Error deriving key for {}", info))?;
+
+    Ok(key)
+}
+
+/// Nonce of the pairing messages, e.g. `PS-Msg05`
+pub fn message_nonce(label: &str) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    for (target, byte) in nonce[4..].iter_mut().zip(label.as_bytes()) {
+        *target = *byte;
+    }
+    nonce
+}
+
+fn counter_nonce(counter: u64) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[4..].copy_from_slice(&counter.to_le_bytes());
+    nonce
+}
+
+pub fn seal(key: &Key, nonce: [u8; 12], aad: &[u8], plaintext: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let key = LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, key).map_err(|_| anyhow::anyhow!("Invalid key"))?);
+
+    let mut data = plaintext.to_vec();
+    key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(aad), &mut data)
+        .map_err(|_| anyhow::anyhow!("Error encrypting data"))?;
+
+    Ok(data)
+}
+
+pub fn open(key: &Key, nonce: [u8; 12], aad: &[u8], ciphertext: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let key = LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, key).map_err(|_| anyhow::anyhow!("Invalid key"))?);
+
+    let mut data = ciphertext.to_vec();
+    let plaintext = key
+        .open_in_place(Nonce::assume_unique_for_key(nonce), Aad::from(aad), &mut data)
+        .map_err(|_| anyhow::anyhow!("Error decrypting data, authentication failed"))?;
+
+    Ok(plaintext.to_vec())
+}
+
+/// Encryption of a verified session: frames of at most 1024 bytes, prefixed with their
+/// little-endian length as additional authenticated data, with a nonce counter per direction.
+pub struct SessionCipher {
+    read_key: Key,
+    write_key: Key,
+    read_counter: u64,
+    write_counter: u64,
+}
+
+impl SessionCipher {
+    pub fn new(shared_secret: &[u8]) -> anyhow::Result<Self> {
+        Ok(Self {
+            read_key: hkdf_sha512(shared_secret, "Control-Salt", "Control-Write-Encryption-Key")?,
+            write_key: hkdf_sha512(shared_secret, "Control-Salt", "Control-Read-Encryption-Key")?,
+            read_counter: 0,
+            write_counter: 0,
+        })
+    }
+
+    pub fn encrypt(&mut self, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let mut out = Vec::with_capacity(data.len() + (data.len() / MAX_FRAME_LEN + 1) * (2 + TAG_LEN));
+
+        for frame in data.chunks(MAX_FRAME_LEN) {
+            let len = (frame.len() as u16).to_le_bytes();
+            let sealed = seal(&self.write_key, counter_nonce(self.write_counter), &len, frame)?;
+            self.write_counter += 1;
+
+            out.extend_from_slice(&len);
+            out.extend_from_slice(&sealed);
+        }
+
+        Ok(out)
+    }
+
+    /// Decrypts all complete frames at the start of `buffer` and removes them from it
+    pub fn decrypt(&mut self, buffer: &mut Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        let mut pos = 0;
+
+        while let Some(&[lo, hi]) = buffer.get(pos..pos + 2) {
+            let len = u16::from_le_bytes([lo, hi]) as usize;
+            if len > MAX_FRAME_LEN {
+                anyhow::bail!("Encrypted frame too long: {}", len);
+            }
+
+            let Some(sealed) = buffer.get(pos + 2..pos + 2 + len + TAG_LEN) else {
+                break;
+            };
+
+            let plaintext = open(&self.read_key, counter_nonce(self.read_counter), &[lo, hi], sealed)
+                .with_context(|| format!("Error decrypting frame {}", self.read_counter))?;
+            self.read_counter += 1;
+
+            out.extend_from_slice(&plaintext);
+            pos += 2 + len + TAG_LEN;
+        }
+
+        buffer.drain(..pos);
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_frames_roundtrip_across_partial_reads() -> anyhow::Result<()> {
+        let mut accessory = SessionCipher::new(b"shared")?;
+        //controller reads with the accessory's write key and vice versa
+        let mut controller = SessionCipher::new(b"shared")?;
+        std::mem::swap(&mut controller.read_key, &mut controller.write_key);
+
+        let message = vec![b'x'; 1500];
+        let encrypted = accessory.encrypt(&message)?;
+        assert_eq!(encrypted.len(), 1500 + 2 * (2 + TAG_LEN));
+
+        let mut buffer = encrypted[..1100].to_vec();
+        let first = controller.decrypt(&mut buffer)?;
+        assert_eq!(first.len(), 1024);
+
+        buffer.extend_from_slice(&encrypted[1100..]);
+        let second = controller.decrypt(&mut buffer)?;
+        assert_eq!([first, second].concat(), message);
+        assert!(buffer.is_empty());
+
+        Ok(())
+    }
+}
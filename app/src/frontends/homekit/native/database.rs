@@ -0,0 +1,410 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::{Value, json};
+
+use super::definitions::{self, ACCESSORY_INFORMATION, CharacteristicMeta, PROTOCOL_INFORMATION};
+use crate::frontends::homekit::{HomekitEvent, HomekitService, HomekitTarget, HomekitTargetConfig};
+
+pub const BRIDGE_AID: u64 = 1;
+
+// HAP status codes of characteristic reads and writes
+pub const STATUS_SUCCESS: i64 = 0;
+pub const STATUS_NOTIFICATION_NOT_SUPPORTED: i64 = -70404;
+pub const STATUS_READ_ONLY: i64 = -70405;
+pub const STATUS_WRITE_ONLY: i64 = -70406;
+pub const STATUS_NOT_FOUND: i64 = -70409;
+pub const STATUS_INVALID_VALUE: i64 = -70410;
+
+pub type CharacteristicId = (u64, u64);
+
+type ServiceTargets = (HomekitService, Vec<HomekitTargetConfig>);
+
+struct Characteristic {
+    meta: CharacteristicMeta,
+    target: Option<HomekitTarget>,
+    /// Value constraints from the accessory config, in HAP JSON naming
+    extra: serde_json::Map<String, Value>,
+    value: Value,
+}
+
+struct Service {
+    iid: u64,
+    uuid: &'static str,
+    characteristics: Vec<u64>,
+}
+
+struct Accessory {
+    aid: u64,
+    services: Vec<Service>,
+}
+
+/// Accessory database of the bridge, generated from the HomeKit registry targets
+pub struct AccessoryDatabase {
+    accessories: Vec<Accessory>,
+    characteristics: HashMap<CharacteristicId, Characteristic>,
+    by_target: HashMap<HomekitTarget, CharacteristicId>,
+    config_number: u32,
+}
+
+impl AccessoryDatabase {
+    pub fn new(bridge_name: &str, targets: Vec<HomekitTargetConfig>) -> Self {
+        let mut db = Self {
+            accessories: Vec::new(),
+            characteristics: HashMap::new(),
+            by_target: HashMap::new(),
+            config_number: 1,
+        };
+
+        let mut bridge = Accessory {
+            aid: BRIDGE_AID,
+            services: Vec::new(),
+        };
+        let mut next_iid = 1;
+        db.add_information_service(&mut bridge, &mut next_iid, bridge_name);
+        db.add_protocol_information_service(&mut bridge, &mut next_iid);
+        db.accessories.push(bridge);
+
+        //group targets by accessory name and service, keeping the registry order
+        let mut grouped: Vec<(String, Vec<ServiceTargets>)> = Vec::new();
+        for target_config in targets {
+            let name = target_config.target.name.clone();
+            let service = target_config.target.service.clone();
+
+            let accessory_index = match grouped.iter().position(|(n, _)| *n == name) {
+                Some(index) => index,
+                None => {
+                    grouped.push((name, Vec::new()));
+                    grouped.len() - 1
+                }
+            };
+            let services = &mut grouped[accessory_index].1;
+
+            match services.iter_mut().find(|(s, _)| *s == service) {
+                Some((_, configs)) => configs.push(target_config),
+                None => services.push((service, vec![target_config])),
+            }
+        }
+
+        for (name, services) in grouped {
+            let aid = db.free_aid(&name);
+            let mut accessory = Accessory {
+                aid,
+                services: Vec::new(),
+            };
+            let mut next_iid = 1;
+            db.add_information_service(&mut accessory, &mut next_iid, &name);
+
+            for (service, configs) in services {
+                let service_iid = next_iid;
+                next_iid += 1;
+
+                let mut characteristic_iids = Vec::new();
+                for target_config in configs {
+                    let iid = next_iid;
+                    next_iid += 1;
+
+                    let meta = definitions::characteristic_meta(&target_config.target.characteristic);
+                    let (extra, value) = match target_config.config {
+                        Some(Value::Object(config)) => (hap_constraints(config), meta.initial_value()),
+                        Some(value) => (serde_json::Map::new(), meta.format.coerce(&value).unwrap_or(value)),
+                        None => (serde_json::Map::new(), meta.initial_value()),
+                    };
+
+                    db.by_target.insert(target_config.target.clone(), (aid, iid));
+                    db.characteristics.insert(
+                        (aid, iid),
+                        Characteristic {
+                            meta,
+                            target: Some(target_config.target),
+                            extra,
+                            value,
+                        },
+                    );
+                    characteristic_iids.push(iid);
+                }
+
+                accessory.services.push(Service {
+                    iid: service_iid,
+                    uuid: definitions::service_uuid(&service),
+                    characteristics: characteristic_iids,
+                });
+            }
+
+            db.accessories.push(accessory);
+        }
+
+        db.config_number = config_number_of(&db.to_json(false).to_string());
+        db
+    }
+
+    pub fn config_number(&self) -> u32 {
+        self.config_number
+    }
+
+    /// Full database for `GET /accessories`
+    pub fn to_json(&self, with_values: bool) -> Value {
+        let accessories: Vec<Value> = self
+            .accessories
+            .iter()
+            .map(|accessory| {
+                let services: Vec<Value> = accessory
+                    .services
+                    .iter()
+                    .map(|service| {
+                        let characteristics: Vec<Value> = service
+                            .characteristics
+                            .iter()
+                            .filter_map(|iid| self.characteristic_json((accessory.aid, *iid), true, with_values))
+                            .collect();
+
+                        json!({
+                            "iid": service.iid,
+                            "type": service.uuid,
+                            "characteristics": characteristics,
+                        })
+                    })
+                    .collect();
+
+                json!({ "aid": accessory.aid, "services": services })
+            })
+            .collect();
+
+        json!({ "accessories": accessories })
+    }
+
+    /// Characteristic without its `aid`, optionally with its metadata
+    pub fn characteristic_json(&self, id: CharacteristicId, with_meta: bool, with_value: bool) -> Option<Value> {
+        let characteristic = self.characteristics.get(&id)?;
+
+        let mut json = if with_meta {
+            let mut json = characteristic.meta.to_json();
+            json.extend(characteristic.extra.clone());
+            json
+        } else {
+            serde_json::Map::new()
+        };
+
+        json.insert("iid".to_string(), json!(id.1));
+
+        if with_value && characteristic.meta.perms.readable() {
+            json.insert("value".to_string(), characteristic.value.clone());
+        }
+
+        Some(Value::Object(json))
+    }
+
+    pub fn read(&self, id: CharacteristicId) -> Result<Value, i64> {
+        match self.characteristics.get(&id) {
+            None => Err(STATUS_NOT_FOUND),
+            Some(c) if !c.meta.perms.readable() => Err(STATUS_WRITE_ONLY),
+            Some(c) => Ok(c.value.clone()),
+        }
+    }
+
+    pub fn supports_events(&self, id: CharacteristicId) -> Result<(), i64> {
+        match self.characteristics.get(&id) {
+            None => Err(STATUS_NOT_FOUND),
+            Some(c) if !c.meta.perms.notifies() => Err(STATUS_NOTIFICATION_NOT_SUPPORTED),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Stores a value written by a controller and returns it as event of the registry target.
+    /// `None` is returned for the accessory information, e.g. identify.
+    pub fn write(&mut self, id: CharacteristicId, value: &Value) -> Result<Option<HomekitEvent>, i64> {
+        let Some(characteristic) = self.characteristics.get_mut(&id) else {
+            return Err(STATUS_NOT_FOUND);
+        };
+
+        if !characteristic.meta.perms.writable() {
+            return Err(STATUS_READ_ONLY);
+        }
+
+        let value = characteristic.meta.format.coerce(value).ok_or(STATUS_INVALID_VALUE)?;
+        if characteristic.meta.perms.readable() {
+            characteristic.value = value.clone();
+        }
+
+        Ok(characteristic
+            .target
+            .clone()
+            .map(|target| HomekitEvent { target, value }))
+    }
+
+    /// Applies a state exported by the registry. Returns the characteristic if its value changed.
+    pub fn update(&mut self, target: &HomekitTarget, value: &Value) -> Option<(CharacteristicId, Value)> {
+        let id = *self.by_target.get(target)?;
+        let characteristic = self.characteristics.get_mut(&id)?;
+        let value = characteristic.meta.format.coerce(value)?;
+
+        if characteristic.value == value {
+            return None;
+        }
+
+        characteristic.value = value.clone();
+        Some((id, value))
+    }
+
+    fn add_information_service(&mut self, accessory: &mut Accessory, next_iid: &mut u64, name: &str) {
+        let serial = format!("{:08X}", accessory.aid);
+        let fields: [(CharacteristicMeta, Value); 6] = [
+            (CharacteristicMeta::identify(), Value::Null),
+            (CharacteristicMeta::string("20"), json!("rusty-home")),
+            (CharacteristicMeta::string("21"), json!("Rusty Home")),
+            (CharacteristicMeta::string("23"), json!(name)),
+            (CharacteristicMeta::string("30"), json!(serial)),
+            (CharacteristicMeta::string("52"), json!(env!("CARGO_PKG_VERSION"))),
+        ];
+
+        let service_iid = *next_iid;
+        *next_iid += 1;
+
+        let mut iids = Vec::new();
+        for (meta, value) in fields {
+            let iid = *next_iid;
+            *next_iid += 1;
+
+            self.insert_info_characteristic((accessory.aid, iid), meta, value);
+            iids.push(iid);
+        }
+
+        accessory.services.push(Service {
+            iid: service_iid,
+            uuid: ACCESSORY_INFORMATION,
+            characteristics: iids,
+        });
+    }
+
+    fn add_protocol_information_service(&mut self, accessory: &mut Accessory, next_iid: &mut u64) {
+        let service_iid = *next_iid;
+        let iid = service_iid + 1;
+        *next_iid = iid + 1;
+
+        //Version characteristic
+        self.insert_info_characteristic((accessory.aid, iid), CharacteristicMeta::string("37"), json!("1.1.0"));
+        accessory.services.push(Service {
+            iid: service_iid,
+            uuid: PROTOCOL_INFORMATION,
+            characteristics: vec![iid],
+        });
+    }
+
+    fn insert_info_characteristic(&mut self, id: CharacteristicId, meta: CharacteristicMeta, value: Value) {
+        self.characteristics.insert(
+            id,
+            Characteristic {
+                meta,
+                target: None,
+                extra: serde_json::Map::new(),
+                value,
+            },
+        );
+    }
+
+    //Accessory ids are derived from the name, so that they stay stable when the config is reordered
+    fn free_aid(&self, name: &str) -> u64 {
+        let mut aid = fnv1a(name.as_bytes()) % 0xFFFF_FFF0 + 2;
+        while self.accessories.iter().any(|a| a.aid == aid) {
+            aid += 1;
+        }
+        aid
+    }
+}
+
+//Constraint names in homebridge-mqtt style (HAP-NodeJS props) mapped to the HAP JSON names
+fn hap_constraints(config: serde_json::Map<String, Value>) -> serde_json::Map<String, Value> {
+    let renamed: BTreeMap<String, Value> = config
+        .into_iter()
+        .map(|(key, value)| {
+            let key = match key.as_str() {
+                "validValues" => "valid-values".to_string(),
+                "validValueRanges" => "valid-values-range".to_string(),
+                _ => key,
+            };
+            (key, value)
+        })
+        .collect();
+
+    renamed.into_iter().collect()
+}
+
+//Changes whenever the database layout changes, so that controllers reload it
+fn config_number_of(layout: &str) -> u32 {
+    (fnv1a(layout.as_bytes()) % 65535) as u32 + 1
+}
+
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontends::homekit::HomekitCharacteristic;
+
+    fn targets() -> Vec<HomekitTargetConfig> {
+        let target = |characteristic| {
+            HomekitTarget::new("Thermostat Bad".to_string(), HomekitService::Thermostat, characteristic)
+        };
+
+        vec![
+            target(HomekitCharacteristic::CurrentTemperature).into_config(),
+            target(HomekitCharacteristic::TargetHeatingCoolingState).with_config(json!({ "validValues": [0, 1, 3] })),
+            HomekitTarget::new(
+                "Haustür".to_string(),
+                HomekitService::GarageDoorOpener,
+                HomekitCharacteristic::TargetDoorState,
+            )
+            .with_config(json!(1)),
+        ]
+    }
+
+    #[test]
+    fn targets_are_grouped_into_bridged_accessories() {
+        let db = AccessoryDatabase::new("Rusty Home", targets());
+        let json = db.to_json(true);
+        let accessories = json["accessories"].as_array().cloned().unwrap_or_default();
+
+        assert_eq!(accessories.len(), 3);
+        assert_eq!(accessories[0]["aid"], json!(BRIDGE_AID));
+
+        let thermostat = &accessories[1]["services"][1];
+        assert_eq!(thermostat["type"], json!("4A"));
+        assert_eq!(thermostat["characteristics"][0]["format"], json!("float"));
+        assert_eq!(thermostat["characteristics"][1]["valid-values"], json!([0, 1, 3]));
+
+        let door = &accessories[2]["services"][1]["characteristics"][0];
+        assert_eq!(door["value"], json!(1));
+        assert_eq!(door["perms"], json!(["pr", "pw", "ev"]));
+    }
+
+    #[test]
+    fn exports_and_writes_are_mapped_to_targets() {
+        let mut db = AccessoryDatabase::new("Rusty Home", targets());
+        let target = HomekitTarget::new(
+            "Thermostat Bad".to_string(),
+            HomekitService::Thermostat,
+            HomekitCharacteristic::TargetHeatingCoolingState,
+        );
+
+        let Some((id, value)) = db.update(&target, &json!(true)) else {
+            panic!("Expected changed value");
+        };
+        assert_eq!(value, json!(1));
+        assert!(db.update(&target, &json!(1)).is_none());
+
+        assert_eq!(
+            db.write(id, &json!(3)),
+            Ok(Some(HomekitEvent {
+                target,
+                value: json!(3)
+            }))
+        );
+        assert_eq!(db.read(id), Ok(json!(3)));
+        assert_eq!(db.write((id.0, id.1 - 1), &json!(20.0)), Err(STATUS_READ_ONLY));
+        assert_eq!(db.write((BRIDGE_AID, 2), &json!(true)), Ok(None));
+        assert_eq!(db.read((BRIDGE_AID, 2)), Err(STATUS_WRITE_ONLY));
+    }
+}
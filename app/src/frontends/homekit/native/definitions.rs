@@ -0,0 +1,226 @@
+use serde_json::{Value, json};
+
+use crate::frontends::homekit::{HomekitCharacteristic, HomekitService};
+
+// Short form of the Apple defined UUIDs, see HAP-NodeJS ServiceDefinitions.ts and CharacteristicDefinitions.ts
+
+pub const ACCESSORY_INFORMATION: &str = "3E";
+pub const PROTOCOL_INFORMATION: &str = "A2";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Bool,
+    Uint8,
+    Int,
+    Float,
+    String,
+}
+
+impl Format {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Format::Bool => "bool",
+            Format::Uint8 => "uint8",
+            Format::Int => "int",
+            Format::Float => "float",
+            Format::String => "string",
+        }
+    }
+
+    /// Brings values from HomeKit or the accessories into the declared format, e.g. 1 for a bool
+    pub fn coerce(&self, value: &Value) -> Option<Value> {
+        match self {
+            Format::Bool => value
+                .as_bool()
+                .or_else(|| value.as_f64().map(|v| v != 0.0))
+                .map(Value::from),
+            Format::Uint8 | Format::Int => value
+                .as_i64()
+                .or_else(|| value.as_f64().map(|v| v.round() as i64))
+                .or_else(|| value.as_bool().map(i64::from))
+                .map(Value::from),
+            Format::Float => value
+                .as_f64()
+                .or_else(|| value.as_bool().map(|v| if v { 1.0 } else { 0.0 }))
+                .map(Value::from),
+            Format::String => value.as_str().map(Value::from),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Perms {
+    Read,
+    ReadWrite,
+    WriteOnly,
+}
+
+impl Perms {
+    pub fn readable(&self) -> bool {
+        *self != Perms::WriteOnly
+    }
+
+    pub fn writable(&self) -> bool {
+        *self != Perms::Read
+    }
+
+    pub fn notifies(&self) -> bool {
+        *self != Perms::WriteOnly
+    }
+
+    fn to_json(self) -> Value {
+        match self {
+            Perms::Read => json!(["pr", "ev"]),
+            Perms::ReadWrite => json!(["pr", "pw", "ev"]),
+            Perms::WriteOnly => json!(["pw"]),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CharacteristicMeta {
+    pub uuid: &'static str,
+    pub format: Format,
+    pub perms: Perms,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub step: Option<f64>,
+    pub unit: Option<&'static str>,
+}
+
+impl CharacteristicMeta {
+    const fn new(uuid: &'static str, format: Format, perms: Perms) -> Self {
+        Self {
+            uuid,
+            format,
+            perms,
+            min: None,
+            max: None,
+            step: None,
+            unit: None,
+        }
+    }
+
+    const fn range(mut self, min: f64, max: f64, step: f64) -> Self {
+        self.min = Some(min);
+        self.max = Some(max);
+        self.step = Some(step);
+        self
+    }
+
+    const fn unit(mut self, unit: &'static str) -> Self {
+        self.unit = Some(unit);
+        self
+    }
+
+    pub fn string(uuid: &'static str) -> Self {
+        Self::new(uuid, Format::String, Perms::Read)
+    }
+
+    pub fn identify() -> Self {
+        Self::new("14", Format::Bool, Perms::WriteOnly)
+    }
+
+    /// Value the characteristic starts with until the first state is exported
+    pub fn initial_value(&self) -> Value {
+        match self.format {
+            Format::Bool => json!(false),
+            Format::String => json!(""),
+            Format::Uint8 | Format::Int => json!(self.min.unwrap_or(0.0).max(0.0) as i64),
+            Format::Float => json!(self.min.unwrap_or(0.0).max(0.0)),
+        }
+    }
+
+    /// Metadata part of the characteristic JSON
+    pub fn to_json(&self) -> serde_json::Map<String, Value> {
+        let mut json = serde_json::Map::new();
+        json.insert("type".to_string(), json!(self.uuid));
+        json.insert("format".to_string(), json!(self.format.as_str()));
+        json.insert("perms".to_string(), self.perms.to_json());
+
+        if let Some(min) = self.min {
+            json.insert("minValue".to_string(), json!(min));
+        }
+        if let Some(max) = self.max {
+            json.insert("maxValue".to_string(), json!(max));
+        }
+        if let Some(step) = self.step {
+            json.insert("minStep".to_string(), json!(step));
+        }
+        if let Some(unit) = self.unit {
+            json.insert("unit".to_string(), json!(unit));
+        }
+
+        json
+    }
+}
+
+pub fn service_uuid(service: &HomekitService) -> &'static str {
+    match service {
+        HomekitService::AirQualitySensor => "8D",
+        HomekitService::BatteryService => "96",
+        HomekitService::ContactSensor => "80",
+        HomekitService::Fanv2 => "B7",
+        HomekitService::GarageDoorOpener => "41",
+        HomekitService::HumiditySensor => "82",
+        HomekitService::LeakSensor => "83",
+        HomekitService::Lightbulb => "43",
+        HomekitService::LightSensor => "84",
+        HomekitService::LockMechanism => "45",
+        HomekitService::OccupancySensor => "86",
+        HomekitService::TemperatureSensor => "8A",
+        HomekitService::Switch => "49",
+        HomekitService::Thermostat => "4A",
+    }
+}
+
+pub fn characteristic_meta(characteristic: &HomekitCharacteristic) -> CharacteristicMeta {
+    use Format::*;
+    use Perms::*;
+
+    match characteristic {
+        HomekitCharacteristic::Active => CharacteristicMeta::new("B0", Uint8, ReadWrite).range(0.0, 1.0, 1.0),
+        HomekitCharacteristic::AirQuality => CharacteristicMeta::new("95", Uint8, Read).range(0.0, 5.0, 1.0),
+        HomekitCharacteristic::BatteryLevel => CharacteristicMeta::new("68", Uint8, Read)
+            .range(0.0, 100.0, 1.0)
+            .unit("percentage"),
+        HomekitCharacteristic::Brightness => CharacteristicMeta::new("8", Int, ReadWrite)
+            .range(0.0, 100.0, 1.0)
+            .unit("percentage"),
+        HomekitCharacteristic::ContactSensorState => CharacteristicMeta::new("6A", Uint8, Read).range(0.0, 1.0, 1.0),
+        HomekitCharacteristic::CurrentAmbientLightLevel => CharacteristicMeta::new("6B", Float, Read)
+            .range(0.0001, 100000.0, 0.0001)
+            .unit("lux"),
+        HomekitCharacteristic::CurrentDoorState => CharacteristicMeta::new("E", Uint8, Read).range(0.0, 4.0, 1.0),
+        HomekitCharacteristic::CurrentHeatingCoolingState => {
+            CharacteristicMeta::new("F", Uint8, Read).range(0.0, 2.0, 1.0)
+        }
+        HomekitCharacteristic::CurrentRelativeHumidity => CharacteristicMeta::new("10", Float, Read)
+            .range(0.0, 100.0, 1.0)
+            .unit("percentage"),
+        HomekitCharacteristic::CurrentTemperature => CharacteristicMeta::new("11", Float, Read)
+            .range(-270.0, 100.0, 0.1)
+            .unit("celsius"),
+        HomekitCharacteristic::LeakDetected => CharacteristicMeta::new("70", Uint8, Read).range(0.0, 1.0, 1.0),
+        HomekitCharacteristic::LockCurrentState => CharacteristicMeta::new("1D", Uint8, Read).range(0.0, 3.0, 1.0),
+        HomekitCharacteristic::LockTargetState => CharacteristicMeta::new("1E", Uint8, ReadWrite).range(0.0, 1.0, 1.0),
+        HomekitCharacteristic::OccupancyDetected => CharacteristicMeta::new("71", Uint8, Read).range(0.0, 1.0, 1.0),
+        HomekitCharacteristic::On => CharacteristicMeta::new("25", Bool, ReadWrite),
+        HomekitCharacteristic::Pm25Density => CharacteristicMeta::new("C6", Float, Read).range(0.0, 1000.0, 1.0),
+        HomekitCharacteristic::RotationDirection => CharacteristicMeta::new("28", Int, ReadWrite).range(0.0, 1.0, 1.0),
+        HomekitCharacteristic::RotationSpeed => CharacteristicMeta::new("29", Float, ReadWrite)
+            .range(0.0, 100.0, 1.0)
+            .unit("percentage"),
+        HomekitCharacteristic::StatusLowBattery => CharacteristicMeta::new("79", Uint8, Read).range(0.0, 1.0, 1.0),
+        HomekitCharacteristic::TargetDoorState => CharacteristicMeta::new("32", Uint8, ReadWrite).range(0.0, 1.0, 1.0),
+        HomekitCharacteristic::TargetHeatingCoolingState => {
+            CharacteristicMeta::new("33", Uint8, ReadWrite).range(0.0, 3.0, 1.0)
+        }
+        HomekitCharacteristic::TargetTemperature => CharacteristicMeta::new("35", Float, ReadWrite)
+            .range(10.0, 38.0, 0.1)
+            .unit("celsius"),
+        HomekitCharacteristic::TemperatureDisplayUnits => {
+            CharacteristicMeta::new("36", Uint8, ReadWrite).range(0.0, 1.0, 1.0)
+        }
+    }
+}
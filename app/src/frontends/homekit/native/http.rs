@@ -0,0 +1,143 @@
+//HAP uses a restricted HTTP/1.1 over a persistent connection, plus unsolicited EVENT/1.0 messages
+
+pub const CONTENT_TYPE_JSON: &str = "application/hap+json";
+pub const CONTENT_TYPE_TLV8: &str = "application/pairing+tlv8";
+
+const MAX_REQUEST_LEN: usize = 64 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    pub query: String,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    pub fn query_param(&self, name: &str) -> Option<&str> {
+        self.query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn query_flag(&self, name: &str) -> bool {
+        matches!(self.query_param(name), Some("1") | Some("true"))
+    }
+}
+
+/// Takes the first complete request from the buffer, if any
+pub fn parse_request(buffer: &mut Vec<u8>) -> anyhow::Result<Option<HttpRequest>> {
+    let Some(header_end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") else {
+        if buffer.len() > MAX_REQUEST_LEN {
+            anyhow::bail!("HTTP header too long");
+        }
+        return Ok(None);
+    };
+
+    let header = std::str::from_utf8(&buffer[..header_end])?;
+    let mut lines = header.split("\r\n");
+
+    let request_line = lines.next().unwrap_or_default();
+    let mut parts = request_line.split(' ');
+    let (Some(method), Some(uri)) = (parts.next(), parts.next()) else {
+        anyhow::bail!("Invalid HTTP request line: {}", request_line);
+    };
+
+    let mut content_length = 0;
+    for line in lines {
+        if let Some((name, value)) = line.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse::<usize>()?;
+        }
+    }
+
+    if content_length > MAX_REQUEST_LEN {
+        anyhow::bail!("HTTP body too long: {}", content_length);
+    }
+
+    let body_start = header_end + 4;
+    let Some(body) = buffer.get(body_start..body_start + content_length) else {
+        return Ok(None);
+    };
+
+    let (path, query) = uri.split_once('?').unwrap_or((uri, ""));
+    let request = HttpRequest {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: query.to_string(),
+        body: body.to_vec(),
+    };
+
+    buffer.drain(..body_start + content_length);
+    Ok(Some(request))
+}
+
+pub fn response(status: u16, content_type: &str, body: &[u8]) -> Vec<u8> {
+    let mut out = format!("HTTP/1.1 {} {}\r\n", status, reason(status));
+    if !body.is_empty() {
+        out.push_str(&format!("Content-Type: {}\r\n", content_type));
+    }
+    out.push_str(&format!("Content-Length: {}\r\n\r\n", body.len()));
+
+    let mut out = out.into_bytes();
+    out.extend_from_slice(body);
+    out
+}
+
+pub fn event(body: &[u8]) -> Vec<u8> {
+    let mut out = format!(
+        "EVENT/1.0 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n",
+        CONTENT_TYPE_JSON,
+        body.len()
+    )
+    .into_bytes();
+    out.extend_from_slice(body);
+    out
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        204 => "No Content",
+        207 => "Multi-Status",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        422 => "Unprocessable Entity",
+        470 => "Connection Authorization Required",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_are_parsed_once_complete() -> anyhow::Result<()> {
+        let raw = b"PUT /characteristics HTTP/1.1\r\nHost: bridge\r\nContent-Length: 5\r\n\r\nhelloGET /characteristics?id=2.10,2.11&meta=1 HTTP/1.1\r\n\r\n";
+
+        let mut buffer = raw[..60].to_vec();
+        assert_eq!(parse_request(&mut buffer)?, None);
+
+        buffer.extend_from_slice(&raw[60..]);
+        let Some(put) = parse_request(&mut buffer)? else {
+            panic!("Expected complete request");
+        };
+        assert_eq!(put.method, "PUT");
+        assert_eq!(put.body, b"hello");
+
+        let Some(get) = parse_request(&mut buffer)? else {
+            panic!("Expected complete request");
+        };
+        assert_eq!(get.path, "/characteristics");
+        assert_eq!(get.query_param("id"), Some("2.10,2.11"));
+        assert!(get.query_flag("meta"));
+        assert!(buffer.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,395 @@
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::{net::UdpSocket, sync::watch};
+
+//Minimal mDNS responder that only answers for the HAP service of this bridge
+
+const MDNS_ADDR: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+const MDNS_PORT: u16 = 5353;
+
+const SERVICE: &str = "_hap._tcp.local";
+const SERVICE_ENUMERATION: &str = "_services._dns-sd._udp.local";
+
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const TYPE_TXT: u16 = 16;
+const TYPE_SRV: u16 = 33;
+const TYPE_ANY: u16 = 255;
+
+const CLASS_IN: u16 = 1;
+const CACHE_FLUSH: u16 = 0x8000;
+
+/// Values of the `_hap._tcp` TXT record
+#[derive(Debug, Clone)]
+pub struct HapServiceInfo {
+    pub name: String,
+    pub port: u16,
+    pub address: Ipv4Addr,
+    pub device_id: String,
+    pub config_number: u32,
+}
+
+impl HapServiceInfo {
+    fn instance(&self) -> String {
+        format!("{}.{}", self.name, SERVICE)
+    }
+
+    fn host(&self) -> String {
+        let host: String = self
+            .name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        format!("{}.local", host)
+    }
+
+    fn txt(&self, paired: bool) -> Vec<String> {
+        vec![
+            format!("c#={}", self.config_number),
+            "ff=0".to_string(),
+            format!("id={}", self.device_id),
+            format!("md={}", self.name),
+            "pv=1.1".to_string(),
+            "s#=1".to_string(),
+            format!("sf={}", if paired { 0 } else { 1 }),
+            //bridge
+            "ci=2".to_string(),
+        ]
+    }
+}
+
+pub struct MdnsResponder {
+    info: HapServiceInfo,
+    paired_rx: watch::Receiver<bool>,
+}
+
+impl MdnsResponder {
+    pub fn new(info: HapServiceInfo, paired_rx: watch::Receiver<bool>) -> Self {
+        Self { info, paired_rx }
+    }
+
+    pub async fn run(mut self) {
+        let socket = match multicast_socket(self.info.address) {
+            Ok(socket) => socket,
+            Err(e) => {
+                tracing::error!(
+                    "Error opening mDNS socket, HAP bridge will not be discoverable: {:?}",
+                    e
+                );
+                return;
+            }
+        };
+
+        let multicast = SocketAddr::V4(SocketAddrV4::new(MDNS_ADDR, MDNS_PORT));
+        self.announce(&socket, multicast).await;
+
+        let mut buffer = vec![0u8; 9000];
+        loop {
+            tokio::select! {
+                received = socket.recv_from(&mut buffer) => match received {
+                    Ok((len, source)) => {
+                        let Some(query) = parse_query(&buffer[..len]) else {
+                            continue;
+                        };
+
+                        if !query.questions.iter().any(|q| self.is_answered_by_us(q)) {
+                            continue;
+                        }
+
+                        let paired = *self.paired_rx.borrow();
+                        let enumeration = query.questions.iter().any(|q| q.name.eq_ignore_ascii_case(SERVICE_ENUMERATION));
+
+                        //legacy unicast queries come from other ports and expect the query id back
+                        let (id, target) = if source.port() != MDNS_PORT {
+                            (query.id, source)
+                        } else if query.unicast_response {
+                            (0, source)
+                        } else {
+                            (0, multicast)
+                        };
+
+                        let response = build_response(&self.info, paired, id, enumeration);
+                        if let Err(e) = socket.send_to(&response, target).await {
+                            tracing::warn!("Error sending mDNS response: {:?}", e);
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!("Error receiving mDNS query: {:?}", e);
+                    }
+                },
+
+                Ok(()) = self.paired_rx.changed() => {
+                    self.announce(&socket, multicast).await;
+                }
+            }
+        }
+    }
+
+    fn is_answered_by_us(&self, question: &Question) -> bool {
+        let name = question.name.as_str();
+
+        (name.eq_ignore_ascii_case(SERVICE) && matches!(question.qtype, TYPE_PTR | TYPE_ANY))
+            || (name.eq_ignore_ascii_case(SERVICE_ENUMERATION) && matches!(question.qtype, TYPE_PTR | TYPE_ANY))
+            || (name.eq_ignore_ascii_case(&self.info.instance())
+                && matches!(question.qtype, TYPE_SRV | TYPE_TXT | TYPE_ANY))
+            || (name.eq_ignore_ascii_case(&self.info.host()) && matches!(question.qtype, TYPE_A | TYPE_ANY))
+    }
+
+    //sent twice, as recommended for announcements
+    async fn announce(&mut self, socket: &UdpSocket, target: SocketAddr) {
+        let paired = *self.paired_rx.borrow_and_update();
+        let response = build_response(&self.info, paired, 0, false);
+
+        for i in 0..2 {
+            if i > 0 {
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+            }
+
+            if let Err(e) = socket.send_to(&response, target).await {
+                tracing::warn!("Error announcing HAP service via mDNS: {:?}", e);
+            }
+        }
+    }
+}
+
+fn multicast_socket(interface: Ipv4Addr) -> anyhow::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    #[cfg(unix)]
+    socket.set_reuse_port(true)?;
+    socket.bind(&SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, MDNS_PORT)).into())?;
+    socket.join_multicast_v4(&MDNS_ADDR, &interface)?;
+    socket.set_multicast_if_v4(&interface)?;
+    socket.set_multicast_ttl_v4(255)?;
+    socket.set_nonblocking(true)?;
+
+    Ok(UdpSocket::from_std(socket.into())?)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Question {
+    name: String,
+    qtype: u16,
+}
+
+#[derive(Debug)]
+struct Query {
+    id: u16,
+    unicast_response: bool,
+    questions: Vec<Question>,
+}
+
+fn parse_query(packet: &[u8]) -> Option<Query> {
+    let id = read_u16(packet, 0)?;
+    let flags = read_u16(packet, 2)?;
+    //only queries
+    if flags & 0x8000 != 0 {
+        return None;
+    }
+
+    let question_count = read_u16(packet, 4)?;
+    let mut pos = 12;
+    let mut questions = Vec::new();
+    let mut unicast_response = false;
+
+    for _ in 0..question_count {
+        let (name, next) = read_name(packet, pos)?;
+        let qtype = read_u16(packet, next)?;
+        let qclass = read_u16(packet, next + 2)?;
+
+        unicast_response |= qclass & 0x8000 != 0;
+        questions.push(Question { name, qtype });
+        pos = next + 4;
+    }
+
+    Some(Query {
+        id,
+        unicast_response,
+        questions,
+    })
+}
+
+fn read_u16(packet: &[u8], pos: usize) -> Option<u16> {
+    packet.get(pos..pos + 2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+//Returns the name and the position after it, following compression pointers
+fn read_name(packet: &[u8], start: usize) -> Option<(String, usize)> {
+    let mut labels: Vec<String> = Vec::new();
+    let mut pos = start;
+    let mut end = None;
+    let mut jumps = 0;
+
+    loop {
+        let len = *packet.get(pos)? as usize;
+
+        if len == 0 {
+            end.get_or_insert(pos + 1);
+            break;
+        }
+
+        if len & 0xC0 == 0xC0 {
+            let pointer = (read_u16(packet, pos)? & 0x3FFF) as usize;
+            end.get_or_insert(pos + 2);
+            jumps += 1;
+            if jumps > 16 {
+                return None;
+            }
+            pos = pointer;
+            continue;
+        }
+
+        let label = packet.get(pos + 1..pos + 1 + len)?;
+        labels.push(String::from_utf8_lossy(label).to_string());
+        pos += 1 + len;
+    }
+
+    Some((labels.join("."), end?))
+}
+
+fn build_response(info: &HapServiceInfo, paired: bool, id: u16, enumeration: bool) -> Vec<u8> {
+    let instance = info.instance();
+    let host = info.host();
+
+    let mut records: Vec<(String, u16, u16, u32, Vec<u8>)> = vec![
+        (SERVICE.to_string(), TYPE_PTR, CLASS_IN, 4500, encode_name(&instance)),
+        (instance.clone(), TYPE_SRV, CLASS_IN | CACHE_FLUSH, 120, {
+            let mut srv = vec![0, 0, 0, 0];
+            srv.extend_from_slice(&info.port.to_be_bytes());
+            srv.extend_from_slice(&encode_name(&host));
+            srv
+        }),
+        (instance.clone(), TYPE_TXT, CLASS_IN | CACHE_FLUSH, 4500, {
+            let mut txt = Vec::new();
+            for entry in info.txt(paired) {
+                let bytes = &entry.as_bytes()[..entry.len().min(255)];
+                txt.push(bytes.len() as u8);
+                txt.extend_from_slice(bytes);
+            }
+            txt
+        }),
+        (
+            host,
+            TYPE_A,
+            CLASS_IN | CACHE_FLUSH,
+            120,
+            info.address.octets().to_vec(),
+        ),
+    ];
+
+    if enumeration {
+        records.push((
+            SERVICE_ENUMERATION.to_string(),
+            TYPE_PTR,
+            CLASS_IN,
+            4500,
+            encode_name(SERVICE),
+        ));
+    }
+
+    let mut packet = Vec::new();
+    packet.extend_from_slice(&id.to_be_bytes());
+    //authoritative response
+    packet.extend_from_slice(&0x8400u16.to_be_bytes());
+    packet.extend_from_slice(&0u16.to_be_bytes());
+    packet.extend_from_slice(&(records.len() as u16).to_be_bytes());
+    packet.extend_from_slice(&0u16.to_be_bytes());
+    packet.extend_from_slice(&0u16.to_be_bytes());
+
+    for (name, rtype, class, ttl, data) in records {
+        packet.extend_from_slice(&encode_name(&name));
+        packet.extend_from_slice(&rtype.to_be_bytes());
+        packet.extend_from_slice(&class.to_be_bytes());
+        packet.extend_from_slice(&ttl.to_be_bytes());
+        packet.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        packet.extend_from_slice(&data);
+    }
+
+    packet
+}
+
+//The instance label may contain dots of the display name, so only the known service suffix is split
+fn encode_name(name: &str) -> Vec<u8> {
+    let (first, rest) = match name.strip_suffix(SERVICE).and_then(|n| n.strip_suffix('.')) {
+        Some(instance) => (Some(instance), SERVICE),
+        None => (None, name),
+    };
+
+    let mut out = Vec::new();
+    for label in first.into_iter().chain(rest.split('.')) {
+        let bytes = &label.as_bytes()[..label.len().min(63)];
+        out.push(bytes.len() as u8);
+        out.extend_from_slice(bytes);
+    }
+    out.push(0);
+    out
+}
+
+/// Address of the interface that routes to the mDNS group, used if none is configured
+pub fn default_address() -> anyhow::Result<Ipv4Addr> {
+    let socket = std::net::UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    socket.connect((MDNS_ADDR, MDNS_PORT))?;
+
+    match socket.local_addr()? {
+        SocketAddr::V4(addr) if !addr.ip().is_unspecified() => Ok(*addr.ip()),
+        other => anyhow::bail!("No IPv4 address to advertise the HAP bridge with, got {}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info() -> HapServiceInfo {
+        HapServiceInfo {
+            name: "Rusty Home".to_string(),
+            port: 51826,
+            address: Ipv4Addr::new(192, 168, 1, 10),
+            device_id: "AA:BB:CC:DD:EE:FF".to_string(),
+            config_number: 7,
+        }
+    }
+
+    #[test]
+    fn ptr_query_with_compression_is_parsed() {
+        // query for _hap._tcp.local PTR, followed by a second question pointing to "_tcp.local"
+        let mut packet = vec![0x12, 0x34, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0];
+        packet.extend_from_slice(&encode_name(SERVICE));
+        packet.extend_from_slice(&[0, 12, 0x80, 1]);
+        packet.extend_from_slice(&[3, b'f', b'o', b'o', 0xC0, 17]);
+        packet.extend_from_slice(&[0, 255, 0, 1]);
+
+        let Some(query) = parse_query(&packet) else {
+            panic!("Expected query");
+        };
+
+        assert_eq!(query.id, 0x1234);
+        assert!(query.unicast_response);
+        assert_eq!(
+            query.questions,
+            vec![
+                Question {
+                    name: SERVICE.to_string(),
+                    qtype: TYPE_PTR
+                },
+                Question {
+                    name: "foo._tcp.local".to_string(),
+                    qtype: TYPE_ANY
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn response_contains_service_records() {
+        let response = build_response(&info(), false, 0, false);
+
+        //header: 4 answers
+        assert_eq!(&response[..12], &[0, 0, 0x84, 0, 0, 0, 0, 4, 0, 0, 0, 0]);
+        assert!(response.windows(4).any(|w| w == b"sf=1"));
+        assert!(response.windows(8).any(|w| w == &b"\x0aRusty Home"[..8]));
+        assert!(response.windows(4).any(|w| w == [192, 168, 1, 10]));
+        assert_eq!(encode_name("A.b._hap._tcp.local")[..5], [3, b'A', b'.', b'b', 4]);
+    }
+}
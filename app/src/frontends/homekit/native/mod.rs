@@ -0,0 +1,153 @@
+mod crypto;
+mod database;
+mod definitions;
+mod http;
+mod mdns;
+mod pairing;
+mod server;
+mod srp;
+mod store;
+mod tlv;
+
+use std::{net::Ipv4Addr, sync::Arc};
+
+use anyhow::Context as _;
+use serde::Deserialize;
+use sqlx::PgPool;
+use tokio::{net::TcpListener, sync::mpsc};
+
+use self::{
+    database::AccessoryDatabase,
+    mdns::{HapServiceInfo, MdnsResponder},
+    pairing::PairingManager,
+    server::{CharacteristicChange, HapServerState},
+    store::HapStore,
+};
+use super::{HomekitEvent, HomekitTargetConfig};
+
+/// Native HAP bridge, replaces Homebridge if configured
+#[derive(Clone, Deserialize, Debug)]
+pub struct HapServerConfig {
+    #[serde(default = "default_name")]
+    pub name: String,
+    /// Code to enter in the Home app, format `XXX-XX-XXX`
+    pub setup_code: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// Address announced via mDNS, detected from the default route if not set
+    #[serde(default)]
+    pub advertise_address: Option<Ipv4Addr>,
+}
+
+fn default_name() -> String {
+    "Rusty Home".to_string()
+}
+
+fn default_port() -> u16 {
+    51826
+}
+
+/// Handle of the running HAP server. Exported states go in, writes of the controllers come out.
+pub struct HapBridge {
+    state: Arc<HapServerState>,
+    writes_rx: mpsc::Receiver<HomekitEvent>,
+}
+
+impl HapBridge {
+    pub async fn start(
+        config: &HapServerConfig,
+        db_pool: PgPool,
+        targets: Vec<HomekitTargetConfig>,
+    ) -> anyhow::Result<Self> {
+        validate_setup_code(&config.setup_code)?;
+
+        let store = HapStore::new(db_pool);
+        let identity = store.load_or_create_identity().await?;
+        let pairing = PairingManager::new(identity, config.setup_code.clone(), store).await?;
+        let database = AccessoryDatabase::new(&config.name, targets);
+
+        let service_info = HapServiceInfo {
+            name: config.name.clone(),
+            port: config.port,
+            address: match config.advertise_address {
+                Some(address) => address,
+                None => mdns::default_address()?,
+            },
+            device_id: pairing.device_id().to_string(),
+            config_number: database.config_number(),
+        };
+        let mdns = MdnsResponder::new(service_info, pairing.subscribe_paired());
+
+        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, config.port))
+            .await
+            .with_context(|| format!("Error binding HAP server to port {}", config.port))?;
+
+        let (writes_tx, writes_rx) = mpsc::channel(64);
+        let state = Arc::new(HapServerState::new(pairing, database, writes_tx));
+
+        tracing::info!(
+            "Starting HAP bridge {} on port {}, paired: {}",
+            config.name,
+            config.port,
+            state.pairing.is_paired()
+        );
+
+        tokio::spawn(server::serve(listener, state.clone()));
+        tokio::spawn(mdns.run());
+
+        Ok(Self { state, writes_rx })
+    }
+
+    pub async fn recv(&mut self) -> Option<HomekitEvent> {
+        self.writes_rx.recv().await
+    }
+
+    pub fn export(&self, events: Vec<HomekitEvent>) {
+        let mut database = self.state.database();
+
+        for event in events {
+            match database.update(&event.target, &event.value) {
+                Some((id, value)) => {
+                    //no subscribed connection is fine
+                    let _ = self.state.changes.send(CharacteristicChange {
+                        id,
+                        value,
+                        origin: None,
+                    });
+                }
+                None => tracing::trace!("Unchanged or unknown HAP target {:?}", event.target),
+            }
+        }
+    }
+}
+
+fn validate_setup_code(code: &str) -> anyhow::Result<()> {
+    let digits: Vec<&str> = code.split('-').collect();
+    let valid = digits.iter().map(|part| part.len()).collect::<Vec<_>>() == [3, 2, 3]
+        && digits.iter().all(|part| part.chars().all(|c| c.is_ascii_digit()));
+
+    if !valid {
+        anyhow::bail!("Invalid HomeKit setup code {}, expected format XXX-XX-XXX", code);
+    }
+
+    //rejected by HomeKit as too simple
+    let plain = digits.concat();
+    if ["12345678", "87654321"].contains(&plain.as_str()) || plain.chars().all(|c| plain.starts_with(c)) {
+        anyhow::bail!("HomeKit setup code {} is too simple", code);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn setup_code_format_is_checked() {
+        assert!(validate_setup_code("031-45-154").is_ok());
+        assert!(validate_setup_code("03145154").is_err());
+        assert!(validate_setup_code("123-45-678").is_err());
+        assert!(validate_setup_code("111-11-111").is_err());
+    }
+}
@@ -0,0 +1,694 @@
+use std::sync::{
+    Mutex, MutexGuard,
+    atomic::{AtomicU32, Ordering},
+};
+
+use ring::{
+    agreement::{EphemeralPrivateKey, UnparsedPublicKey, X25519, agree_ephemeral},
+    rand::{SecureRandom, SystemRandom},
+    signature::{ED25519, UnparsedPublicKey as SignaturePublicKey},
+};
+use tokio::sync::{broadcast, watch};
+
+use super::{
+    crypto::{self, Key, SessionCipher},
+    srp::SrpServer,
+    store::{AccessoryIdentity, HapStore, Pairing},
+    tlv::{self, Tlv},
+};
+use crate::core::sync::lock_ignoring_poison;
+
+const MAX_PAIRINGS: usize = 16;
+/// Pair setup is refused after this many failed attempts until the counter is reset
+const MAX_PAIR_SETUP_ATTEMPTS: u32 = 100;
+
+const METHOD_ADD_PAIRING: u8 = 3;
+const METHOD_REMOVE_PAIRING: u8 = 4;
+const METHOD_LIST_PAIRINGS: u8 = 5;
+
+const PERMISSION_ADMIN: u8 = 1;
+
+struct PairSetup {
+    connection_id: u64,
+    srp: SrpServer,
+    session_key: Option<Vec<u8>>,
+}
+
+/// Pair verify state of a single connection
+pub struct PairVerify {
+    accessory_public: Vec<u8>,
+    controller_public: Vec<u8>,
+    shared_secret: Vec<u8>,
+    session_key: Key,
+}
+
+pub struct VerifiedSession {
+    pub controller_id: String,
+    pub cipher: SessionCipher,
+}
+
+/// Pair setup, pair verify and pairing management of the bridge
+pub struct PairingManager {
+    identity: AccessoryIdentity,
+    setup_code: String,
+    store: HapStore,
+    pairings: Mutex<Vec<Pairing>>,
+    setup: Mutex<Option<PairSetup>>,
+    failed_setup_attempts: AtomicU32,
+    paired_tx: watch::Sender<bool>,
+    removed_tx: broadcast::Sender<String>,
+    rng: SystemRandom,
+}
+
+impl PairingManager {
+    pub async fn new(identity: AccessoryIdentity, setup_code: String, store: HapStore) -> anyhow::Result<Self> {
+        let pairings = store.get_pairings().await?;
+        let failed_setup_attempts = store.get_failed_pair_setup_attempts().await?;
+        let (paired_tx, _) = watch::channel(!pairings.is_empty());
+        let (removed_tx, _) = broadcast::channel(MAX_PAIRINGS);
+
+        Ok(Self {
+            identity,
+            setup_code,
+            store,
+            pairings: Mutex::new(pairings),
+            setup: Mutex::new(None),
+            failed_setup_attempts: AtomicU32::new(failed_setup_attempts),
+            paired_tx,
+            removed_tx,
+            rng: SystemRandom::new(),
+        })
+    }
+
+    pub fn device_id(&self) -> &str {
+        &self.identity.device_id
+    }
+
+    pub fn subscribe_paired(&self) -> watch::Receiver<bool> {
+        self.paired_tx.subscribe()
+    }
+
+    /// Controller ids whose pairing was removed, their sessions must be closed
+    pub fn subscribe_removed(&self) -> broadcast::Receiver<String> {
+        self.removed_tx.subscribe()
+    }
+
+    pub fn is_paired(&self) -> bool {
+        !self.pairings().is_empty()
+    }
+
+    /// `None` if the controller is not (or no longer) paired
+    pub fn is_admin(&self, controller_id: &str) -> Option<bool> {
+        self.pairings()
+            .iter()
+            .find(|p| p.controller_id == controller_id)
+            .map(|p| p.admin)
+    }
+
+    /// Drops a pair setup that was started on a closed connection
+    pub fn abort_setup(&self, connection_id: u64) {
+        let mut setup = self.setup();
+        if setup.as_ref().is_some_and(|s| s.connection_id == connection_id) {
+            *setup = None;
+        }
+    }
+
+    pub async fn pair_setup(&self, connection_id: u64, body: &[u8]) -> Tlv {
+        let request = match Tlv::decode(body) {
+            Ok(request) => request,
+            Err(e) => {
+                tracing::warn!("Invalid pair setup request: {:?}", e);
+                return Tlv::error(2, tlv::ERROR_UNKNOWN);
+            }
+        };
+
+        match request.get_u8(tlv::STATE) {
+            Some(1) => self.pair_setup_start(connection_id),
+            Some(3) => self.pair_setup_verify(connection_id, &request).await,
+            Some(5) => self.pair_setup_exchange(connection_id, &request).await,
+            state => {
+                tracing::warn!("Unexpected pair setup state {:?}", state);
+                Tlv::error(2, tlv::ERROR_UNKNOWN)
+            }
+        }
+    }
+
+    //M1 -> M2: SRP start
+    fn pair_setup_start(&self, connection_id: u64) -> Tlv {
+        if self.is_paired() {
+            return Tlv::error(2, tlv::ERROR_UNAVAILABLE);
+        }
+
+        if self.failed_setup_attempts.load(Ordering::Relaxed) >= MAX_PAIR_SETUP_ATTEMPTS {
+            tracing::warn!("HAP pair setup refused after {} failed attempts", MAX_PAIR_SETUP_ATTEMPTS);
+            return Tlv::error(2, tlv::ERROR_MAX_TRIES);
+        }
+
+        let mut setup = self.setup();
+        if setup.as_ref().is_some_and(|s| s.connection_id != connection_id) {
+            return Tlv::error(2, tlv::ERROR_BUSY);
+        }
+
+        let mut salt = [0u8; 16];
+        let mut private_b = [0u8; 32];
+        if self.rng.fill(&mut salt).is_err() || self.rng.fill(&mut private_b).is_err() {
+            return Tlv::error(2, tlv::ERROR_UNKNOWN);
+        }
+
+        let srp = SrpServer::new(&self.setup_code, salt, &private_b);
+        let response = Tlv::new()
+            .with(tlv::STATE, [2])
+            .with(tlv::SALT, srp.salt())
+            .with(tlv::PUBLIC_KEY, srp.public_key());
+
+        *setup = Some(PairSetup {
+            connection_id,
+            srp,
+            session_key: None,
+        });
+
+        response
+    }
+
+    //M3 -> M4: SRP verify
+    async fn pair_setup_verify(&self, connection_id: u64, request: &Tlv) -> Tlv {
+        {
+            let mut setup = self.setup();
+            let Some(pair_setup) = setup.as_mut().filter(|s| s.connection_id == connection_id) else {
+                return Tlv::error(4, tlv::ERROR_UNKNOWN);
+            };
+
+            let (Some(public_key), Some(proof)) = (request.get(tlv::PUBLIC_KEY), request.get(tlv::PROOF)) else {
+                return Tlv::error(4, tlv::ERROR_UNKNOWN);
+            };
+
+            match pair_setup.srp.verify(public_key, proof) {
+                Ok(session) => {
+                    pair_setup.session_key = Some(session.key);
+                    return Tlv::new().with(tlv::STATE, [4]).with(tlv::PROOF, session.server_proof);
+                }
+                Err(e) => {
+                    tracing::warn!("HAP pair setup failed, wrong setup code? {:?}", e);
+                    *setup = None;
+                }
+            }
+        }
+
+        //counted in memory as well, so that a failing database doesn't lift the limit
+        self.failed_setup_attempts.fetch_add(1, Ordering::Relaxed);
+        match self.store.add_failed_pair_setup_attempt().await {
+            Ok(attempts) => self.failed_setup_attempts.store(attempts, Ordering::Relaxed),
+            Err(e) => tracing::error!("Error saving failed HAP pair setup attempt: {:?}", e),
+        }
+
+        Tlv::error(4, tlv::ERROR_AUTHENTICATION)
+    }
+
+    //M5 -> M6: exchange of long-term keys
+    async fn pair_setup_exchange(&self, connection_id: u64, request: &Tlv) -> Tlv {
+        let session_key = {
+            let mut setup = self.setup();
+            match setup.take().filter(|s| s.connection_id == connection_id) {
+                Some(PairSetup {
+                    session_key: Some(key), ..
+                }) => key,
+                other => {
+                    *setup = other;
+                    return Tlv::error(6, tlv::ERROR_UNKNOWN);
+                }
+            }
+        };
+
+        let pairing = match self.verify_controller_keys(&session_key, request) {
+            Ok(pairing) => pairing,
+            Err(e) => {
+                tracing::warn!("HAP pair setup failed in key exchange: {:?}", e);
+                return Tlv::error(6, tlv::ERROR_AUTHENTICATION);
+            }
+        };
+
+        if let Err(e) = self.add_pairing(pairing.clone()).await {
+            tracing::error!("Error saving HAP pairing: {:?}", e);
+            return Tlv::error(6, tlv::ERROR_UNKNOWN);
+        }
+
+        if let Err(e) = self.store.reset_failed_pair_setup_attempts().await {
+            tracing::error!("Error resetting failed HAP pair setup attempts: {:?}", e);
+        }
+        self.failed_setup_attempts.store(0, Ordering::Relaxed);
+
+        match self.accessory_keys(&session_key) {
+            Ok(encrypted) => {
+                tracing::info!("Paired with HomeKit controller {}", pairing.controller_id);
+                Tlv::new().with(tlv::STATE, [6]).with(tlv::ENCRYPTED_DATA, encrypted)
+            }
+            Err(e) => {
+                tracing::error!("Error creating HAP pair setup response: {:?}", e);
+                Tlv::error(6, tlv::ERROR_UNKNOWN)
+            }
+        }
+    }
+
+    fn verify_controller_keys(&self, session_key: &[u8], request: &Tlv) -> anyhow::Result<Pairing> {
+        let encrypt_key = crypto::hkdf_sha512(session_key, "Pair-Setup-Encrypt-Salt", "Pair-Setup-Encrypt-Info")?;
+        let encrypted = request
+            .get(tlv::ENCRYPTED_DATA)
+            .ok_or_else(|| anyhow::anyhow!("Missing encrypted data"))?;
+        let data = Tlv::decode(&crypto::open(
+            &encrypt_key,
+            crypto::message_nonce("PS-Msg05"),
+            &[],
+            encrypted,
+        )?)?;
+
+        let (Some(controller_id), Some(public_key), Some(signature)) = (
+            data.get(tlv::IDENTIFIER),
+            data.get(tlv::PUBLIC_KEY),
+            data.get(tlv::SIGNATURE),
+        ) else {
+            anyhow::bail!("Missing controller identifier, key or signature");
+        };
+
+        let controller_x = crypto::hkdf_sha512(
+            session_key,
+            "Pair-Setup-Controller-Sign-Salt",
+            "Pair-Setup-Controller-Sign-Info",
+        )?;
+        let info = [&controller_x[..], controller_id, public_key].concat();
+        SignaturePublicKey::new(&ED25519, public_key)
+            .verify(&info, signature)
+            .map_err(|_| anyhow::anyhow!("Invalid controller signature"))?;
+
+        Ok(Pairing {
+            controller_id: String::from_utf8(controller_id.to_vec())?,
+            public_key: public_key.to_vec(),
+            admin: true,
+        })
+    }
+
+    fn accessory_keys(&self, session_key: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let accessory_x = crypto::hkdf_sha512(
+            session_key,
+            "Pair-Setup-Accessory-Sign-Salt",
+            "Pair-Setup-Accessory-Sign-Info",
+        )?;
+        let info = [
+            &accessory_x[..],
+            self.identity.device_id.as_bytes(),
+            self.identity.public_key(),
+        ]
+        .concat();
+        let signature = self.identity.key_pair.sign(&info);
+
+        let data = Tlv::new()
+            .with(tlv::IDENTIFIER, self.identity.device_id.as_bytes())
+            .with(tlv::PUBLIC_KEY, self.identity.public_key())
+            .with(tlv::SIGNATURE, signature.as_ref());
+
+        let encrypt_key = crypto::hkdf_sha512(session_key, "Pair-Setup-Encrypt-Salt", "Pair-Setup-Encrypt-Info")?;
+        crypto::seal(&encrypt_key, crypto::message_nonce("PS-Msg06"), &[], &data.encode())
+    }
+
+    /// Returns the response and, once verification completed, the session to encrypt the connection with
+    pub fn pair_verify(&self, state: &mut Option<PairVerify>, body: &[u8]) -> (Tlv, Option<VerifiedSession>) {
+        let request = match Tlv::decode(body) {
+            Ok(request) => request,
+            Err(e) => {
+                tracing::warn!("Invalid pair verify request: {:?}", e);
+                return (Tlv::error(2, tlv::ERROR_UNKNOWN), None);
+            }
+        };
+
+        match request.get_u8(tlv::STATE) {
+            Some(1) => match self.pair_verify_start(&request) {
+                Ok((response, verify)) => {
+                    *state = Some(verify);
+                    (response, None)
+                }
+                Err(e) => {
+                    tracing::warn!("HAP pair verify start failed: {:?}", e);
+                    (Tlv::error(2, tlv::ERROR_UNKNOWN), None)
+                }
+            },
+            Some(3) => {
+                let Some(verify) = state.take() else {
+                    return (Tlv::error(4, tlv::ERROR_UNKNOWN), None);
+                };
+
+                match self.pair_verify_finish(&verify, &request) {
+                    Ok(session) => (Tlv::new().with(tlv::STATE, [4]), Some(session)),
+                    Err(e) => {
+                        tracing::warn!("HAP pair verify failed: {:?}", e);
+                        (Tlv::error(4, tlv::ERROR_AUTHENTICATION), None)
+                    }
+                }
+            }
+            other => {
+                tracing::warn!("Unexpected pair verify state {:?}", other);
+                (Tlv::error(2, tlv::ERROR_UNKNOWN), None)
+            }
+        }
+    }
+
+    //M1 -> M2: ephemeral key exchange, accessory proves its identity
+    fn pair_verify_start(&self, request: &Tlv) -> anyhow::Result<(Tlv, PairVerify)> {
+        let controller_public = request
+            .get(tlv::PUBLIC_KEY)
+            .ok_or_else(|| anyhow::anyhow!("Missing controller public key"))?
+            .to_vec();
+
+        let private_key = EphemeralPrivateKey::generate(&X25519, &self.rng)
+            .map_err(|_| anyhow::anyhow!("Error generating ephemeral key"))?;
+        let accessory_public = private_key
+            .compute_public_key()
+            .map_err(|_| anyhow::anyhow!("Error computing ephemeral public key"))?
+            .as_ref()
+            .to_vec();
+        let shared_secret = agree_ephemeral(
+            private_key,
+            &UnparsedPublicKey::new(&X25519, &controller_public),
+            |secret| secret.to_vec(),
+        )
+        .map_err(|_| anyhow::anyhow!("Error agreeing on shared secret"))?;
+
+        let info = [
+            accessory_public.as_slice(),
+            self.identity.device_id.as_bytes(),
+            controller_public.as_slice(),
+        ]
+        .concat();
+        let signature = self.identity.key_pair.sign(&info);
+
+        let data = Tlv::new()
+            .with(tlv::IDENTIFIER, self.identity.device_id.as_bytes())
+            .with(tlv::SIGNATURE, signature.as_ref());
+
+        let session_key = crypto::hkdf_sha512(&shared_secret, "Pair-Verify-Encrypt-Salt", "Pair-Verify-Encrypt-Info")?;
+        let encrypted = crypto::seal(&session_key, crypto::message_nonce("PV-Msg02"), &[], &data.encode())?;
+
+        let response = Tlv::new()
+            .with(tlv::STATE, [2])
+            .with(tlv::PUBLIC_KEY, &accessory_public)
+            .with(tlv::ENCRYPTED_DATA, encrypted);
+
+        Ok((
+            response,
+            PairVerify {
+                accessory_public,
+                controller_public,
+                shared_secret,
+                session_key,
+            },
+        ))
+    }
+
+    //M3 -> M4: controller proves it is paired
+    fn pair_verify_finish(&self, verify: &PairVerify, request: &Tlv) -> anyhow::Result<VerifiedSession> {
+        let encrypted = request
+            .get(tlv::ENCRYPTED_DATA)
+            .ok_or_else(|| anyhow::anyhow!("Missing encrypted data"))?;
+        let data = Tlv::decode(&crypto::open(
+            &verify.session_key,
+            crypto::message_nonce("PV-Msg03"),
+            &[],
+            encrypted,
+        )?)?;
+
+        let (Some(controller_id), Some(signature)) = (data.get(tlv::IDENTIFIER), data.get(tlv::SIGNATURE)) else {
+            anyhow::bail!("Missing controller identifier or signature");
+        };
+        let controller_id = String::from_utf8(controller_id.to_vec())?;
+
+        let public_key = self
+            .pairings()
+            .iter()
+            .find(|p| p.controller_id == controller_id)
+            .map(|p| p.public_key.clone())
+            .ok_or_else(|| anyhow::anyhow!("Controller {} is not paired", controller_id))?;
+
+        let info = [
+            verify.controller_public.as_slice(),
+            controller_id.as_bytes(),
+            verify.accessory_public.as_slice(),
+        ]
+        .concat();
+        SignaturePublicKey::new(&ED25519, &public_key)
+            .verify(&info, signature)
+            .map_err(|_| anyhow::anyhow!("Invalid signature of controller {}", controller_id))?;
+
+        Ok(VerifiedSession {
+            controller_id,
+            cipher: SessionCipher::new(&verify.shared_secret)?,
+        })
+    }
+
+    /// Add, remove and list pairings. Only allowed for admin controllers.
+    pub async fn manage_pairings(&self, controller_id: &str, body: &[u8]) -> Tlv {
+        let request = match Tlv::decode(body) {
+            Ok(request) => request,
+            Err(e) => {
+                tracing::warn!("Invalid pairings request: {:?}", e);
+                return Tlv::error(2, tlv::ERROR_UNKNOWN);
+            }
+        };
+
+        if self.is_admin(controller_id) != Some(true) {
+            return Tlv::error(2, tlv::ERROR_AUTHENTICATION);
+        }
+
+        let result = match request.get_u8(tlv::METHOD) {
+            Some(METHOD_ADD_PAIRING) => self.handle_add_pairing(&request).await,
+            Some(METHOD_REMOVE_PAIRING) => self.handle_remove_pairing(&request).await,
+            Some(METHOD_LIST_PAIRINGS) => Ok(self.list_pairings()),
+            other => Err(anyhow::anyhow!("Unsupported pairings method {:?}", other)),
+        };
+
+        result.unwrap_or_else(|e| {
+            tracing::error!("Error managing HAP pairings: {:?}", e);
+            Tlv::error(2, tlv::ERROR_UNKNOWN)
+        })
+    }
+
+    async fn handle_add_pairing(&self, request: &Tlv) -> anyhow::Result<Tlv> {
+        let (Some(controller_id), Some(public_key)) = (request.get(tlv::IDENTIFIER), request.get(tlv::PUBLIC_KEY))
+        else {
+            anyhow::bail!("Missing identifier or public key");
+        };
+
+        let pairing = Pairing {
+            controller_id: String::from_utf8(controller_id.to_vec())?,
+            public_key: public_key.to_vec(),
+            admin: request.get_u8(tlv::PERMISSIONS) == Some(PERMISSION_ADMIN),
+        };
+
+        {
+            let pairings = self.pairings();
+            match pairings.iter().find(|p| p.controller_id == pairing.controller_id) {
+                Some(existing) if existing.public_key != pairing.public_key => {
+                    return Ok(Tlv::error(2, tlv::ERROR_UNKNOWN));
+                }
+                None if pairings.len() >= MAX_PAIRINGS => return Ok(Tlv::error(2, tlv::ERROR_MAX_PEERS)),
+                _ => {}
+            }
+        }
+
+        self.add_pairing(pairing).await?;
+        Ok(Tlv::new().with(tlv::STATE, [2]))
+    }
+
+    async fn handle_remove_pairing(&self, request: &Tlv) -> anyhow::Result<Tlv> {
+        let controller_id = request
+            .get(tlv::IDENTIFIER)
+            .ok_or_else(|| anyhow::anyhow!("Missing identifier"))?;
+        let controller_id = String::from_utf8(controller_id.to_vec())?;
+
+        self.store.remove_pairing(&controller_id).await?;
+        let no_admin_left = {
+            let mut pairings = self.pairings();
+            pairings.retain(|p| p.controller_id != controller_id);
+            !pairings.iter().any(|p| p.admin)
+        };
+
+        let mut removed = vec![controller_id.clone()];
+
+        //without admin nobody could manage the bridge anymore
+        if no_admin_left {
+            self.store.remove_all_pairings().await?;
+            removed.extend(self.pairings().drain(..).map(|p| p.controller_id));
+        }
+
+        tracing::info!("Removed HomeKit controller pairing {}", controller_id);
+        self.paired_tx.send_replace(self.is_paired());
+
+        //no receiver means no open connection
+        for controller_id in removed {
+            let _ = self.removed_tx.send(controller_id);
+        }
+
+        Ok(Tlv::new().with(tlv::STATE, [2]))
+    }
+
+    fn list_pairings(&self) -> Tlv {
+        let mut response = Tlv::new().with(tlv::STATE, [2]);
+
+        for (i, pairing) in self.pairings().iter().enumerate() {
+            if i > 0 {
+                response = response.with(tlv::SEPARATOR, []);
+            }
+
+            response = response
+                .with(tlv::IDENTIFIER, pairing.controller_id.as_bytes())
+                .with(tlv::PUBLIC_KEY, &pairing.public_key)
+                .with(tlv::PERMISSIONS, [u8::from(pairing.admin)]);
+        }
+
+        response
+    }
+
+    async fn add_pairing(&self, pairing: Pairing) -> anyhow::Result<()> {
+        self.store.save_pairing(&pairing).await?;
+
+        {
+            let mut pairings = self.pairings();
+            match pairings.iter_mut().find(|p| p.controller_id == pairing.controller_id) {
+                Some(existing) => existing.admin = pairing.admin,
+                None => pairings.push(pairing),
+            }
+        }
+
+        self.paired_tx.send_replace(true);
+        Ok(())
+    }
+
+    fn pairings(&self) -> MutexGuard<'_, Vec<Pairing>> {
+        lock_ignoring_poison(&self.pairings)
+    }
+
+    fn setup(&self) -> MutexGuard<'_, Option<PairSetup>> {
+        lock_ignoring_poison(&self.setup)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ring::signature::{Ed25519KeyPair, KeyPair as _};
+    use sqlx::PgPool;
+
+    use super::*;
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn paired_controller_is_verified(db_pool: PgPool) -> anyhow::Result<()> {
+        let store = HapStore::new(db_pool);
+        let identity = store.load_or_create_identity().await?;
+        let accessory_ltpk = identity.public_key().to_vec();
+
+        let rng = SystemRandom::new();
+        let controller_key = Ed25519KeyPair::from_pkcs8(
+            Ed25519KeyPair::generate_pkcs8(&rng)
+                .map_err(|_| anyhow::anyhow!("keygen"))?
+                .as_ref(),
+        )
+        .map_err(|_| anyhow::anyhow!("key"))?;
+        store
+            .save_pairing(&Pairing {
+                controller_id: "iphone".to_string(),
+                public_key: controller_key.public_key().as_ref().to_vec(),
+                admin: true,
+            })
+            .await?;
+
+        let manager = PairingManager::new(identity, "031-45-154".to_string(), store).await?;
+        let mut state = None;
+
+        //M1
+        let controller_ephemeral =
+            EphemeralPrivateKey::generate(&X25519, &rng).map_err(|_| anyhow::anyhow!("ephemeral"))?;
+        let controller_public = controller_ephemeral
+            .compute_public_key()
+            .map_err(|_| anyhow::anyhow!("public"))?
+            .as_ref()
+            .to_vec();
+        let m1 = Tlv::new()
+            .with(tlv::STATE, [1])
+            .with(tlv::PUBLIC_KEY, &controller_public);
+        let (m2, _) = manager.pair_verify(&mut state, &m1.encode());
+
+        //M2: accessory signature must verify with its long-term key
+        let accessory_public = m2.get(tlv::PUBLIC_KEY).unwrap_or_default().to_vec();
+        let shared = agree_ephemeral(
+            controller_ephemeral,
+            &UnparsedPublicKey::new(&X25519, &accessory_public),
+            |secret| secret.to_vec(),
+        )
+        .map_err(|_| anyhow::anyhow!("agree"))?;
+        let session_key = crypto::hkdf_sha512(&shared, "Pair-Verify-Encrypt-Salt", "Pair-Verify-Encrypt-Info")?;
+        let m2_data = Tlv::decode(&crypto::open(
+            &session_key,
+            crypto::message_nonce("PV-Msg02"),
+            &[],
+            m2.get(tlv::ENCRYPTED_DATA).unwrap_or_default(),
+        )?)?;
+        let accessory_id = m2_data.get(tlv::IDENTIFIER).unwrap_or_default();
+        let info = [accessory_public.as_slice(), accessory_id, &controller_public].concat();
+        assert!(
+            SignaturePublicKey::new(&ED25519, &accessory_ltpk)
+                .verify(&info, m2_data.get(tlv::SIGNATURE).unwrap_or_default())
+                .is_ok()
+        );
+
+        //M3
+        let info = [controller_public.as_slice(), b"iphone", &accessory_public].concat();
+        let m3_data = Tlv::new()
+            .with(tlv::IDENTIFIER, b"iphone")
+            .with(tlv::SIGNATURE, controller_key.sign(&info).as_ref());
+        let m3 = Tlv::new().with(tlv::STATE, [3]).with(
+            tlv::ENCRYPTED_DATA,
+            crypto::seal(&session_key, crypto::message_nonce("PV-Msg03"), &[], &m3_data.encode())?,
+        );
+        let (m4, session) = manager.pair_verify(&mut state, &m3.encode());
+
+        assert_eq!(m4.get_u8(tlv::STATE), Some(4));
+        assert_eq!(m4.get(tlv::ERROR), None);
+        assert_eq!(session.map(|s| s.controller_id), Some("iphone".to_string()));
+
+        //pair setup is not possible anymore
+        let setup = manager.pair_setup(1, &Tlv::new().with(tlv::STATE, [1]).encode()).await;
+        assert_eq!(setup.get_u8(tlv::ERROR), Some(tlv::ERROR_UNAVAILABLE));
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn pair_setup_is_locked_after_max_failed_attempts(db_pool: PgPool) -> anyhow::Result<()> {
+        let store = HapStore::new(db_pool);
+        let identity = store.load_or_create_identity().await?;
+        for _ in 1..MAX_PAIR_SETUP_ATTEMPTS {
+            store.add_failed_pair_setup_attempt().await?;
+        }
+
+        let manager = PairingManager::new(identity, "031-45-154".to_string(), store.clone()).await?;
+        let m1 = Tlv::new().with(tlv::STATE, [1]).encode();
+
+        let m2 = manager.pair_setup(1, &m1).await;
+        assert_eq!(m2.get_u8(tlv::ERROR), None);
+
+        let m3 = Tlv::new()
+            .with(tlv::STATE, [3])
+            .with(tlv::PUBLIC_KEY, [7u8; 384])
+            .with(tlv::PROOF, [0u8; 64]);
+        let m4 = manager.pair_setup(1, &m3.encode()).await;
+        assert_eq!(m4.get_u8(tlv::ERROR), Some(tlv::ERROR_AUTHENTICATION));
+
+        let m2 = manager.pair_setup(1, &m1).await;
+        assert_eq!(m2.get_u8(tlv::ERROR), Some(tlv::ERROR_MAX_TRIES));
+        assert_eq!(store.get_failed_pair_setup_attempts().await?, MAX_PAIR_SETUP_ATTEMPTS);
+
+        store.reset_failed_pair_setup_attempts().await?;
+        let identity = store.load_or_create_identity().await?;
+        let manager = PairingManager::new(identity, "031-45-154".to_string(), store).await?;
+        let m2 = manager.pair_setup(1, &m1).await;
+        assert_eq!(m2.get_u8(tlv::ERROR), None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,365 @@
+use std::{
+    collections::HashSet,
+    sync::{
+        Arc, Mutex, MutexGuard,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::{
+    io::{AsyncReadExt as _, AsyncWriteExt as _},
+    net::{TcpListener, TcpStream},
+    sync::{
+        broadcast::{self, error::RecvError},
+        mpsc,
+    },
+};
+
+use super::{
+    crypto::SessionCipher,
+    database::{self, AccessoryDatabase, CharacteristicId},
+    http::{self, HttpRequest},
+    pairing::{PairVerify, PairingManager},
+};
+use crate::{core::sync::lock_ignoring_poison, frontends::homekit::HomekitEvent};
+
+#[derive(Debug, Clone)]
+pub struct CharacteristicChange {
+    pub id: CharacteristicId,
+    pub value: Value,
+    /// Connection that wrote the value, it doesn't get an event for it
+    pub origin: Option<u64>,
+}
+
+pub struct HapServerState {
+    pub pairing: PairingManager,
+    pub database: Mutex<AccessoryDatabase>,
+    pub changes: broadcast::Sender<CharacteristicChange>,
+    pub writes: mpsc::Sender<HomekitEvent>,
+    next_connection_id: AtomicU64,
+}
+
+impl HapServerState {
+    pub fn new(pairing: PairingManager, database: AccessoryDatabase, writes: mpsc::Sender<HomekitEvent>) -> Self {
+        let (changes, _) = broadcast::channel(256);
+
+        Self {
+            pairing,
+            database: Mutex::new(database),
+            changes,
+            writes,
+            next_connection_id: AtomicU64::new(1),
+        }
+    }
+
+    pub fn database(&self) -> MutexGuard<'_, AccessoryDatabase> {
+        lock_ignoring_poison(&self.database)
+    }
+}
+
+pub async fn serve(listener: TcpListener, state: Arc<HapServerState>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer)) => {
+                let connection_id = state.next_connection_id.fetch_add(1, Ordering::Relaxed);
+                tracing::debug!("HAP connection {} from {}", connection_id, peer);
+
+                let state = state.clone();
+                tokio::spawn(async move {
+                    let mut connection = Connection::new(connection_id, state.clone());
+                    if let Err(e) = connection.run(stream).await {
+                        tracing::debug!("HAP connection {} closed with error: {:?}", connection_id, e);
+                    }
+                    state.pairing.abort_setup(connection_id);
+                });
+            }
+            Err(e) => {
+                tracing::error!("Error accepting HAP connection: {:?}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }
+    }
+}
+
+struct Connection {
+    id: u64,
+    state: Arc<HapServerState>,
+    verify: Option<PairVerify>,
+    controller_id: Option<String>,
+    cipher: Option<SessionCipher>,
+    subscriptions: HashSet<CharacteristicId>,
+}
+
+impl Connection {
+    fn new(id: u64, state: Arc<HapServerState>) -> Self {
+        Self {
+            id,
+            state,
+            verify: None,
+            controller_id: None,
+            cipher: None,
+            subscriptions: HashSet::new(),
+        }
+    }
+
+    async fn run(&mut self, mut stream: TcpStream) -> anyhow::Result<()> {
+        let mut changes = self.state.changes.subscribe();
+        let mut removed_pairings = self.state.pairing.subscribe_removed();
+        let mut raw = Vec::new();
+        let mut plaintext = Vec::new();
+
+        loop {
+            tokio::select! {
+                read = stream.read_buf(&mut raw) => {
+                    if read? == 0 {
+                        return Ok(());
+                    }
+
+                    match self.cipher.as_mut() {
+                        Some(cipher) => plaintext.extend(cipher.decrypt(&mut raw)?),
+                        None => plaintext.append(&mut raw),
+                    }
+
+                    while let Some(request) = http::parse_request(&mut plaintext)? {
+                        let (response, session) = self.handle(request).await;
+                        self.write(&mut stream, &response).await?;
+
+                        //encryption starts with the first message after pair verify
+                        if let Some(session) = session {
+                            tracing::info!("HomeKit controller {} connected", session.controller_id);
+                            self.controller_id = Some(session.controller_id);
+                            self.cipher = Some(session.cipher);
+                        }
+                    }
+                },
+
+                removed = removed_pairings.recv() => {
+                    let Some(controller_id) = &self.controller_id else {
+                        continue;
+                    };
+
+                    let session_removed = match removed {
+                        Ok(removed) => removed == *controller_id,
+                        Err(RecvError::Lagged(_)) => self.state.pairing.is_admin(controller_id).is_none(),
+                        Err(RecvError::Closed) => return Ok(()),
+                    };
+
+                    //the response to the remove request was already written in the read branch
+                    if session_removed {
+                        anyhow::bail!("Pairing of controller {} was removed", controller_id);
+                    }
+                },
+
+                change = changes.recv() => match change {
+                    Ok(change) => {
+                        if self.cipher.is_some()
+                            && self.subscriptions.contains(&change.id)
+                            && change.origin != Some(self.id)
+                        {
+                            let body = json!({ "characteristics": [{ "aid": change.id.0, "iid": change.id.1, "value": change.value }] });
+                            self.write(&mut stream, &http::event(body.to_string().as_bytes())).await?;
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!("HAP connection {} skipped {} events", self.id, skipped);
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                }
+            }
+        }
+    }
+
+    async fn write(&mut self, stream: &mut TcpStream, data: &[u8]) -> anyhow::Result<()> {
+        match self.cipher.as_mut() {
+            Some(cipher) => stream.write_all(&cipher.encrypt(data)?).await?,
+            None => stream.write_all(data).await?,
+        }
+        Ok(())
+    }
+
+    async fn handle(&mut self, request: HttpRequest) -> (Vec<u8>, Option<super::pairing::VerifiedSession>) {
+        tracing::trace!("HAP request {} {}", request.method, request.path);
+
+        match (request.method.as_str(), request.path.as_str()) {
+            ("POST", "/pair-setup") => {
+                let response = self.state.pairing.pair_setup(self.id, &request.body).await;
+                (tlv_response(response), None)
+            }
+            ("POST", "/pair-verify") => {
+                let (response, session) = self.state.pairing.pair_verify(&mut self.verify, &request.body);
+                (tlv_response(response), session)
+            }
+            ("POST", "/identify") => {
+                if self.state.pairing.is_paired() {
+                    (json_response(400, &json!({ "status": -70401 })), None)
+                } else {
+                    tracing::info!("HomeKit identify requested");
+                    (http::response(204, http::CONTENT_TYPE_JSON, &[]), None)
+                }
+            }
+            _ => {
+                let Some(controller_id) = self.controller_id.clone() else {
+                    return (json_response(470, &json!({ "status": -70401 })), None);
+                };
+
+                let response = match (request.method.as_str(), request.path.as_str()) {
+                    ("POST", "/pairings") => {
+                        tlv_response(self.state.pairing.manage_pairings(&controller_id, &request.body).await)
+                    }
+                    ("GET", "/accessories") => json_response(200, &self.state.database().to_json(true)),
+                    ("GET", "/characteristics") => self.read_characteristics(&request),
+                    ("PUT", "/characteristics") => self.write_characteristics(&request).await,
+                    _ => http::response(404, http::CONTENT_TYPE_JSON, &[]),
+                };
+
+                (response, None)
+            }
+        }
+    }
+
+    fn read_characteristics(&self, request: &HttpRequest) -> Vec<u8> {
+        let ids = request.query_param("id").map(parse_ids).unwrap_or_default();
+        if ids.is_empty() {
+            return json_response(400, &json!({ "status": database::STATUS_INVALID_VALUE }));
+        }
+
+        let with_meta = request.query_flag("meta") || request.query_flag("perms") || request.query_flag("type");
+        let with_ev = request.query_flag("ev");
+        let db = self.state.database();
+
+        let mut all_ok = true;
+        let characteristics: Vec<Value> = ids
+            .iter()
+            .map(|id| {
+                let mut entry = match (db.read(*id), db.characteristic_json(*id, with_meta, true)) {
+                    (Ok(_), Some(json)) => json,
+                    (result, _) => {
+                        all_ok = false;
+                        json!({ "iid": id.1, "status": result.err().unwrap_or(database::STATUS_NOT_FOUND) })
+                    }
+                };
+
+                entry["aid"] = json!(id.0);
+                if with_ev {
+                    entry["ev"] = json!(self.subscriptions.contains(id));
+                }
+                entry
+            })
+            .collect();
+
+        //status must be present on every entry of a multi-status response
+        let characteristics = if all_ok {
+            characteristics
+        } else {
+            characteristics
+                .into_iter()
+                .map(|mut entry| {
+                    if entry.get("status").is_none() {
+                        entry["status"] = json!(database::STATUS_SUCCESS);
+                    }
+                    entry
+                })
+                .collect()
+        };
+
+        json_response(
+            if all_ok { 200 } else { 207 },
+            &json!({ "characteristics": characteristics }),
+        )
+    }
+
+    async fn write_characteristics(&mut self, request: &HttpRequest) -> Vec<u8> {
+        #[derive(Deserialize)]
+        struct WriteRequest {
+            characteristics: Vec<CharacteristicWrite>,
+        }
+
+        #[derive(Deserialize)]
+        struct CharacteristicWrite {
+            aid: u64,
+            iid: u64,
+            value: Option<Value>,
+            ev: Option<bool>,
+        }
+
+        let write_request: WriteRequest = match serde_json::from_slice(&request.body) {
+            Ok(r) => r,
+            Err(e) => {
+                tracing::warn!("Invalid HAP characteristic write: {:?}", e);
+                return json_response(400, &json!({ "status": database::STATUS_INVALID_VALUE }));
+            }
+        };
+
+        let mut statuses = Vec::new();
+        let mut forwarded = Vec::new();
+
+        for write in write_request.characteristics {
+            let id = (write.aid, write.iid);
+            let mut status = database::STATUS_SUCCESS;
+
+            if let Some(ev) = write.ev {
+                match self.state.database().supports_events(id) {
+                    Ok(()) if ev => {
+                        self.subscriptions.insert(id);
+                    }
+                    Ok(()) => {
+                        self.subscriptions.remove(&id);
+                    }
+                    Err(e) => status = e,
+                }
+            }
+
+            if let Some(value) = write.value {
+                let written = self.state.database().write(id, &value);
+                match written {
+                    Ok(Some(event)) => {
+                        let _ = self.state.changes.send(CharacteristicChange {
+                            id,
+                            value: event.value.clone(),
+                            origin: Some(self.id),
+                        });
+                        forwarded.push(event);
+                    }
+                    Ok(None) => {
+                        tracing::info!("HomeKit identify requested for accessory {}", write.aid);
+                    }
+                    Err(e) => status = e,
+                }
+            }
+
+            statuses.push(json!({ "aid": write.aid, "iid": write.iid, "status": status }));
+        }
+
+        for event in forwarded {
+            if let Err(e) = self.state.writes.send_timeout(event, Duration::from_secs(5)).await {
+                tracing::error!("Error forwarding HomeKit write: {:?}", e);
+            }
+        }
+
+        if statuses.iter().all(|s| s["status"] == json!(database::STATUS_SUCCESS)) {
+            http::response(204, http::CONTENT_TYPE_JSON, &[])
+        } else {
+            json_response(207, &json!({ "characteristics": statuses }))
+        }
+    }
+}
+
+fn parse_ids(ids: &str) -> Vec<CharacteristicId> {
+    ids.split(',')
+        .filter_map(|id| {
+            let (aid, iid) = id.split_once('.')?;
+            Some((aid.parse().ok()?, iid.parse().ok()?))
+        })
+        .collect()
+}
+
+fn tlv_response(tlv: super::tlv::Tlv) -> Vec<u8> {
+    http::response(200, http::CONTENT_TYPE_TLV8, &tlv.encode())
+}
+
+fn json_response(status: u16, body: &Value) -> Vec<u8> {
+    http::response(status, http::CONTENT_TYPE_JSON, body.to_string().as_bytes())
+}
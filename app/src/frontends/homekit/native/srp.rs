@@ -0,0 +1,191 @@
+use num_bigint_dig::BigUint;
+use ring::digest::{Context, SHA512, digest};
+
+//SRP-6a with the 3072-bit group of RFC 5054, generator 5 and SHA-512, as required by HAP.
+//Numbers are padded to the size of N where the HAP spec hashes them.
+
+const N_HEX: &str = concat!(
+    "FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD1",
+    "29024E088A67CC74020BBEA63B139B22514A08798E3404DD",
+    "EF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245",
+    "E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED",
+    "EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3D",
+    "C2007CB8A163BF0598DA48361C55D39A69163FA8FD24CF5F",
+    "83655D23DCA3AD961C62F356208552BB9ED529077096966D",
+    "670C354E4ABC9804F1746C08CA18217C32905E462E36CE3B",
+    "E39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9",
+    "DE2BCBF6955817183995497CEA956AE515D2261898FA0510",
+    "15728E5A8AAAC42DAD33170D04507A33A85521ABDF1CBA64",
+    "ECFB850458DBEF0A8AEA71575D060C7DB3970F85A6E1E4C7",
+    "ABF5AE8CDB0933D71E8C94E04A25619DCEE3D2261AD2EE6B",
+    "F12FFA06D98A0864D87602733EC86A64521F2B18177B200C",
+    "BBE117577A615D6C770988C0BAD946E208E24FA074E5AB31",
+    "43DB5BFCE0FD108E4B82D120A93AD2CAFFFFFFFFFFFFFFFF",
+);
+const G: u32 = 5;
+const N_LEN: usize = 384;
+
+const USERNAME: &[u8] = b"Pair-Setup";
+
+pub struct SrpServer {
+    n: BigUint,
+    g: BigUint,
+    salt: [u8; 16],
+    verifier: BigUint,
+    b: BigUint,
+    public_b: BigUint,
+}
+
+pub struct SrpSession {
+    /// Shared session key K, input for all HKDF derivations of pair setup
+    pub key: Vec<u8>,
+    /// Server proof M2 to return to the controller
+    pub server_proof: Vec<u8>,
+}
+
+impl SrpServer {
+    /// `private_b` must be random, at least 32 bytes
+    pub fn new(setup_code: &str, salt: [u8; 16], private_b: &[u8]) -> Self {
+        let n = modulus();
+        let g = BigUint::from(G);
+
+        let identity_hash = digest(&SHA512, &[USERNAME, b":", setup_code.as_bytes()].concat());
+        let x = BigUint::from_bytes_be(digest(&SHA512, &[&salt[..], identity_hash.as_ref()].concat()).as_ref());
+        let verifier = g.modpow(&x, &n);
+
+        let b = BigUint::from_bytes_be(private_b);
+        let k = multiplier(&n, &g);
+        let public_b = (k * &verifier + g.modpow(&b, &n)) % &n;
+
+        Self {
+            n,
+            g,
+            salt,
+            verifier,
+            b,
+            public_b,
+        }
+    }
+
+    pub fn salt(&self) -> &[u8] {
+        &self.salt
+    }
+
+    pub fn public_key(&self) -> Vec<u8> {
+        pad(&self.public_b)
+    }
+
+    /// Verifies the client proof M1 and returns the session key and server proof
+    pub fn verify(&self, client_public_key: &[u8], client_proof: &[u8]) -> anyhow::Result<SrpSession> {
+        let a = BigUint::from_bytes_be(client_public_key);
+        if (&a % &self.n) == BigUint::from(0u32) {
+            anyhow::bail!("Invalid SRP client public key");
+        }
+
+        let padded_a = pad(&a);
+        let padded_b = pad(&self.public_b);
+
+        let u = BigUint::from_bytes_be(digest(&SHA512, &[padded_a.as_slice(), padded_b.as_slice()].concat()).as_ref());
+        let s = (&a * self.verifier.modpow(&u, &self.n)).modpow(&self.b, &self.n);
+        let key = digest(&SHA512, &pad(&s)).as_ref().to_vec();
+
+        let expected_proof = client_proof_of(&self.n, &self.g, &self.salt, &padded_a, &padded_b, &key);
+        if !constant_time_eq(&expected_proof, client_proof) {
+            anyhow::bail!("SRP client proof does not match");
+        }
+
+        let server_proof = digest(&SHA512, &[padded_a.as_slice(), &expected_proof, &key].concat())
+            .as_ref()
+            .to_vec();
+
+        Ok(SrpSession { key, server_proof })
+    }
+}
+
+#[allow(clippy::expect_used)]
+fn modulus() -> BigUint {
+    BigUint::parse_bytes(N_HEX.as_bytes(), 16).expect("Invalid SRP modulus")
+}
+
+fn multiplier(n: &BigUint, g: &BigUint) -> BigUint {
+    BigUint::from_bytes_be(digest(&SHA512, &[pad(n), pad(g)].concat()).as_ref())
+}
+
+//M1 = H(H(N) xor H(g) | H(I) | s | A | B | K)
+fn client_proof_of(n: &BigUint, g: &BigUint, salt: &[u8], padded_a: &[u8], padded_b: &[u8], key: &[u8]) -> Vec<u8> {
+    let hash_n = digest(&SHA512, &n.to_bytes_be());
+    let hash_g = digest(&SHA512, &g.to_bytes_be());
+    let group_hash: Vec<u8> = hash_n
+        .as_ref()
+        .iter()
+        .zip(hash_g.as_ref())
+        .map(|(a, b)| a ^ b)
+        .collect();
+
+    let mut ctx = Context::new(&SHA512);
+    ctx.update(&group_hash);
+    ctx.update(digest(&SHA512, USERNAME).as_ref());
+    ctx.update(salt);
+    ctx.update(padded_a);
+    ctx.update(padded_b);
+    ctx.update(key);
+    ctx.finish().as_ref().to_vec()
+}
+
+fn pad(value: &BigUint) -> Vec<u8> {
+    let bytes = value.to_bytes_be();
+    let mut padded = vec![0u8; N_LEN.saturating_sub(bytes.len())];
+    padded.extend_from_slice(&bytes);
+    padded
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //Client side of the exchange, as done by the iOS controller
+    fn client(setup_code: &str, salt: &[u8], server_public_key: &[u8]) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        let n = modulus();
+        let g = BigUint::from(G);
+        let a = BigUint::from_bytes_be(&[0x42; 32]);
+        let public_a = g.modpow(&a, &n);
+
+        let identity_hash = digest(&SHA512, &[USERNAME, b":", setup_code.as_bytes()].concat());
+        let x = BigUint::from_bytes_be(digest(&SHA512, &[salt, identity_hash.as_ref()].concat()).as_ref());
+        let b = BigUint::from_bytes_be(server_public_key);
+        let u = BigUint::from_bytes_be(digest(&SHA512, &[pad(&public_a), pad(&b)].concat()).as_ref());
+        let k = multiplier(&n, &g);
+
+        //S = (B - k * g^x) ^ (a + u * x) mod N
+        let kgx = (k * g.modpow(&x, &n)) % &n;
+        let base = (&b + &n - kgx) % &n;
+        let s = base.modpow(&(&a + &u * &x), &n);
+        let key = digest(&SHA512, &pad(&s)).as_ref().to_vec();
+
+        let proof = client_proof_of(&n, &g, salt, &pad(&public_a), &pad(&b), &key);
+        (pad(&public_a), proof, key)
+    }
+
+    #[test]
+    fn client_with_setup_code_is_accepted() -> anyhow::Result<()> {
+        let server = SrpServer::new("123-45-678", [3; 16], &[0x17; 32]);
+
+        let (public_a, proof, key) = client("123-45-678", server.salt(), &server.public_key());
+        let session = server.verify(&public_a, &proof)?;
+
+        assert_eq!(session.key, key);
+        assert_eq!(
+            session.server_proof,
+            digest(&SHA512, &[public_a.as_slice(), &proof, &key].concat()).as_ref()
+        );
+
+        let (public_a, proof, _) = client("111-22-333", server.salt(), &server.public_key());
+        assert!(server.verify(&public_a, &proof).is_err());
+
+        Ok(())
+    }
+}
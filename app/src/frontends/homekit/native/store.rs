@@ -0,0 +1,199 @@
+use anyhow::{Context as _, Result};
+use ring::{
+    rand::{SecureRandom, SystemRandom},
+    signature::{Ed25519KeyPair, KeyPair as _},
+};
+use sqlx::PgPool;
+
+/// Long-term identity of the bridge. Changing it makes all controllers lose the pairing.
+pub struct AccessoryIdentity {
+    pub device_id: String,
+    pub key_pair: Ed25519KeyPair,
+}
+
+impl AccessoryIdentity {
+    pub fn public_key(&self) -> &[u8] {
+        self.key_pair.public_key().as_ref()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pairing {
+    pub controller_id: String,
+    pub public_key: Vec<u8>,
+    pub admin: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct HapStore {
+    pool: PgPool,
+}
+
+impl HapStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn load_or_create_identity(&self) -> Result<AccessoryIdentity> {
+        let row = sqlx::query!(r#"SELECT device_id, signing_key FROM hap_accessory_identity WHERE id = 1"#)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        if let Some(row) = row {
+            let key_pair = Ed25519KeyPair::from_pkcs8(&row.signing_key)
+                .map_err(|e| anyhow::anyhow!("Error loading HAP accessory key: {}", e))?;
+
+            return Ok(AccessoryIdentity {
+                device_id: row.device_id,
+                key_pair,
+            });
+        }
+
+        let rng = SystemRandom::new();
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).map_err(|_| anyhow::anyhow!("Error generating HAP key"))?;
+        let mut id_bytes = [0u8; 6];
+        rng.fill(&mut id_bytes)
+            .map_err(|_| anyhow::anyhow!("Error generating HAP device id"))?;
+
+        let device_id = id_bytes
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<_>>()
+            .join(":");
+
+        sqlx::query!(
+            r#"INSERT INTO hap_accessory_identity (id, device_id, signing_key) VALUES (1, $1, $2)"#,
+            device_id,
+            pkcs8.as_ref(),
+        )
+        .execute(&self.pool)
+        .await
+        .context("Error saving HAP accessory identity")?;
+
+        tracing::info!("Created new HAP accessory identity {}", device_id);
+
+        let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref())
+            .map_err(|e| anyhow::anyhow!("Error loading HAP accessory key: {}", e))?;
+
+        Ok(AccessoryIdentity { device_id, key_pair })
+    }
+
+    pub async fn get_failed_pair_setup_attempts(&self) -> Result<u32> {
+        let attempts =
+            sqlx::query_scalar!(r#"SELECT failed_pair_setup_attempts FROM hap_accessory_identity WHERE id = 1"#)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(attempts.unwrap_or_default().try_into()?)
+    }
+
+    /// Returns the number of failed attempts including this one
+    pub async fn add_failed_pair_setup_attempt(&self) -> Result<u32> {
+        let attempts = sqlx::query_scalar!(
+            r#"UPDATE hap_accessory_identity SET failed_pair_setup_attempts = failed_pair_setup_attempts + 1
+               WHERE id = 1
+               RETURNING failed_pair_setup_attempts"#
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Error saving failed HAP pair setup attempt")?;
+
+        Ok(attempts.try_into()?)
+    }
+
+    pub async fn reset_failed_pair_setup_attempts(&self) -> Result<()> {
+        sqlx::query!(r#"UPDATE hap_accessory_identity SET failed_pair_setup_attempts = 0 WHERE id = 1"#)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .context("Error resetting failed HAP pair setup attempts")
+    }
+
+    pub async fn get_pairings(&self) -> Result<Vec<Pairing>> {
+        let records = sqlx::query!(
+            r#"SELECT controller_id, public_key, admin FROM hap_pairing ORDER BY created_at, controller_id"#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|row| Pairing {
+                controller_id: row.controller_id,
+                public_key: row.public_key,
+                admin: row.admin,
+            })
+            .collect())
+    }
+
+    #[tracing::instrument(skip(self, pairing), fields(controller_id = %pairing.controller_id))]
+    pub async fn save_pairing(&self, pairing: &Pairing) -> Result<()> {
+        sqlx::query!(
+            r#"INSERT INTO hap_pairing (controller_id, public_key, admin) VALUES ($1, $2, $3)
+               ON CONFLICT (controller_id) DO UPDATE SET admin = EXCLUDED.admin"#,
+            pairing.controller_id,
+            pairing.public_key,
+            pairing.admin,
+        )
+        .execute(&self.pool)
+        .await
+        .map(|_| ())
+        .context("Error saving HAP pairing")
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn remove_pairing(&self, controller_id: &str) -> Result<()> {
+        sqlx::query!(r#"DELETE FROM hap_pairing WHERE controller_id = $1"#, controller_id)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .context("Error removing HAP pairing")
+    }
+
+    pub async fn remove_all_pairings(&self) -> Result<()> {
+        sqlx::query!(r#"DELETE FROM hap_pairing"#)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .context("Error removing HAP pairings")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn identity_is_created_once(db_pool: PgPool) -> anyhow::Result<()> {
+        let store = HapStore::new(db_pool);
+
+        let created = store.load_or_create_identity().await?;
+        let loaded = store.load_or_create_identity().await?;
+
+        assert_eq!(created.device_id, loaded.device_id);
+        assert_eq!(created.public_key(), loaded.public_key());
+        assert_eq!(created.device_id.len(), 17);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn pairing_permissions_are_updated(db_pool: PgPool) -> anyhow::Result<()> {
+        let store = HapStore::new(db_pool);
+        let mut pairing = Pairing {
+            controller_id: "controller-1".to_string(),
+            public_key: vec![1; 32],
+            admin: true,
+        };
+
+        store.save_pairing(&pairing).await?;
+        pairing.admin = false;
+        store.save_pairing(&pairing).await?;
+        assert_eq!(store.get_pairings().await?, vec![pairing]);
+
+        store.remove_pairing("controller-1").await?;
+        assert!(store.get_pairings().await?.is_empty());
+
+        Ok(())
+    }
+}
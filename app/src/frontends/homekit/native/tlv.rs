@@ -0,0 +1,121 @@
+//TLV8 encoding used by the pairing endpoints. Values longer than 255 bytes are split into
+//consecutive items of the same type.
+
+pub const METHOD: u8 = 0x00;
+pub const IDENTIFIER: u8 = 0x01;
+pub const SALT: u8 = 0x02;
+pub const PUBLIC_KEY: u8 = 0x03;
+pub const PROOF: u8 = 0x04;
+pub const ENCRYPTED_DATA: u8 = 0x05;
+pub const STATE: u8 = 0x06;
+pub const ERROR: u8 = 0x07;
+pub const SIGNATURE: u8 = 0x0A;
+pub const PERMISSIONS: u8 = 0x0B;
+pub const SEPARATOR: u8 = 0xFF;
+
+pub const ERROR_UNKNOWN: u8 = 0x01;
+pub const ERROR_AUTHENTICATION: u8 = 0x02;
+pub const ERROR_MAX_PEERS: u8 = 0x04;
+pub const ERROR_MAX_TRIES: u8 = 0x05;
+pub const ERROR_UNAVAILABLE: u8 = 0x06;
+pub const ERROR_BUSY: u8 = 0x07;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tlv {
+    items: Vec<(u8, Vec<u8>)>,
+}
+
+impl Tlv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, tlv_type: u8, value: impl AsRef<[u8]>) -> Self {
+        self.items.push((tlv_type, value.as_ref().to_vec()));
+        self
+    }
+
+    pub fn error(state: u8, error: u8) -> Self {
+        Self::new().with(STATE, [state]).with(ERROR, [error])
+    }
+
+    pub fn get(&self, tlv_type: u8) -> Option<&[u8]> {
+        self.items
+            .iter()
+            .find(|(t, _)| *t == tlv_type)
+            .map(|(_, value)| value.as_slice())
+    }
+
+    pub fn get_u8(&self, tlv_type: u8) -> Option<u8> {
+        self.get(tlv_type).and_then(|value| value.first().copied())
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+
+        for (tlv_type, value) in &self.items {
+            if value.is_empty() {
+                out.extend_from_slice(&[*tlv_type, 0]);
+                continue;
+            }
+
+            for chunk in value.chunks(255) {
+                out.push(*tlv_type);
+                out.push(chunk.len() as u8);
+                out.extend_from_slice(chunk);
+            }
+        }
+
+        out
+    }
+
+    pub fn decode(data: &[u8]) -> anyhow::Result<Self> {
+        let mut items: Vec<(u8, Vec<u8>)> = Vec::new();
+        let mut pos = 0;
+        let mut last_len = 0;
+
+        while pos < data.len() {
+            let Some(&[tlv_type, len]) = data.get(pos..pos + 2) else {
+                anyhow::bail!("Truncated TLV header at {}", pos);
+            };
+            let len = len as usize;
+            let Some(value) = data.get(pos + 2..pos + 2 + len) else {
+                anyhow::bail!("Truncated TLV value of type {} at {}", tlv_type, pos);
+            };
+
+            //continuation of a fragmented value
+            match items.last_mut() {
+                Some((last_type, last_value)) if *last_type == tlv_type && last_len == 255 => {
+                    last_value.extend_from_slice(value);
+                }
+                _ => items.push((tlv_type, value.to_vec())),
+            }
+
+            last_len = len;
+            pos += 2 + len;
+        }
+
+        Ok(Self { items })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_values_are_fragmented_and_joined() -> anyhow::Result<()> {
+        let key = vec![7u8; 384];
+        let tlv = Tlv::new().with(STATE, [2]).with(PUBLIC_KEY, &key).with(SALT, [1u8; 16]);
+
+        let encoded = tlv.encode();
+        assert_eq!(encoded.len(), 3 + (2 + 255) + (2 + 129) + 18);
+
+        let decoded = Tlv::decode(&encoded)?;
+        assert_eq!(decoded.get_u8(STATE), Some(2));
+        assert_eq!(decoded.get(PUBLIC_KEY), Some(key.as_slice()));
+        assert_eq!(decoded, tlv);
+
+        Ok(())
+    }
+}
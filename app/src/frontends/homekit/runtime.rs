@@ -2,67 +2,143 @@ use std::collections::HashMap;
 
 use infrastructure::{EventListener, MqttInMessage, MqttSender, MqttSubscription};
 use serde::{Deserialize, Serialize};
-use tokio::task::JoinHandle;
+use tokio::{sync::watch, task::JoinHandle};
 
 use super::{
     HomekitEvent, HomekitService, HomekitTarget, HomekitTargetConfig, accessory::HomekitRegistry,
-    hap::HomekitCharacteristic,
+    hap::HomekitCharacteristic, native::HapBridge,
 };
 use crate::{
+    automation::{AutomationSwitchClient, AutomationSwitches},
     core::timeseries::DataPoint,
     home_state::{HomeStateEvent, HomeStateValue},
     trigger::TriggerClient,
 };
 
+/// Connection to the Home app, either via the Homebridge MQTT plugin or the native HAP bridge
+pub enum HomekitTransport {
+    Homebridge {
+        mqtt_sender: MqttSender,
+        mqtt_receiver: MqttSubscription,
+    },
+    Native(HapBridge),
+}
+
 pub struct HomekitRunner {
     registry: HomekitRegistry,
     state_change_rx: EventListener<HomeStateEvent>,
-    mqtt_sender: MqttSender,
-    mqtt_receiver: MqttSubscription,
+    transport: HomekitTransport,
     trigger_client: TriggerClient,
     trigger_debounce: HashMap<HomekitTarget, JoinHandle<()>>,
+    switch_client: AutomationSwitchClient,
+    switch_rx: watch::Receiver<AutomationSwitches>,
+}
+
+impl HomekitTransport {
+    async fn recv(&mut self) -> Option<HomekitEvent> {
+        match self {
+            HomekitTransport::Homebridge { mqtt_receiver, .. } => loop {
+                let msg = mqtt_receiver.recv().await?;
+                if let Some(event) = Self::parse_mqtt_message(msg) {
+                    return Some(event);
+                }
+            },
+            HomekitTransport::Native(bridge) => bridge.recv().await,
+        }
+    }
+
+    fn parse_mqtt_message(msg: MqttInMessage) -> Option<HomekitEvent> {
+        //example
+        // {"name": "flex_lamp", "service_name": "light", "characteristic": "On", "value": true}
+        #[derive(Deserialize, Debug)]
+        struct IncomingMessage {
+            name: String,
+            #[serde(rename = "service_name")]
+            service: HomekitService,
+            characteristic: HomekitCharacteristic,
+            value: serde_json::Value,
+        }
+
+        let incoming: IncomingMessage = match serde_json::from_str(&msg.payload) {
+            Ok(msg) => msg,
+            Err(e) => {
+                tracing::error!("Error parsing incoming Homekit message: {:?} -- {:?}", msg.payload, e);
+                return None;
+            }
+        };
+
+        Some(HomekitEvent {
+            target: HomekitTarget::new(incoming.name, incoming.service, incoming.characteristic),
+            value: incoming.value,
+        })
+    }
 }
 
 impl HomekitRunner {
     pub fn new(
         registry: HomekitRegistry,
         state_change_rx: EventListener<HomeStateEvent>,
-        mqtt_sender: MqttSender,
-        mqtt_receiver: MqttSubscription,
+        transport: HomekitTransport,
         trigger_client: TriggerClient,
+        switch_client: AutomationSwitchClient,
     ) -> Self {
         Self {
             registry,
             state_change_rx,
-            mqtt_sender,
-            mqtt_receiver,
+            transport,
             trigger_client,
             trigger_debounce: HashMap::new(),
+            switch_rx: switch_client.subscribe(),
+            switch_client,
         }
     }
 
     pub async fn run(mut self) {
         self.register_accessory().await;
+        self.export_automation_switches().await;
 
         loop {
             tokio::select! {
-                event = self.mqtt_receiver.recv() => match event {
-                    Some(mqtt_msg) => {
-                        self.handle_mqtt_message(mqtt_msg).await;
+                event = self.transport.recv() => match event {
+                    Some(event) => {
+                        self.handle_homekit_event(event).await;
                     },
                     None => {
-                        tracing::error!("Homekit MQTT receiver channel closed");
+                        tracing::error!("Homekit receiver channel closed");
                     }
                 },
 
                 state_change = self.state_change_rx.recv() => if let Some(HomeStateEvent::Changed(state)) = state_change {
                     self.handle_state_change(state).await;
+                },
+
+                Ok(()) = self.switch_rx.changed() => {
+                    self.export_automation_switches().await;
                 }
             }
         }
     }
 
     async fn handle_state_change(&mut self, state: DataPoint<HomeStateValue>) {
+        let exports = self.registry.export_state(&state.value);
+        self.send_exports(exports).await;
+    }
+
+    async fn export_automation_switches(&mut self) {
+        let switches = self.switch_rx.borrow_and_update().clone();
+        let exports = self.registry.export_automation_switches(&switches);
+        self.send_exports(exports).await;
+    }
+
+    async fn send_exports(&self, exports: Vec<HomekitEvent>) {
+        let mqtt_sender = match &self.transport {
+            HomekitTransport::Homebridge { mqtt_sender, .. } => mqtt_sender,
+            HomekitTransport::Native(bridge) => {
+                bridge.export(exports);
+                return;
+            }
+        };
+
         //example
         // {"name": "flex_lamp", "service_name": "light", "characteristic": "On", "value": true}
         #[derive(Debug, Serialize)]
@@ -74,9 +150,7 @@ impl HomekitRunner {
             value: serde_json::Value,
         }
 
-        let exports = self
-            .registry
-            .export_state(&state.value)
+        let exports = exports
             .into_iter()
             .map(|export| OutgoingMessage {
                 name: export.target.name,
@@ -96,38 +170,22 @@ impl HomekitRunner {
                 }
             };
 
-            if let Err(e) = self.mqtt_sender.send_transient(topic, payload).await {
+            if let Err(e) = mqtt_sender.send_transient(topic, payload).await {
                 tracing::error!("Error sending MQTT message to Homekit: {} -- {:?}", topic, e);
             }
         }
     }
 
-    async fn handle_mqtt_message(&mut self, msg: MqttInMessage) {
-        //example
-        // {"name": "flex_lamp", "service_name": "light", "characteristic": "On", "value": true}
-        #[derive(Deserialize, Debug)]
-        struct IncomingMessage {
-            name: String,
-            #[serde(rename = "service_name")]
-            service: HomekitService,
-            characteristic: HomekitCharacteristic,
-            value: serde_json::Value,
-        }
+    async fn handle_homekit_event(&mut self, state: HomekitEvent) {
+        tracing::debug!("Processing Homekit event: {:?}", state);
 
-        let incoming: IncomingMessage = match serde_json::from_str(&msg.payload) {
-            Ok(msg) => msg,
-            Err(e) => {
-                tracing::error!("Error parsing incoming Homekit message: {:?} -- {:?}", msg.payload, e);
-                return;
+        if let Some((target, enabled)) = self.registry.process_automation_toggle(&state) {
+            tracing::info!("Received Homekit automation switch: {} enabled={}", target, enabled);
+            if let Err(e) = self.switch_client.set_enabled(target, enabled).await {
+                tracing::error!("Error processing Homekit automation switch: {:?}", e);
             }
-        };
-
-        let state = HomekitEvent {
-            target: HomekitTarget::new(incoming.name, incoming.service, incoming.characteristic),
-            value: incoming.value,
-        };
-
-        tracing::debug!("Processing Homekit MQTT event: {:?}", state);
+            return;
+        }
 
         if let Some(trigger) = self.registry.process_trigger(&state) {
             if let Some(handle) = self.trigger_debounce.get(&state.target) {
@@ -150,8 +208,13 @@ impl HomekitRunner {
         }
     }
 
+    //the native bridge builds its accessory database on start
     async fn register_accessory(&mut self) {
-        let bootstrap_data = self.get_bootstrap_data();
+        let HomekitTransport::Homebridge { mqtt_sender, .. } = &self.transport else {
+            return;
+        };
+
+        let bootstrap_data = Self::get_bootstrap_data(&self.registry);
 
         let mut already_registered: Vec<String> = vec![];
 
@@ -168,14 +231,14 @@ impl HomekitRunner {
 
             let payload = Self::service_registration_payload(name.clone(), service.clone(), &characteristics);
 
-            if let Err(e) = self.mqtt_sender.send_transient(topic, payload.to_string()).await {
+            if let Err(e) = mqtt_sender.send_transient(topic, payload.to_string()).await {
                 tracing::error!("Error sending MQTT message to Homekit: {} -- {:?}", topic, e);
             }
         }
     }
 
-    fn get_bootstrap_data(&mut self) -> HashMap<(String, HomekitService), Vec<HomekitTargetConfig>> {
-        self.registry
+    fn get_bootstrap_data(registry: &HomekitRegistry) -> HashMap<(String, HomekitService), Vec<HomekitTargetConfig>> {
+        registry
             .get_device_config()
             .into_iter()
             .map(|entry| {
@@ -1,3 +1,4 @@
 pub mod energy_meter;
+pub mod homeassistant;
 pub mod homekit;
 pub mod remote;
@@ -1,12 +1,19 @@
 mod config;
 
 use crate::{
-    core::DeviceConfig,
+    core::{DeviceConfig, ExpectedZ2mDevice},
     trigger::{DualButtonPress, RemoteTrigger, RemoteTriggerTarget},
 };
 use infrastructure::{Mqtt, MqttInMessage, MqttSubscription};
 use serde::Deserialize;
 
+pub fn expected_devices() -> Vec<ExpectedZ2mDevice> {
+    config::default_z2m_remote_config()
+        .into_iter()
+        .map(|(name, target)| ExpectedZ2mDevice::new(name, format!("remote {:?}", target), &["action"]))
+        .collect()
+}
+
 pub struct Z2mRemoteIncomingDataSource {
     device_config: DeviceConfig<RemoteTriggerTarget>,
     mqtt_receiver: MqttSubscription,
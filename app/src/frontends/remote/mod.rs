@@ -12,6 +12,11 @@ pub struct RemoteModule {
     z2m_ds: adapter::z2m::Z2mRemoteIncomingDataSource,
 }
 
+/// Zigbee2MQTT devices used as remotes
+pub fn expected_z2m_devices() -> Vec<crate::core::ExpectedZ2mDevice> {
+    adapter::z2m::expected_devices()
+}
+
 impl RemoteModule {
     pub async fn new(mqtt_client: &mut Mqtt, z2m_event_topic: &str, trigger_client: TriggerClient) -> Self {
         let z2m_ds = adapter::z2m::Z2mRemoteIncomingDataSource::new(mqtt_client, z2m_event_topic).await;
@@ -0,0 +1,64 @@
+use r#macro::{EnumVariants, Id};
+
+use crate::core::domain::Radiator;
+use crate::core::unit::Percent;
+use crate::home_state::calc::{DerivedStateProvider, StateCalculationContext};
+
+/// Batteries below this level should be replaced
+pub const LOW_BATTERY_LEVEL: Percent = Percent(20.0);
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, EnumVariants, Id)]
+pub enum BatteryLevel {
+    ApartmentDoorLock,
+    LivingRoomClimateSensor,
+    BedroomClimateSensor,
+    BedroomOuterWallClimateSensor,
+    RoomOfRequirementsClimateSensor,
+    BathroomClimateSensor,
+    KitchenClimateSensor,
+    KitchenOuterWallClimateSensor,
+    RadiatorClimateSensor(Radiator),
+    Thermostat(Radiator),
+    KitchenWindowSensor,
+    BedroomWindowSensor,
+    LivingRoomWindowLeftSensor,
+    LivingRoomWindowRightSensor,
+    LivingRoomWindowSideSensor,
+    LivingRoomBalconyDoorSensor,
+    RoomOfRequirementsWindowLeftSensor,
+    RoomOfRequirementsWindowRightSensor,
+    RoomOfRequirementsWindowSideSensor,
+}
+
+pub struct BatteryLevelStateProvider;
+
+impl DerivedStateProvider<BatteryLevel, Percent> for BatteryLevelStateProvider {
+    fn calculate_current(&self, id: BatteryLevel, ctx: &StateCalculationContext) -> Option<Percent> {
+        use crate::device_state::BatteryLevel as DeviceBatteryLevel;
+
+        ctx.device_state(match id {
+            BatteryLevel::ApartmentDoorLock => DeviceBatteryLevel::ApartmentDoorLock,
+            BatteryLevel::LivingRoomClimateSensor => DeviceBatteryLevel::LivingRoomClimateSensor,
+            BatteryLevel::BedroomClimateSensor => DeviceBatteryLevel::BedroomClimateSensor,
+            BatteryLevel::BedroomOuterWallClimateSensor => DeviceBatteryLevel::BedroomOuterWallClimateSensor,
+            BatteryLevel::RoomOfRequirementsClimateSensor => DeviceBatteryLevel::RoomOfRequirementsClimateSensor,
+            BatteryLevel::BathroomClimateSensor => DeviceBatteryLevel::BathroomClimateSensor,
+            BatteryLevel::KitchenClimateSensor => DeviceBatteryLevel::KitchenClimateSensor,
+            BatteryLevel::KitchenOuterWallClimateSensor => DeviceBatteryLevel::KitchenOuterWallClimateSensor,
+            BatteryLevel::RadiatorClimateSensor(radiator) => DeviceBatteryLevel::RadiatorClimateSensor(radiator),
+            BatteryLevel::Thermostat(radiator) => DeviceBatteryLevel::Thermostat(radiator),
+            BatteryLevel::KitchenWindowSensor => DeviceBatteryLevel::KitchenWindowSensor,
+            BatteryLevel::BedroomWindowSensor => DeviceBatteryLevel::BedroomWindowSensor,
+            BatteryLevel::LivingRoomWindowLeftSensor => DeviceBatteryLevel::LivingRoomWindowLeftSensor,
+            BatteryLevel::LivingRoomWindowRightSensor => DeviceBatteryLevel::LivingRoomWindowRightSensor,
+            BatteryLevel::LivingRoomWindowSideSensor => DeviceBatteryLevel::LivingRoomWindowSideSensor,
+            BatteryLevel::LivingRoomBalconyDoorSensor => DeviceBatteryLevel::LivingRoomBalconyDoorSensor,
+            BatteryLevel::RoomOfRequirementsWindowLeftSensor => DeviceBatteryLevel::RoomOfRequirementsWindowLeftSensor,
+            BatteryLevel::RoomOfRequirementsWindowRightSensor => {
+                DeviceBatteryLevel::RoomOfRequirementsWindowRightSensor
+            }
+            BatteryLevel::RoomOfRequirementsWindowSideSensor => DeviceBatteryLevel::RoomOfRequirementsWindowSideSensor,
+        })
+        .map(|dp| dp.value)
+    }
+}
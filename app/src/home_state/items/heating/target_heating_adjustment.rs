@@ -37,6 +37,30 @@ pub enum AdjustmentDirection {
     MustOff = -3,
 }
 
+impl AdjustmentDirection {
+    pub fn all() -> [AdjustmentDirection; 6] {
+        [
+            AdjustmentDirection::MustIncrease,
+            AdjustmentDirection::ShouldIncrease,
+            AdjustmentDirection::Hold,
+            AdjustmentDirection::ShouldDecrease,
+            AdjustmentDirection::MustDecrease,
+            AdjustmentDirection::MustOff,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdjustmentDirection::MustIncrease => "must_increase",
+            AdjustmentDirection::ShouldIncrease => "should_increase",
+            AdjustmentDirection::Hold => "hold",
+            AdjustmentDirection::ShouldDecrease => "should_decrease",
+            AdjustmentDirection::MustDecrease => "must_decrease",
+            AdjustmentDirection::MustOff => "must_off",
+        }
+    }
+}
+
 pub struct TargetHeatingAdjustmentStateProvider;
 
 impl DerivedStateProvider<TargetHeatingAdjustment, AdjustmentDirection> for TargetHeatingAdjustmentStateProvider {
@@ -31,6 +31,33 @@ pub enum HeatingMode {
     Manual(DegreeCelsius, UserTriggerId),
 }
 
+impl HeatingMode {
+    const MANUAL: &str = "manual";
+
+    /// Names of all modes, `Manual` is listed once regardless of its temperature
+    pub fn all_names() -> [&'static str; 6] {
+        [
+            HeatingMode::EnergySaving.as_str(),
+            HeatingMode::Comfort.as_str(),
+            HeatingMode::Sleep.as_str(),
+            HeatingMode::Ventilation.as_str(),
+            HeatingMode::Away.as_str(),
+            Self::MANUAL,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HeatingMode::EnergySaving => "energy_saving",
+            HeatingMode::Comfort => "comfort",
+            HeatingMode::Sleep => "sleep",
+            HeatingMode::Ventilation => "ventilation",
+            HeatingMode::Away => "away",
+            HeatingMode::Manual(_, _) => Self::MANUAL,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Id, EnumVariants)]
 pub enum TargetHeatingMode {
     HeatingZone(HeatingZone),
@@ -118,6 +145,7 @@ impl TargetHeatingModeStateProvider {
         if let Some(target_temperature) = target_temperature {
             return Some(UserHeatingOverride {
                 timestamp: user_trigger.timestamp,
+                expired: user_trigger.is_expired(&t!(1 hours)),
                 target_temperature,
                 trigger_id: user_trigger.id.clone(),
             });
@@ -147,7 +175,7 @@ fn calculate_heating_mode(
     }
 
     if let Some(user_override) = user_override {
-        if user_override.timestamp.elapsed() > t!(1 hours) {
+        if user_override.expired {
             tracing::trace!(
                 "User override expired ({} minutes) - ignoring",
                 user_override.timestamp.elapsed().as_minutes()
@@ -217,6 +245,7 @@ fn calculate_heating_mode(
 #[derive(Debug, Clone)]
 struct UserHeatingOverride {
     timestamp: DateTime,
+    expired: bool,
     target_temperature: DegreeCelsius,
     trigger_id: UserTriggerId,
 }
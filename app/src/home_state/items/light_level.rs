@@ -0,0 +1,26 @@
+use r#macro::{EnumVariants, Id};
+
+use crate::core::unit::Lux;
+use crate::home_state::calc::{DerivedStateProvider, StateCalculationContext};
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, EnumVariants, Id)]
+pub enum LightLevel {
+    LivingRoom,
+    Kitchen,
+    Bedroom,
+}
+
+pub struct LightLevelStateProvider;
+
+impl DerivedStateProvider<LightLevel, Lux> for LightLevelStateProvider {
+    fn calculate_current(&self, id: LightLevel, ctx: &StateCalculationContext) -> Option<Lux> {
+        use crate::device_state::LightLevel as DeviceLightLevel;
+
+        ctx.device_state(match id {
+            LightLevel::LivingRoom => DeviceLightLevel::LivingRoom,
+            LightLevel::Kitchen => DeviceLightLevel::Kitchen,
+            LightLevel::Bedroom => DeviceLightLevel::Bedroom,
+        })
+        .map(|dp| dp.value)
+    }
+}
@@ -0,0 +1,21 @@
+use r#macro::{EnumVariants, Id};
+
+use crate::home_state::calc::{DerivedStateProvider, StateCalculationContext};
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, EnumVariants, Id)]
+pub enum Locked {
+    ApartmentDoor,
+}
+
+pub struct LockedStateProvider;
+
+impl DerivedStateProvider<Locked, bool> for LockedStateProvider {
+    fn calculate_current(&self, id: Locked, ctx: &StateCalculationContext) -> Option<bool> {
+        use crate::device_state::Locked as DeviceLocked;
+
+        ctx.device_state(match id {
+            Locked::ApartmentDoor => DeviceLocked::ApartmentDoor,
+        })
+        .map(|dp| dp.value)
+    }
+}
@@ -1,5 +1,6 @@
 mod absolute_humidity;
 mod allergen_index;
+mod battery_level;
 mod cold_air_coming_in;
 mod dewpoint;
 mod energy_saving;
@@ -7,6 +8,8 @@ mod fan_activity;
 mod felt_temperature;
 mod heating;
 mod is_running;
+mod light_level;
+mod locked;
 mod occupancy;
 mod opened;
 mod particulate_matter;
@@ -23,6 +26,7 @@ use std::fmt::Debug;
 
 pub use absolute_humidity::AbsoluteHumidity;
 pub use allergen_index::AllergenIndex;
+pub use battery_level::{BatteryLevel, LOW_BATTERY_LEVEL};
 pub use cold_air_coming_in::ColdAirComingIn;
 pub use dewpoint::DewPoint;
 pub use energy_saving::EnergySaving;
@@ -30,6 +34,8 @@ pub use fan_activity::*;
 pub use felt_temperature::FeltTemperature;
 pub use heating::*;
 pub use is_running::IsRunning;
+pub use light_level::LightLevel;
+pub use locked::Locked;
 pub use occupancy::Occupancy;
 pub use opened::Opened;
 pub use particulate_matter::ParticulateMatter;
@@ -53,10 +59,13 @@ use r#macro::StateEnumDerive;
 pub enum HomeStateValue {
     AbsoluteHumidity(AbsoluteHumidity, GramPerCubicMeter),
     AllergenIndex(AllergenIndex, AllergenIndexValue),
+    BatteryLevel(BatteryLevel, Percent),
     ColdAirComingIn(ColdAirComingIn, bool),
     DewPoint(DewPoint, DegreeCelsius),
     FeltTemperature(FeltTemperature, DegreeCelsius),
     IsRunning(IsRunning, bool),
+    LightLevel(LightLevel, Lux),
+    Locked(Locked, bool),
     Occupancy(Occupancy, Probability),
     Opened(Opened, bool),
     ParticulateMatter(ParticulateMatter, MicrogramsPerCubicMeter),
@@ -98,6 +107,9 @@ impl DerivedStateProvider<HomeStateId, HomeStateValue> for HomeStateDerivedState
             HomeStateId::AllergenIndex(id) => allergen_index::AllergenIndexStateProvider
                 .calculate_current(id, ctx)
                 .map(|value| HomeStateValue::AllergenIndex(id, value)),
+            HomeStateId::BatteryLevel(id) => battery_level::BatteryLevelStateProvider
+                .calculate_current(id, ctx)
+                .map(|value| HomeStateValue::BatteryLevel(id, value)),
             HomeStateId::ColdAirComingIn(id) => cold_air_coming_in::ColdAirComingInStateProvider
                 .calculate_current(id, ctx)
                 .map(|value| HomeStateValue::ColdAirComingIn(id, value)),
@@ -110,6 +122,12 @@ impl DerivedStateProvider<HomeStateId, HomeStateValue> for HomeStateDerivedState
             HomeStateId::IsRunning(id) => is_running::IsRunningStateProvider
                 .calculate_current(id, ctx)
                 .map(|value| HomeStateValue::IsRunning(id, value)),
+            HomeStateId::LightLevel(id) => light_level::LightLevelStateProvider
+                .calculate_current(id, ctx)
+                .map(|value| HomeStateValue::LightLevel(id, value)),
+            HomeStateId::Locked(id) => locked::LockedStateProvider
+                .calculate_current(id, ctx)
+                .map(|value| HomeStateValue::Locked(id, value)),
             HomeStateId::Occupancy(id) => occupancy::OccupancyStateProvider
                 .calculate_current(id, ctx)
                 .map(|value| HomeStateValue::Occupancy(id, value)),
@@ -20,6 +20,9 @@ mod observability;
 mod settings;
 mod trigger;
 
+#[cfg(test)]
+mod e2e_tests;
+
 struct Infrastructure {
     db_pool: sqlx::PgPool,
     mqtt_client: Mqtt,
@@ -32,6 +35,11 @@ type AppTask = (&'static str, JoinHandle<()>);
 pub async fn main() {
     let settings = Settings::new().expect("Error reading configuration");
 
+    if let Some(path) = device_state_import_arg() {
+        run_device_state_import(&settings, &path).await;
+        return;
+    }
+
     let mut infrastructure = Infrastructure::init(&settings)
         .await
         .expect("Error initializing infrastructure");
@@ -44,11 +52,18 @@ pub async fn main() {
         &mut infrastructure.mqtt_client,
         &settings.tasmota.event_topic,
         &settings.z2m.event_topic,
+        settings.homeassistant.event_source,
         &settings.homeassistant.topic_event,
         &settings.homeassistant.url,
         &settings.homeassistant.token,
+        &settings.nuki.url,
+        &settings.nuki.token,
+        &settings.nuki.smart_lock_id,
         energy_meter_bus.subscribe(),
         command_event_bus.subscribe(),
+        &settings.device_state_filter,
+        &settings.device_state_staleness,
+        &settings.device_state_retention,
     )
     .await;
 
@@ -72,17 +87,41 @@ pub async fn main() {
         &settings.homeassistant.token,
         &settings.nuki.url,
         &settings.nuki.token,
+        &settings.nuki.smart_lock_id,
+        &settings.mqtt_commands,
+        home_state_module.subscribe(),
         home_state_module.subscribe(),
     )
-    .await;
+    .await
+    .expect("Error initializing command module");
 
-    let automation_module =
-        AutomationModule::new(home_state_module.subscribe(), command_module.client(), trigger_module.client());
+    automation::check_resource_plans(&command_module.command_targets()).expect("Invalid resource plans");
+
+    let automation_module = AutomationModule::new(
+        home_state_module.subscribe(),
+        command_module.client(),
+        trigger_module.client(),
+        home_state_module.client(),
+        infrastructure.db_pool.clone(),
+    )
+    .await
+    .expect("Error initializing automation module");
 
     let homekit_module = settings
         .homebridge
-        .new_runner(&mut infrastructure, trigger_module.client(), home_state_module.subscribe())
-        .await;
+        .new_runner(
+            &mut infrastructure,
+            trigger_module.client(),
+            automation_module.switch_client(),
+            home_state_module.subscribe(),
+        )
+        .await
+        .expect("Error initializing HomeKit");
+
+    let ha_discovery_runner = settings
+        .ha_discovery
+        .as_ref()
+        .map(|ha_discovery| ha_discovery.new_runner(&infrastructure, home_state_module.subscribe()));
 
     let remote_module = RemoteModule::new(
         &mut infrastructure.mqtt_client,
@@ -91,18 +130,32 @@ pub async fn main() {
     )
     .await;
 
+    let expected_z2m_devices = [
+        device_state::expected_z2m_devices(),
+        command::expected_z2m_devices(),
+        frontends::remote::expected_z2m_devices(),
+    ]
+    .concat();
+
     let observability_module = observability::ObservabilityModule::new(
         settings.metrics.victoria_url.clone(),
+        &mut infrastructure.mqtt_client,
+        &settings.z2m.event_topic,
+        expected_z2m_devices,
         device_state_module.subscribe(),
         home_state_module.subscribe(),
         device_state_module.client(),
         home_state_module.client(),
         command_module.client(),
-    );
+    )
+    .await;
 
     let http_server_exec = {
         let energy_reading_emitter = energy_meter_bus.emitter();
         let metrics_export_api = observability_module.api();
+        let automation_api = automation_module.api();
+        let trigger_api = trigger_module.api();
+        let device_state_api = device_state_module.api();
 
         async move {
             settings
@@ -111,6 +164,9 @@ pub async fn main() {
                     vec![
                         frontends::energy_meter::EnergyMeter::new_web_service(energy_reading_emitter.clone()),
                         metrics_export_api.routes(),
+                        automation_api.routes(),
+                        trigger_api.routes(),
+                        device_state_api.routes(),
                     ]
                 })
                 .await
@@ -148,6 +204,12 @@ pub async fn main() {
             remote_module.run().await;
         }),
     ];
+    if let Some(ha_discovery_runner) = ha_discovery_runner {
+        tasks.push(spawn_app_task("ha-discovery", async move {
+            tracing::info!("Starting Home Assistant discovery runner");
+            ha_discovery_runner.run().await;
+        }));
+    }
     let task_abort_handles = tasks
         .iter()
         .map(|(_, task_handle)| task_handle.abort_handle())
@@ -179,6 +241,39 @@ impl Infrastructure {
     }
 }
 
+/// `app import-device-state <file>` imports historical device states instead of starting the application
+fn device_state_import_arg() -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+
+    match args.as_slice() {
+        [_, command, path] if command == "import-device-state" => Some(path.clone()),
+        _ => None,
+    }
+}
+
+#[allow(clippy::expect_used)]
+async fn run_device_state_import(settings: &Settings, path: &str) {
+    settings.monitoring.init().expect("Error initializing monitoring");
+    let db_pool = settings.database.new_pool().await.expect("Error initializing database");
+
+    let progress = device_state::import_file(db_pool, path)
+        .await
+        .expect("Error importing device states");
+
+    tracing::info!(
+        "Import of {} finished: {} new, {} skipped",
+        path,
+        progress.imported,
+        progress.skipped
+    );
+    for (tag, count) in progress.unknown_tags.iter() {
+        tracing::warn!("Skipped {} rows with unknown tag {}", count, tag);
+    }
+    for row in progress.invalid_rows.iter() {
+        tracing::warn!("Skipped invalid row: {}", row);
+    }
+}
+
 fn spawn_app_task(name: &'static str, future: impl Future<Output = ()> + Send + 'static) -> AppTask {
     (name, tokio::spawn(future))
 }
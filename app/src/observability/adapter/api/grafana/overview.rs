@@ -3,7 +3,7 @@ use std::sync::Arc;
 use actix_web::web;
 use infrastructure::CorrelationId;
 
-use crate::command::{Command, CommandClient};
+use crate::command::{Command, CommandClient, CommandExecution, CommandState};
 use crate::device_state::{DeviceStateClient, DeviceStateId};
 use crate::observability::adapter::api::grafana::{GrafanaApiError, GrafanaResponse, TimeRangeQuery, csv_response};
 
@@ -27,6 +27,8 @@ async fn get_commands(
         r#type: String,
         target: String,
         state: String,
+        status: String,
+        attempts: u32,
         source: String,
         trace_id: Option<String>,
     }
@@ -41,6 +43,7 @@ async fn get_commands(
 
     let rows = commands.into_iter().map(|cmd| {
         let (command_type, target, state) = command_as_string(&cmd.command);
+        let status = command_status(&cmd);
         let source = cmd.source.to_string();
         let icon = if cmd.is_user_generated() { "USER" } else { "SYSTEM" };
 
@@ -52,6 +55,8 @@ async fn get_commands(
             r#type: command_type.to_string(),
             target,
             state,
+            status,
+            attempts: cmd.attempts,
             source,
             trace_id,
         }
@@ -60,6 +65,20 @@ async fn get_commands(
     csv_response(rows)
 }
 
+fn command_status(cmd: &CommandExecution) -> String {
+    match (&cmd.state, cmd.next_retry_at) {
+        (CommandState::Error(e), Some(next_retry_at)) => {
+            format!("retry at {} after: {}", next_retry_at.to_human_readable(), e)
+        }
+        (CommandState::Error(e), None) => format!("error: {e}"),
+        (CommandState::Pending, _) => "pending".to_string(),
+        (CommandState::InProgress, _) => "in progress".to_string(),
+        (CommandState::Success, _) => "success".to_string(),
+        (CommandState::Confirmed, _) => "confirmed".to_string(),
+        (CommandState::Unconfirmed, _) => "unconfirmed".to_string(),
+    }
+}
+
 fn command_as_string(command: &Command) -> (&str, String, String) {
     match command {
         Command::SetPower { device, power_on } => {
@@ -78,6 +97,11 @@ fn command_as_string(command: &Command) -> (&str, String, String) {
         ),
         Command::ControlFan { device, speed } => ("ControlFan", device.to_string(), speed.to_string()),
         Command::OpenDoor { device } => ("Open", device.to_string(), "open".to_string()),
+        Command::SetLock { device, locked } => (
+            "SetLock",
+            device.to_string(),
+            if *locked { "locked" } else { "unlocked" }.to_string(),
+        ),
     }
 }
 
@@ -1,11 +1,12 @@
 pub mod admin;
 pub mod grafana;
+pub mod z2m;
 
 use std::sync::Arc;
 
 use crate::{
     command::CommandClient, device_state::DeviceStateClient, home_state::HomeStateClient,
-    observability::adapter::repository::VictoriaRepository,
+    observability::{adapter::repository::VictoriaRepository, z2m_bridge::Z2mDriftReports},
 };
 
 #[derive(Clone)]
@@ -14,6 +15,7 @@ pub struct MetricsExportApi {
     command_client: Arc<CommandClient>,
     device_state_client: Arc<DeviceStateClient>,
     home_state_client: Arc<HomeStateClient>,
+    z2m_drift_reports: Z2mDriftReports,
 }
 
 impl MetricsExportApi {
@@ -22,12 +24,14 @@ impl MetricsExportApi {
         command_client: CommandClient,
         device_state_client: DeviceStateClient,
         home_state_client: HomeStateClient,
+        z2m_drift_reports: Z2mDriftReports,
     ) -> Self {
         Self {
             repo,
             command_client: Arc::new(command_client),
             device_state_client: Arc::new(device_state_client),
             home_state_client: Arc::new(home_state_client),
+            z2m_drift_reports,
         }
     }
 
@@ -39,5 +43,6 @@ impl MetricsExportApi {
                 self.home_state_client.clone(),
             ))
             .service(grafana::routes(self.command_client.clone(), self.device_state_client.clone()))
+            .service(z2m::routes(self.z2m_drift_reports.clone()))
     }
 }
@@ -0,0 +1,16 @@
+use actix_web::{Error, HttpResponse, web};
+
+use crate::observability::z2m_bridge::Z2mDriftReports;
+
+pub fn routes(reports: Z2mDriftReports) -> actix_web::Scope {
+    web::scope("/z2m")
+        .route("/drift", web::get().to(drift_report_handler))
+        .app_data(web::Data::new(reports))
+}
+
+async fn drift_report_handler(reports: web::Data<Z2mDriftReports>) -> Result<HttpResponse, Error> {
+    match reports.latest() {
+        Some(report) => Ok(HttpResponse::Ok().json(report)),
+        None => Ok(HttpResponse::ServiceUnavailable().body("No Z2M bridge device list received yet")),
+    }
+}
@@ -1,18 +1,22 @@
 mod adapter;
 mod domain;
+mod z2m_bridge;
 
 pub use infrastructure::meter::increment as system_metric_increment;
+pub use infrastructure::meter::record as system_metric_record;
 pub use infrastructure::meter::set as system_metric_set;
 
 use std::sync::Arc;
 
-use infrastructure::EventListener;
+use infrastructure::{EventListener, Mqtt};
 
 use crate::{
     command::CommandClient,
+    core::ExpectedZ2mDevice,
     device_state::{DeviceStateClient, DeviceStateEvent},
     home_state::{HomeStateClient, HomeStateEvent},
     observability::adapter::{MetricsAdapter as _, api::MetricsExportApi, repository::VictoriaRepository},
+    observability::z2m_bridge::Z2mBridgeMonitor,
     t,
 };
 
@@ -27,11 +31,16 @@ pub struct ObservabilityModule {
     command_client: CommandClient,
     home_metrics_adapter: HomeMetricsAdapter,
     device_metrics_adapter: DeviceMetricsAdapter,
+    z2m_bridge: Z2mBridgeMonitor,
 }
 
 impl ObservabilityModule {
-    pub fn new(
+    #[allow(clippy::too_many_arguments)]
+    pub async fn new(
         victoria_url: String,
+        mqtt_client: &mut Mqtt,
+        z2m_event_topic: &str,
+        expected_z2m_devices: Vec<ExpectedZ2mDevice>,
         device_state_events: EventListener<DeviceStateEvent>,
         home_state_events: EventListener<HomeStateEvent>,
         device_state_client: DeviceStateClient,
@@ -39,6 +48,7 @@ impl ObservabilityModule {
         command_client: CommandClient,
     ) -> Self {
         let repo = Arc::new(VictoriaRepository::new(victoria_url));
+        let z2m_bridge = Z2mBridgeMonitor::new(mqtt_client, z2m_event_topic, expected_z2m_devices).await;
 
         Self {
             repo,
@@ -49,6 +59,7 @@ impl ObservabilityModule {
             command_client,
             home_metrics_adapter: HomeMetricsAdapter,
             device_metrics_adapter: DeviceMetricsAdapter,
+            z2m_bridge,
         }
     }
 
@@ -58,6 +69,7 @@ impl ObservabilityModule {
             self.command_client.clone(),
             self.device_state_client.clone(),
             self.home_state_client.clone(),
+            self.z2m_bridge.reports(),
         )
     }
 
@@ -90,6 +102,11 @@ impl ObservabilityModule {
                 event = self.home_state_events.recv() => match event {
                     Some(HomeStateEvent::Updated(data_point)) => self.home_metrics_adapter.to_metrics(data_point.clone()),
                     _ => vec![],
+                },
+
+                Some(msg) = self.z2m_bridge.recv() => {
+                    self.z2m_bridge.handle_bridge_devices(&msg);
+                    vec![]
                 }
             };
 
@@ -0,0 +1,198 @@
+mod report;
+
+pub use report::Z2mDriftReport;
+
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+
+use infrastructure::{Mqtt, MqttInMessage, MqttSubscription};
+use serde::Deserialize;
+
+use crate::{
+    core::{ExpectedZ2mDevice, sync::lock_ignoring_poison},
+    t,
+};
+use report::BridgeDevice;
+
+/// Compares the device list published by the Zigbee2MQTT bridge with the configured devices
+pub struct Z2mBridgeMonitor {
+    subscription: MqttSubscription,
+    expected: Vec<ExpectedZ2mDevice>,
+    reports: Z2mDriftReports,
+}
+
+/// Latest drift report, shared with the admin API
+#[derive(Clone, Default)]
+pub struct Z2mDriftReports {
+    latest: Arc<Mutex<Option<Z2mDriftReport>>>,
+}
+
+impl Z2mDriftReports {
+    pub fn latest(&self) -> Option<Z2mDriftReport> {
+        self.with_latest(|latest| latest.clone())
+    }
+
+    fn with_latest<R>(&self, f: impl FnOnce(&mut Option<Z2mDriftReport>) -> R) -> R {
+        let mut latest = lock_ignoring_poison(&self.latest);
+        f(&mut latest)
+    }
+}
+
+impl Z2mBridgeMonitor {
+    #[allow(clippy::expect_used)]
+    pub async fn new(mqtt_client: &mut Mqtt, event_topic: &str, expected: Vec<ExpectedZ2mDevice>) -> Self {
+        //Retained by the bridge, so the first report is created right after startup
+        let subscription = mqtt_client
+            .subscribe(event_topic, "bridge/devices")
+            .await
+            .expect("Error subscribing to MQTT topic");
+
+        Self {
+            subscription,
+            expected,
+            reports: Z2mDriftReports::default(),
+        }
+    }
+
+    pub fn reports(&self) -> Z2mDriftReports {
+        self.reports.clone()
+    }
+
+    pub async fn recv(&mut self) -> Option<MqttInMessage> {
+        self.subscription.recv().await
+    }
+
+    pub fn handle_bridge_devices(&self, msg: &MqttInMessage) {
+        let bridge_devices = match parse_bridge_devices(&msg.payload) {
+            Ok(devices) => devices,
+            Err(e) => {
+                tracing::error!("Error parsing Z2M bridge device list: {:?}", e);
+                return;
+            }
+        };
+
+        let report = Z2mDriftReport::new(&self.expected, &bridge_devices, t!(now));
+
+        crate::observability::system_metric_set(
+            "z2m_config_drift",
+            report.missing.len() as f64,
+            &[("kind", "missing")],
+        );
+        crate::observability::system_metric_set(
+            "z2m_config_drift",
+            report.unmapped.len() as f64,
+            &[("kind", "unmapped")],
+        );
+        crate::observability::system_metric_set(
+            "z2m_config_drift",
+            report.feature_mismatches.len() as f64,
+            &[("kind", "feature_mismatch")],
+        );
+
+        self.reports.with_latest(|latest| {
+            //bridge/devices is republished on every interview or rename, only log what changed
+            if latest.as_ref().is_none_or(|prev| !prev.has_same_findings(&report)) {
+                if report.missing.is_empty() && report.feature_mismatches.is_empty() {
+                    tracing::info!("Z2M config drift report: {}", report);
+                } else {
+                    tracing::warn!("Z2M config drift report: {}", report);
+                }
+            }
+
+            *latest = Some(report);
+        });
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BridgeDevicePayload {
+    friendly_name: String,
+    #[serde(rename = "type")]
+    device_type: String,
+    definition: Option<BridgeDeviceDefinition>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BridgeDeviceDefinition {
+    #[serde(default)]
+    exposes: Vec<Expose>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Expose {
+    property: Option<String>,
+    #[serde(default)]
+    features: Vec<Expose>,
+}
+
+fn parse_bridge_devices(payload: &str) -> anyhow::Result<Vec<BridgeDevice>> {
+    let devices: Vec<BridgeDevicePayload> = serde_json::from_str(payload)?;
+
+    Ok(devices
+        .into_iter()
+        .filter(|d| d.device_type != "Coordinator")
+        .map(|d| {
+            let mut features = BTreeSet::new();
+            if let Some(definition) = &d.definition {
+                collect_properties(&definition.exposes, &mut features);
+            }
+
+            BridgeDevice {
+                friendly_name: d.friendly_name,
+                features,
+            }
+        })
+        .collect())
+}
+
+//Composite exposes like climate or switch nest their properties in features
+fn collect_properties(exposes: &[Expose], properties: &mut BTreeSet<String>) {
+    for expose in exposes {
+        if let Some(property) = &expose.property {
+            properties.insert(property.clone());
+        }
+        collect_properties(&expose.features, properties);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nested_exposes_and_skips_coordinator() -> anyhow::Result<()> {
+        let payload = r#"[
+            {"friendly_name": "Coordinator", "type": "Coordinator", "definition": null},
+            {
+                "friendly_name": "bathroom/radiator_thermostat_sonoff",
+                "type": "EndDevice",
+                "definition": {
+                    "model": "TRVZB",
+                    "exposes": [
+                        {
+                            "type": "climate",
+                            "features": [
+                                {"type": "numeric", "name": "occupied_heating_setpoint", "property": "occupied_heating_setpoint"},
+                                {"type": "numeric", "name": "local_temperature", "property": "local_temperature"}
+                            ]
+                        },
+                        {"type": "numeric", "name": "battery", "property": "battery"}
+                    ]
+                }
+            },
+            {"friendly_name": "0x00158d0001", "type": "Router", "definition": null}
+        ]"#;
+
+        let devices = parse_bridge_devices(payload)?;
+
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].friendly_name, "bathroom/radiator_thermostat_sonoff");
+        assert_eq!(
+            devices[0].features.iter().map(|f| f.as_str()).collect::<Vec<_>>(),
+            vec!["battery", "local_temperature", "occupied_heating_setpoint"]
+        );
+        assert!(devices[1].features.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::core::{ExpectedZ2mDevice, time::DateTime};
+
+/// Device as announced on `bridge/devices`, with the properties of all exposed features
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BridgeDevice {
+    pub friendly_name: String,
+    pub features: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MissingDevice {
+    pub friendly_name: String,
+    pub usages: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FeatureMismatch {
+    pub friendly_name: String,
+    pub usage: String,
+    pub missing_features: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Z2mDriftReport {
+    pub checked_at: DateTime,
+    pub bridge_devices: usize,
+    pub configured_devices: usize,
+    /// Configured, but not known to the bridge
+    pub missing: Vec<MissingDevice>,
+    /// Known to the bridge, but not used by any config
+    pub unmapped: Vec<String>,
+    /// Known to the bridge, but not exposing everything the config relies on
+    pub feature_mismatches: Vec<FeatureMismatch>,
+}
+
+impl Z2mDriftReport {
+    pub fn new(expected: &[ExpectedZ2mDevice], bridge: &[BridgeDevice], checked_at: DateTime) -> Self {
+        let bridge_by_name: BTreeMap<&str, &BridgeDevice> =
+            bridge.iter().map(|d| (d.friendly_name.as_str(), d)).collect();
+
+        let mut expected_by_name: BTreeMap<&str, Vec<&ExpectedZ2mDevice>> = BTreeMap::new();
+        for device in expected {
+            expected_by_name
+                .entry(device.friendly_name.as_str())
+                .or_default()
+                .push(device);
+        }
+
+        let mut missing = vec![];
+        let mut feature_mismatches = vec![];
+
+        for (name, usages) in expected_by_name.iter() {
+            let Some(bridge_device) = bridge_by_name.get(name) else {
+                missing.push(MissingDevice {
+                    friendly_name: name.to_string(),
+                    usages: usages.iter().map(|d| d.usage.clone()).collect(),
+                });
+                continue;
+            };
+
+            for usage in usages {
+                let missing_features: Vec<String> = usage
+                    .features
+                    .iter()
+                    .filter(|f| !bridge_device.features.contains(**f))
+                    .map(|f| f.to_string())
+                    .collect();
+
+                if !missing_features.is_empty() {
+                    feature_mismatches.push(FeatureMismatch {
+                        friendly_name: name.to_string(),
+                        usage: usage.usage.clone(),
+                        missing_features,
+                    });
+                }
+            }
+        }
+
+        let unmapped = bridge_by_name
+            .keys()
+            .filter(|name| !expected_by_name.contains_key(*name))
+            .map(|name| name.to_string())
+            .collect();
+
+        Self {
+            checked_at,
+            bridge_devices: bridge_by_name.len(),
+            configured_devices: expected_by_name.len(),
+            missing,
+            unmapped,
+            feature_mismatches,
+        }
+    }
+
+    pub fn has_same_findings(&self, other: &Z2mDriftReport) -> bool {
+        self.missing == other.missing
+            && self.unmapped == other.unmapped
+            && self.feature_mismatches == other.feature_mismatches
+    }
+}
+
+impl std::fmt::Display for Z2mDriftReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} bridge devices, {} configured: {} missing, {} unmapped, {} feature mismatches",
+            self.bridge_devices,
+            self.configured_devices,
+            self.missing.len(),
+            self.unmapped.len(),
+            self.feature_mismatches.len()
+        )?;
+
+        for device in &self.missing {
+            write!(
+                f,
+                "\n  missing: {} ({})",
+                device.friendly_name,
+                device.usages.join(", ")
+            )?;
+        }
+
+        for name in &self.unmapped {
+            write!(f, "\n  unmapped: {}", name)?;
+        }
+
+        for mismatch in &self.feature_mismatches {
+            write!(
+                f,
+                "\n  feature mismatch: {} ({}) lacks {}",
+                mismatch.friendly_name,
+                mismatch.usage,
+                mismatch.missing_features.join(", ")
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::t;
+
+    fn bridge_device(name: &str, features: &[&str]) -> BridgeDevice {
+        BridgeDevice {
+            friendly_name: name.to_string(),
+            features: features.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn reports_missing_unmapped_and_mismatching_devices() {
+        let expected = vec![
+            ExpectedZ2mDevice::new("bedroom/temp_sensor", "ClimateSensor", &["temperature", "humidity"]),
+            ExpectedZ2mDevice::new("kitchen/window", "ContactSensor", &["contact"]),
+            ExpectedZ2mDevice::new("bedroom/remote", "Remote", &["action"]),
+        ];
+        let bridge = vec![
+            bridge_device("bedroom/temp_sensor", &["temperature", "battery"]),
+            bridge_device("bedroom/remote", &["action", "battery"]),
+            bridge_device("hallway/new_plug", &["state", "power"]),
+        ];
+
+        let report = Z2mDriftReport::new(&expected, &bridge, t!(now));
+
+        assert_eq!(
+            report.missing,
+            vec![MissingDevice {
+                friendly_name: "kitchen/window".to_string(),
+                usages: vec!["ContactSensor".to_string()],
+            }]
+        );
+        assert_eq!(report.unmapped, vec!["hallway/new_plug".to_string()]);
+        assert_eq!(
+            report.feature_mismatches,
+            vec![FeatureMismatch {
+                friendly_name: "bedroom/temp_sensor".to_string(),
+                usage: "ClimateSensor".to_string(),
+                missing_features: vec!["humidity".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn device_used_by_several_configs_is_checked_per_usage() {
+        let expected = vec![
+            ExpectedZ2mDevice::new("bathroom/plug", "PowerPlug state", &["power", "energy", "state"]),
+            ExpectedZ2mDevice::new("bathroom/plug", "SetPower", &["state"]),
+        ];
+        let bridge = vec![bridge_device("bathroom/plug", &["power", "energy", "state"])];
+
+        let report = Z2mDriftReport::new(&expected, &bridge, t!(now));
+
+        assert!(report.missing.is_empty());
+        assert!(report.unmapped.is_empty());
+        assert!(report.feature_mismatches.is_empty());
+        assert_eq!(report.configured_devices, 1);
+    }
+}
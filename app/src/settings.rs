@@ -10,10 +10,20 @@ pub struct Settings {
     pub monitoring: MonitoringConfig,
     pub homebridge: crate::frontends::homekit::Homekit,
     pub homeassistant: HomeAssistantSettings,
+    #[serde(default)]
+    pub ha_discovery: Option<crate::frontends::homeassistant::HaDiscovery>,
     pub z2m: Zigbee2MqttSettings,
     pub tasmota: TasmotaSettings,
     pub nuki: NukiSettings,
+    #[serde(default)]
+    pub device_state_filter: crate::device_state::PlausibilityConfig,
+    #[serde(default)]
+    pub device_state_staleness: crate::device_state::StalenessConfig,
+    #[serde(default)]
+    pub device_state_retention: crate::device_state::RetentionConfig,
     pub metrics: MetricsExportSettings,
+    #[serde(default)]
+    pub mqtt_commands: Vec<crate::command::MqttCommandConfig>,
 }
 
 impl Settings {
@@ -29,6 +39,8 @@ impl Settings {
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct HomeAssistantSettings {
+    #[serde(default)]
+    pub event_source: crate::device_state::HaEventSource,
     pub topic_event: String,
     pub url: String,
     pub token: String,
@@ -48,6 +60,8 @@ pub struct Zigbee2MqttSettings {
 pub struct NukiSettings {
     pub url: String,
     pub token: String,
+    /// Nuki ID of the apartment door smart lock in hex, as listed by the bridge
+    pub smart_lock_id: String,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -0,0 +1,61 @@
+use actix_web::{Error, HttpResponse, web};
+use serde::Deserialize;
+
+use crate::{
+    core::time::DateTime,
+    t,
+    trigger::{Scene, TriggerClient, UserTrigger},
+};
+
+#[derive(Clone)]
+pub struct TriggerApi {
+    trigger_client: TriggerClient,
+}
+
+impl TriggerApi {
+    pub fn new(trigger_client: TriggerClient) -> Self {
+        Self { trigger_client }
+    }
+
+    pub fn routes(&self) -> actix_web::Scope {
+        web::scope("/trigger")
+            .route("", web::post().to(schedule_trigger_handler))
+            .route("/scene/{scene}", web::post().to(activate_scene_handler))
+            .app_data(web::Data::new(self.clone()))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ScheduleTriggerRequest {
+    trigger: UserTrigger,
+    #[serde(default)]
+    start: Option<DateTime>,
+    #[serde(default)]
+    end: Option<DateTime>,
+}
+
+async fn schedule_trigger_handler(
+    api: web::Data<TriggerApi>,
+    body: web::Json<ScheduleTriggerRequest>,
+) -> Result<HttpResponse, Error> {
+    let request = body.into_inner();
+    let start = request.start.unwrap_or_else(|| t!(now));
+
+    api.trigger_client
+        .schedule_trigger(request.trigger, start, request.end)
+        .await
+        .map_err(|e| actix_web::error::ErrorBadRequest(format!("Error scheduling trigger: {}", e)))?;
+
+    Ok(HttpResponse::Accepted().finish())
+}
+
+async fn activate_scene_handler(api: web::Data<TriggerApi>, scene: web::Path<Scene>) -> Result<HttpResponse, Error> {
+    let scene = scene.into_inner();
+
+    api.trigger_client
+        .add_trigger(UserTrigger::Scene { scene })
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Error activating scene: {}", e)))?;
+
+    Ok(HttpResponse::Accepted().finish())
+}
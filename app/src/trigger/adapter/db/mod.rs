@@ -24,7 +24,7 @@ impl TriggerRepository {
         let result = sqlx::query!(
             r#"UPDATE user_trigger
                SET active_until = $1
-               WHERE active_until IS NULL
+               WHERE (active_until IS NULL OR active_until > $1)
                AND timestamp < $1
                AND id != ALL($2)"#,
             before.into_db(),
@@ -38,13 +38,19 @@ impl TriggerRepository {
     }
 
     #[tracing::instrument(skip(self))]
-    pub async fn add_trigger(&self, trigger: UserTrigger) -> anyhow::Result<()> {
+    pub async fn add_trigger(
+        &self,
+        trigger: UserTrigger,
+        start: DateTime,
+        end: Option<DateTime>,
+    ) -> anyhow::Result<()> {
         let trigger: serde_json::Value = serde_json::to_value(trigger)?;
 
         sqlx::query!(
-            r#"INSERT INTO user_trigger (trigger, timestamp, correlation_id) VALUES ($1, $2, $3)"#,
+            r#"INSERT INTO user_trigger (trigger, timestamp, active_until, correlation_id) VALUES ($1, $2, $3, $4)"#,
             trigger,
-            t!(now).into_db(),
+            start.into_db(),
+            end.map(|dt| dt.into_db()),
             infrastructure::TraceContext::current()
                 .correlation_id()
                 .map(|id| id.to_string()),
@@ -99,14 +105,16 @@ impl TriggerRepository {
             let active_from = row.active_from.map(|dt| dt.into());
             let active_until = row.active_until.map(|dt| dt.into());
 
-            result.push(UserTriggerExecution {
+            let execution = UserTriggerExecution {
                 id: row.id.into(),
                 trigger,
                 timestamp,
                 active_from,
                 active_until,
                 correlation_id: row.correlation_id,
-            });
+            };
+
+            result.extend(execution.expand_scene());
         }
 
         Ok(result)
@@ -140,20 +148,20 @@ impl TriggerRepository {
             let active_from = row.active_from.map(|dt| dt.into());
             let active_until = row.active_until.map(|dt| dt.into());
 
-            let target = trigger.target();
-            if seen_targets.contains(&target) {
-                continue;
-            }
-            seen_targets.insert(target);
-
-            result.push(UserTriggerExecution {
+            let execution = UserTriggerExecution {
                 id: row.id.into(),
                 trigger,
                 timestamp,
                 active_from,
                 active_until,
                 correlation_id: row.correlation_id,
-            });
+            };
+
+            for execution in execution.expand_scene() {
+                if seen_targets.insert(execution.target()) {
+                    result.push(execution);
+                }
+            }
         }
 
         Ok(result)
@@ -172,7 +180,11 @@ fn parse_user_trigger(id: i64, trigger: Value) -> Option<UserTrigger> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{core::time::DateTimeRange, t, trigger::OnOffDevice};
+    use crate::{
+        core::time::DateTimeRange,
+        t,
+        trigger::{OnOffDevice, Scene, UserTriggerTarget},
+    };
 
     use super::*;
 
@@ -247,4 +259,85 @@ mod tests {
 
         Ok(())
     }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn scheduled_trigger_is_not_active_before_start(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = TriggerRepository::new(pool);
+        let trigger = UserTrigger::DevicePower {
+            device: OnOffDevice::InfraredHeater,
+            on: true,
+        };
+
+        repo.add_trigger(trigger, t!(in 30 minutes), Some(t!(in 90 minutes)))
+            .await?;
+
+        let triggers = repo.get_all_active_triggers_since(t!(1 hours ago)).await?;
+        assert!(triggers.is_empty());
+
+        let triggers = t!(in 40 minutes)
+            .eval_timeshifted(repo.get_all_active_triggers_since(t!(1 hours ago)))
+            .await?;
+        assert_eq!(triggers.len(), 1);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn cancel_keeps_pending_scheduled_triggers(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = TriggerRepository::new(pool);
+        let trigger = UserTrigger::DevicePower {
+            device: OnOffDevice::InfraredHeater,
+            on: true,
+        };
+
+        repo.add_trigger(trigger.clone(), t!(10 minutes ago), None).await?;
+        repo.add_trigger(trigger, t!(in 30 minutes), Some(t!(in 90 minutes)))
+            .await?;
+
+        let cancelled = repo.cancel_triggers_before_excluding(t!(now), &[]).await?;
+        assert_eq!(cancelled, 1);
+
+        let scheduled = repo
+            .get_all_triggers_active_anytime_in_range(DateTimeRange::new(t!(in 30 minutes), t!(in 60 minutes)))
+            .await?;
+        assert_eq!(scheduled.len(), 1);
+        assert!(scheduled[0].active_until.is_some_and(|until| until > t!(in 60 minutes)));
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../migrations")]
+    async fn scene_is_expanded_into_member_triggers(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = TriggerRepository::new(pool);
+        let scene = Scene::LeavingHome;
+
+        repo.add_trigger(UserTrigger::Scene { scene: scene.clone() }, t!(10 minutes ago), None)
+            .await?;
+        repo.add_trigger(
+            UserTrigger::DevicePower {
+                device: OnOffDevice::Dehumidifier,
+                on: true,
+            },
+            t!(5 minutes ago),
+            None,
+        )
+        .await?;
+
+        let triggers = repo.get_all_active_triggers_since(t!(1 hours ago)).await?;
+
+        assert_eq!(triggers.len(), scene.triggers().len());
+        let scene_ids: HashSet<_> = triggers
+            .iter()
+            .filter(|t| t.target() != UserTriggerTarget::DevicePower(OnOffDevice::Dehumidifier))
+            .map(|t| t.id.clone())
+            .collect();
+        assert_eq!(scene_ids.len(), 1);
+
+        let dehumidifier = triggers
+            .iter()
+            .find(|t| t.target() == UserTriggerTarget::DevicePower(OnOffDevice::Dehumidifier));
+        assert!(dehumidifier.is_some_and(|t| !scene_ids.contains(&t.id)));
+
+        Ok(())
+    }
 }
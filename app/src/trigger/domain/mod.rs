@@ -1,6 +1,8 @@
 mod remote;
+mod scene;
 
 pub use remote::*;
+pub use scene::*;
 
 use r#macro::{EnumVariants, Id};
 use serde::{Deserialize, Serialize};
@@ -30,7 +32,9 @@ pub enum UserTrigger {
     FanSpeed { fan: FanActivity, airflow: FanAirflow },
     Heating { zone: HeatingZone, request: HeatingRequest },
     OpenDoor { door: Door },
+    DoorLock { door: Door, locked: bool },
     Remote(RemoteTrigger),
+    Scene { scene: Scene },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, derive_more::From, derive_more::Display, Id)]
@@ -44,13 +48,19 @@ pub enum UserTriggerTarget {
     Heating(HeatingZone),
     #[display("OpenDoor[{}]", _0)]
     OpenDoor(Door),
+    #[display("DoorLock[{}]", _0)]
+    #[from(ignore)]
+    DoorLock(Door),
     Remote(RemoteTriggerTarget),
+    #[display("Scene[{}]", _0)]
+    Scene(Scene),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, derive_more::Display, Id, EnumVariants)]
 #[serde(rename_all = "snake_case")]
 pub enum Door {
     Building,
+    Apartment,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,7 +78,9 @@ impl UserTrigger {
             UserTrigger::FanSpeed { fan, .. } => UserTriggerTarget::FanSpeed(*fan),
             UserTrigger::Heating { zone, .. } => UserTriggerTarget::Heating(*zone),
             UserTrigger::OpenDoor { door } => UserTriggerTarget::OpenDoor(door.clone()),
+            UserTrigger::DoorLock { door, .. } => UserTriggerTarget::DoorLock(door.clone()),
             UserTrigger::Remote(command) => UserTriggerTarget::Remote(command.into()),
+            UserTrigger::Scene { scene } => UserTriggerTarget::Scene(scene.clone()),
         }
     }
 }
@@ -95,6 +107,19 @@ mod serialization {
         );
     }
 
+    #[test]
+    fn test_serialize_scene() {
+        assert_json_eq!(
+            UserTrigger::Scene {
+                scene: Scene::MovieNight
+            },
+            json!({
+                "type": "scene",
+                "scene": "movie_night"
+            })
+        );
+    }
+
     #[test]
     fn test_display_device_power() {
         assert_eq!(
@@ -0,0 +1,78 @@
+use r#macro::{EnumVariants, Id};
+use serde::{Deserialize, Serialize};
+
+use crate::core::domain::HeatingZone;
+use crate::core::unit::{DegreeCelsius, FanAirflow, FanSpeed};
+use crate::home_state::FanActivity;
+
+use super::{HeatingRequest, OnOffDevice, UserTrigger};
+
+/// Named set of triggers that are activated together with a single trigger id
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, derive_more::Display, Id, EnumVariants)]
+#[serde(rename_all = "snake_case")]
+pub enum Scene {
+    MovieNight,
+    LeavingHome,
+    GoodNight,
+}
+
+impl Scene {
+    pub fn triggers(&self) -> Vec<UserTrigger> {
+        match self {
+            Scene::MovieNight => vec![
+                UserTrigger::DevicePower {
+                    device: OnOffDevice::LivingRoomTvEnergySaving,
+                    on: false,
+                },
+                UserTrigger::FanSpeed {
+                    fan: FanActivity::LivingRoomAirPurifier,
+                    airflow: FanAirflow::Forward(FanSpeed::Low),
+                },
+                UserTrigger::Heating {
+                    zone: HeatingZone::LivingRoom,
+                    request: HeatingRequest::Heat(DegreeCelsius(21.0)),
+                },
+            ],
+            Scene::LeavingHome => vec![
+                UserTrigger::DevicePower {
+                    device: OnOffDevice::InfraredHeater,
+                    on: false,
+                },
+                UserTrigger::DevicePower {
+                    device: OnOffDevice::Dehumidifier,
+                    on: false,
+                },
+                UserTrigger::FanSpeed {
+                    fan: FanActivity::LivingRoomAirPurifier,
+                    airflow: FanAirflow::Off,
+                },
+                UserTrigger::Heating {
+                    zone: HeatingZone::LivingRoom,
+                    request: HeatingRequest::Auto,
+                },
+                UserTrigger::Heating {
+                    zone: HeatingZone::Bedroom,
+                    request: HeatingRequest::Auto,
+                },
+            ],
+            Scene::GoodNight => vec![
+                UserTrigger::DevicePower {
+                    device: OnOffDevice::InfraredHeater,
+                    on: false,
+                },
+                UserTrigger::DevicePower {
+                    device: OnOffDevice::LivingRoomTvEnergySaving,
+                    on: true,
+                },
+                UserTrigger::FanSpeed {
+                    fan: FanActivity::LivingRoomAirPurifier,
+                    airflow: FanAirflow::Off,
+                },
+                UserTrigger::Heating {
+                    zone: HeatingZone::Bedroom,
+                    request: HeatingRequest::Heat(DegreeCelsius(18.0)),
+                },
+            ],
+        }
+    }
+}
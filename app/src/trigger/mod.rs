@@ -9,9 +9,12 @@ use infrastructure::{EventBus, EventListener};
 use sqlx::PgPool;
 
 use crate::{
-    core::time::{DateTime, DateTimeRange},
+    core::time::{DateTime, DateTimeRange, Duration},
     t,
-    trigger::{adapter::db::TriggerRepository, service::TriggerService},
+    trigger::{
+        adapter::{api::TriggerApi, db::TriggerRepository},
+        service::TriggerService,
+    },
 };
 
 #[derive(Debug, Clone)]
@@ -34,6 +37,23 @@ impl UserTriggerExecution {
         self.trigger.target()
     }
 
+    /// A scene is replaced by its member triggers, all sharing the id and time window of the scene.
+    /// Other triggers are returned as they are.
+    pub fn expand_scene(self) -> Vec<UserTriggerExecution> {
+        let UserTrigger::Scene { scene } = &self.trigger else {
+            return vec![self];
+        };
+
+        scene
+            .triggers()
+            .into_iter()
+            .map(|trigger| UserTriggerExecution {
+                trigger,
+                ..self.clone()
+            })
+            .collect()
+    }
+
     pub fn is_active(&self) -> bool {
         let now = t!(now);
         match self.active_until {
@@ -42,6 +62,14 @@ impl UserTriggerExecution {
         }
     }
 
+    /// Expired after the explicit end if scheduled with one, otherwise after `default_duration`
+    pub fn is_expired(&self, default_duration: &Duration) -> bool {
+        match self.active_until {
+            Some(active_until) => t!(now) >= active_until,
+            None => self.timestamp.elapsed() > *default_duration,
+        }
+    }
+
     pub fn execution_started(&self) -> bool {
         let now = t!(now);
         match self.active_from {
@@ -68,7 +96,18 @@ pub struct TriggerClient {
 
 impl TriggerClient {
     pub async fn add_trigger(&self, trigger: UserTrigger) -> anyhow::Result<()> {
-        self.service.add_trigger(trigger).await
+        self.service.add_trigger(trigger, t!(now), None).await
+    }
+
+    /// Trigger becomes active at `start` and stays active until `end`. Without an end,
+    /// the default duration of the consuming rule applies.
+    pub async fn schedule_trigger(
+        &self,
+        trigger: UserTrigger,
+        start: DateTime,
+        end: Option<DateTime>,
+    ) -> anyhow::Result<()> {
+        self.service.add_trigger(trigger, start, end).await
     }
 
     pub async fn get_all_active_triggers(&self) -> anyhow::Result<Vec<UserTriggerExecution>> {
@@ -110,6 +149,10 @@ impl TriggerModule {
         }
     }
 
+    pub fn api(&self) -> TriggerApi {
+        TriggerApi::new(self.client())
+    }
+
     pub fn subscribe(&self) -> EventListener<TriggerEvent> {
         self.event_bus.subscribe()
     }
@@ -16,8 +16,25 @@ impl TriggerService {
         Self { repo, event_tx }
     }
 
-    pub async fn add_trigger(&self, trigger: UserTrigger) -> anyhow::Result<()> {
-        self.repo.add_trigger(trigger).await?;
+    pub async fn add_trigger(
+        &self,
+        trigger: UserTrigger,
+        start: DateTime,
+        end: Option<DateTime>,
+    ) -> anyhow::Result<()> {
+        if let Some(end) = end
+            && end <= start
+        {
+            anyhow::bail!("End of trigger {} must be after its start {}", end, start);
+        }
+
+        if let Some(end) = end
+            && end <= t!(now)
+        {
+            anyhow::bail!("End of trigger {} is already in the past", end);
+        }
+
+        self.repo.add_trigger(trigger, start, end).await?;
         self.event_tx.send(TriggerEvent::TriggerAdded);
         Ok(())
     }
@@ -14,5 +14,5 @@ pub use http::server::HttpServerConfig;
 pub use mqtt::{Mqtt, MqttConfig, MqttInMessage, MqttSender, MqttSubscription};
 
 pub mod meter {
-    pub use super::monitoring::meter::{increment, set};
+    pub use super::monitoring::meter::{increment, record, set};
 }
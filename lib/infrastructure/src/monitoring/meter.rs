@@ -11,6 +11,11 @@ pub fn set(name: &'static str, value: f64, kv: &[(&str, &str)]) {
     gauge(name).record(value, &kv)
 }
 
+pub fn record(name: &'static str, value: f64, kv: &[(&str, &str)]) {
+    let kv: Vec<KeyValue> = kv.iter().map(|(k, v)| as_kv(k, v)).collect();
+    histogram(name).record(value, &kv)
+}
+
 fn as_kv(k: &str, v: &str) -> KeyValue {
     KeyValue::new(k.to_owned(), v.to_owned())
 }
@@ -24,3 +29,8 @@ fn counter(name: &'static str) -> opentelemetry::metrics::Counter<u64> {
 fn gauge(name: &'static str) -> opentelemetry::metrics::Gauge<f64> {
     opentelemetry::global::meter("home").f64_gauge(name).build()
 }
+
+#[cached]
+fn histogram(name: &'static str) -> opentelemetry::metrics::Histogram<f64> {
+    opentelemetry::global::meter("home").f64_histogram(name).build()
+}
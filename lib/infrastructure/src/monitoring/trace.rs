@@ -1,9 +1,15 @@
+use std::cell::RefCell;
+
 use opentelemetry::{KeyValue, trace::TraceContextExt};
 use tracing::Span;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 use crate::CorrelationId;
 
+thread_local! {
+    static CAPTURED_ATTRIBUTES: RefCell<Option<Vec<(String, String)>>> = const { RefCell::new(None) };
+}
+
 #[derive(Debug, Clone)]
 pub struct TraceContext {
     span: tracing::Span,
@@ -49,12 +55,35 @@ impl TraceContext {
     }
 
     pub fn record(&self, key: impl Into<String>, value: impl Into<String>) -> &Self {
-        self.otel_ctx()
-            .span()
-            .set_attribute(KeyValue::new(key.into(), value.into()));
+        let key = key.into();
+        let value = value.into();
+
+        CAPTURED_ATTRIBUTES.with_borrow_mut(|captured| {
+            if let Some(captured) = captured {
+                captured.push((key.clone(), value.clone()));
+            }
+        });
+
+        self.otel_ctx().span().set_attribute(KeyValue::new(key, value));
         self
     }
 
+    /// Runs `f` and additionally returns all attributes recorded on this thread in the meantime,
+    /// making them available without an OTLP backend. Only suitable for synchronous code.
+    pub fn capture_attributes<R>(f: impl FnOnce() -> R) -> (R, Vec<(String, String)>) {
+        let outer = CAPTURED_ATTRIBUTES.replace(Some(Vec::new()));
+        let result = f();
+        let captured = CAPTURED_ATTRIBUTES.replace(outer).unwrap_or_default();
+
+        CAPTURED_ATTRIBUTES.with_borrow_mut(|outer| {
+            if let Some(outer) = outer {
+                outer.extend(captured.iter().cloned());
+            }
+        });
+
+        (result, captured)
+    }
+
     pub fn record_json(&self, key: impl Into<String>, value: &serde_json::Value) {
         if let Ok(value_str) = serde_json::to_string_pretty(&value) {
             self.record(key, value_str);
@@ -65,3 +94,35 @@ impl TraceContext {
         self.span.context()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captures_recorded_attributes_including_nested() {
+        let (inner, outer) = TraceContext::capture_attributes(|| {
+            TraceContext::current().record("outer", "1");
+            let (_, inner) = TraceContext::capture_attributes(|| {
+                TraceContext::current().record("inner", "2");
+            });
+            inner
+        });
+
+        assert_eq!(inner, vec![("inner".to_string(), "2".to_string())]);
+        assert_eq!(
+            outer,
+            vec![
+                ("outer".to_string(), "1".to_string()),
+                ("inner".to_string(), "2".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn nothing_is_captured_outside_of_capture() {
+        TraceContext::current().record("ignored", "1");
+        let (_, captured) = TraceContext::capture_attributes(|| ());
+        assert!(captured.is_empty());
+    }
+}
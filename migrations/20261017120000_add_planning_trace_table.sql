@@ -0,0 +1,10 @@
+CREATE TABLE planning_trace (
+    id BIGSERIAL PRIMARY KEY,
+    timestamp TIMESTAMPTZ NOT NULL,
+    resource VARCHAR NOT NULL,
+    correlation_id VARCHAR,
+    steps JSONB NOT NULL
+);
+
+CREATE INDEX idx_planning_trace_resource_timestamp ON planning_trace (resource, timestamp);
+CREATE INDEX idx_planning_trace_timestamp ON planning_trace (timestamp);
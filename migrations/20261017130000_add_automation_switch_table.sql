@@ -0,0 +1,7 @@
+CREATE TABLE automation_switch (
+    kind VARCHAR NOT NULL,
+    name VARCHAR NOT NULL,
+    enabled BOOLEAN NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (kind, name)
+);
@@ -0,0 +1,5 @@
+ALTER TABLE thing_command
+    ADD COLUMN attempts INT NOT NULL DEFAULT 1,
+    ADD COLUMN next_retry_at TIMESTAMPTZ;
+
+CREATE INDEX idx_thing_command_next_retry_at ON thing_command(next_retry_at) WHERE next_retry_at IS NOT NULL;
@@ -0,0 +1,13 @@
+CREATE TABLE hap_accessory_identity (
+    id INTEGER PRIMARY KEY DEFAULT 1 CHECK (id = 1),
+    device_id VARCHAR NOT NULL,
+    signing_key BYTEA NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE hap_pairing (
+    controller_id VARCHAR PRIMARY KEY,
+    public_key BYTEA NOT NULL,
+    admin BOOLEAN NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,11 @@
+CREATE TABLE device_state_quarantine (
+    id BIGSERIAL PRIMARY KEY,
+    channel VARCHAR NOT NULL,
+    name VARCHAR NOT NULL,
+    value DOUBLE PRECISION NOT NULL,
+    reason VARCHAR NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX device_state_quarantine_timestamp_idx ON device_state_quarantine (timestamp);
@@ -0,0 +1,13 @@
+-- Monthly thing_value partitions that were replaced by a downsampled copy
+CREATE TABLE thing_value_retention (
+    partition_name VARCHAR PRIMARY KEY,
+    raw_rows BIGINT NOT NULL,
+    kept_rows BIGINT NOT NULL,
+    raw_bytes BIGINT NOT NULL,
+    downsampled_bytes BIGINT NOT NULL,
+    archived BOOLEAN NOT NULL,
+    processed_at TIMESTAMPTZ NOT NULL
+);
+
+-- Raw partitions are moved here if archiving is enabled
+CREATE SCHEMA IF NOT EXISTS thing_value_archive;
@@ -0,0 +1,3 @@
+-- Failed pair setup attempts, pair setup is refused after too many
+ALTER TABLE hap_accessory_identity
+    ADD COLUMN failed_pair_setup_attempts INT NOT NULL DEFAULT 0;
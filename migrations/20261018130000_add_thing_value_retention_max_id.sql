@@ -0,0 +1,5 @@
+-- Rows with a higher id were added after downsampling, e.g. by an import, and are downsampled again
+ALTER TABLE thing_value_retention ADD COLUMN max_id BIGINT NOT NULL DEFAULT 0;
+
+-- Archived raw data still occupies space and doesn't count as reclaimed
+ALTER TABLE thing_value_retention ADD COLUMN archived_bytes BIGINT NOT NULL DEFAULT 0;